@@ -0,0 +1,39 @@
+use std::{io, path::PathBuf};
+
+/// Directory next to the executable that stores all collection data, shared with the GUI
+pub const APP_DATA_DIR: &str = "appdata";
+/// Name of the file that stores the current full trade list in the appdata directory
+pub const CURRENT_FULL_TRADE_LIST: &str = "current-full-trade-list.dek";
+
+/// Get the path to the appdata directory, either the given one or the default one next to the executable
+///
+/// # Errors
+///
+/// If the path to the executable cannot be determined
+pub fn appdata_dir(dir: Option<PathBuf>) -> io::Result<PathBuf> {
+    match dir {
+        Some(dir) => Ok(dir),
+        None => {
+            let mut appdata_dir = std::env::current_exe()?;
+            appdata_dir.pop();
+            appdata_dir.push(APP_DATA_DIR);
+            Ok(appdata_dir)
+        }
+    }
+}
+
+/// Like [appdata_dir] but requires that the directory exists
+///
+/// # Errors
+///
+/// If the appdata directory doesn't exist or its path cannot be determined
+pub fn existing_appdata_dir(dir: Option<PathBuf>) -> io::Result<PathBuf> {
+    let dir = appdata_dir(dir)?;
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Appdata directory {dir:?} doesn't exist, run `update` first"),
+        ));
+    }
+    Ok(dir)
+}
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+
+/// Headless front end for MTGO Collection Manager
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path to the appdata directory [default: `appdata` next to the executable, the same as the GUI]
+    #[arg(long, global = true, env = "MCM_APPDATA_DIR")]
+    pub appdata_dir: Option<PathBuf>,
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+    /// Increase log verbosity (-v: info, -vv: debug, -vvv: trace)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Download Scryfall and Goatbots data if any of it needs updating
+    Update,
+    /// Import a full trade list and save a new collection snapshot
+    Import {
+        /// Path to the `Full Trade List.dek`-file exported from MTGO
+        file: PathBuf,
+    },
+    /// Show statistics of the most recent collection snapshot
+    Stats,
+    /// List the cards in the most recent collection snapshot
+    List(ListArgs),
+    /// Show the sets of the most recent collection snapshot in release order
+    Sets,
+    /// Show the countdown to the release of the next MTGO set
+    NextRelease,
+    /// Show the cards that were added to the Goatbots card definitions after a set release
+    NewCards {
+        /// Only cards from this set, e.g. `MH3`
+        #[arg(long)]
+        set: Option<String>,
+    },
+    /// Show the differences between two collection snapshots
+    Diff {
+        /// The older snapshot (a path or a file name in the appdata directory)
+        old: PathBuf,
+        /// The newer snapshot (a path or a file name in the appdata directory)
+        new: PathBuf,
+    },
+    /// Show the quantity and price history of a card across all collection snapshots
+    History {
+        /// Card ID or exact card name (case-insensitive)
+        card: String,
+    },
+    /// Remove old history from the appdata directory according to its retention policy (`retention.toml`)
+    Cleanup {
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Only cards whose name contains this text (case-insensitive)
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Only cards from this set, e.g. `MH3` or `Modern Horizons 3`
+    #[arg(long)]
+    pub set: Option<String>,
+    /// Only cards of this rarity, e.g. `Mythic`
+    #[arg(long)]
+    pub rarity: Option<String>,
+    /// Only foil cards
+    #[arg(long, conflicts_with = "nonfoil")]
+    pub foil: bool,
+    /// Only non-foil cards
+    #[arg(long)]
+    pub nonfoil: bool,
+    /// Only cards with a Goatbots price of at least this many tix
+    #[arg(long)]
+    pub min_price: Option<f32>,
+    /// Only cards with a Goatbots price of at most this many tix
+    #[arg(long)]
+    pub max_price: Option<f32>,
+    /// Sort the cards by
+    #[arg(long, value_enum, default_value_t = SortKey::Name)]
+    pub sort: SortKey,
+    /// Sort in descending order
+    #[arg(long)]
+    pub desc: bool,
+    /// Show at most this many cards
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    Id,
+    Name,
+    Quantity,
+    /// Set release order, oldest first
+    Set,
+    Rarity,
+    Price,
+    CardhoarderPrice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
@@ -0,0 +1,93 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use mtgoupdater::{Collection, MtgoCard};
+
+use crate::{
+    appdata,
+    cli::{Cli, Command},
+    output::Table,
+};
+
+pub mod cleanup;
+pub mod diff;
+pub mod history;
+pub mod import;
+pub mod list;
+pub mod releases;
+pub mod sets;
+pub mod stats;
+pub mod update;
+
+/// Run the command given on the command line
+///
+/// # Returns
+///
+/// The output of the command as a [Table]
+///
+/// # Errors
+///
+/// Returns an error describing why the command failed
+pub fn run(cli: Cli) -> Result<Table, Box<dyn Error>> {
+    match cli.command {
+        Command::Update => update::update(&appdata::appdata_dir(cli.appdata_dir)?),
+        Command::Import { file } => {
+            import::import(&appdata::existing_appdata_dir(cli.appdata_dir)?, &file)
+        }
+        Command::Stats => {
+            let appdata_dir = appdata::existing_appdata_dir(cli.appdata_dir)?;
+            Ok(stats::stats(&latest_cards(&appdata_dir)?))
+        }
+        Command::List(args) => {
+            let appdata_dir = appdata::existing_appdata_dir(cli.appdata_dir)?;
+            Ok(list::list(latest_cards(&appdata_dir)?, &args))
+        }
+        Command::Sets => {
+            let appdata_dir = appdata::existing_appdata_dir(cli.appdata_dir)?;
+            Ok(sets::sets(&latest_cards(&appdata_dir)?))
+        }
+        Command::NextRelease => {
+            releases::next_release(&appdata::existing_appdata_dir(cli.appdata_dir)?)
+        }
+        Command::NewCards { set } => releases::new_cards(
+            &appdata::existing_appdata_dir(cli.appdata_dir)?,
+            set.as_deref(),
+        ),
+        Command::Diff { old, new } => {
+            let appdata_dir = appdata::existing_appdata_dir(cli.appdata_dir)?;
+            let old = load_snapshot(&resolve_snapshot(&appdata_dir, old))?;
+            let new = load_snapshot(&resolve_snapshot(&appdata_dir, new))?;
+            Ok(diff::diff(&old, &new))
+        }
+        Command::History { card } => {
+            history::history(&appdata::existing_appdata_dir(cli.appdata_dir)?, &card)
+        }
+        Command::Cleanup { dry_run } => {
+            cleanup::cleanup(&appdata::existing_appdata_dir(cli.appdata_dir)?, dry_run)
+        }
+    }
+}
+
+/// Load the cards of the most recent collection snapshot in the appdata directory
+fn latest_cards(appdata_dir: &Path) -> Result<Vec<MtgoCard>, Box<dyn Error>> {
+    let snapshot = mtgoupdater::most_recent_collection_snapshot(appdata_dir)?
+        .ok_or_else(|| format!("No collection snapshots in {appdata_dir:?}, run `import` first"))?;
+    load_snapshot(&snapshot)
+}
+
+fn load_snapshot(path: &Path) -> Result<Vec<MtgoCard>, Box<dyn Error>> {
+    let mut collection = Collection::from_json_file(path)
+        .map_err(|e| format!("Failed to load snapshot {path:?}: {e}"))?;
+    Ok(collection.take_cards())
+}
+
+/// A snapshot argument can be a path or the name of a snapshot in the appdata directory
+fn resolve_snapshot(appdata_dir: &Path, snapshot: PathBuf) -> PathBuf {
+    if snapshot.exists() {
+        snapshot
+    } else {
+        appdata_dir.join(snapshot)
+    }
+}
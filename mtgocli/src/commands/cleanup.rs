@@ -0,0 +1,99 @@
+use std::{error::Error, path::Path};
+
+use chrono::Utc;
+use mtgoupdater::retention::{Location, RetentionPlan, RetentionPolicy};
+
+use crate::output::{Cell, Table};
+
+/// Remove old history from the appdata directory according to its retention policy (`retention.toml`)
+///
+/// The policy is applied even if it isn't enabled, that only decides whether it's also applied after each update.
+///
+/// # Arguments
+///
+/// * `appdata_dir` - The appdata directory
+/// * `dry_run` - Only report what would be removed
+///
+/// # Returns
+///
+/// A [Table] with a row for each file or entry of a ZIP-file that is (or would be) removed
+pub fn cleanup(appdata_dir: &Path, dry_run: bool) -> Result<Table, Box<dyn Error>> {
+    let policy = RetentionPolicy::load(appdata_dir)?;
+    let plan = RetentionPlan::new(appdata_dir, &policy, Utc::now())?;
+    if !dry_run {
+        plan.apply()?;
+    }
+    log::info!(
+        "{} {} of {} bytes",
+        if dry_run { "Would free" } else { "Freed" },
+        plan.freed_bytes(),
+        plan.total_bytes()
+    );
+    Ok(plan_table(appdata_dir, &plan))
+}
+
+fn plan_table(appdata_dir: &Path, plan: &RetentionPlan) -> Table {
+    let mut table = Table::new(vec!["path", "bytes", "reason"]);
+    for action in plan.actions() {
+        let path = match &action.location {
+            Location::File(path) => relative(appdata_dir, path),
+            Location::Zipped { zip, name } => format!("{}:{name}", relative(appdata_dir, zip)),
+        };
+        table.push_row(vec![
+            path.into(),
+            Cell::Int(action.bytes),
+            action.reason.to_string().into(),
+        ]);
+    }
+    table
+}
+
+fn relative(appdata_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(appdata_dir)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_cleanup_dry_run() -> TestResult {
+        let dir = TempDir::new()?;
+        let snapshot = |age: chrono::Duration| {
+            format!(
+                "mtgo-cards_{}",
+                (Utc::now() - age).format("%Y-%m-%dT%H%M%SZ")
+            )
+        };
+        // Two snapshots of the same day that is old enough to be thinned to one
+        let day = chrono::Duration::days(60);
+        let old_snapshot = snapshot(day + chrono::Duration::seconds(1));
+        fs::write(dir.child(&old_snapshot), "[]")?;
+        fs::write(dir.child(snapshot(day)), "[]")?;
+        fs::write(dir.child(snapshot(chrono::Duration::zero())), "[]")?;
+
+        let table = cleanup(dir.path(), true)?;
+        assert_eq!(
+            table.rows(),
+            [vec![
+                Cell::from(old_snapshot.as_str()),
+                Cell::Int(2),
+                Cell::from("thinned to daily")
+            ]]
+        );
+        // Nothing is changed on a dry run
+        assert!(dir.child(&old_snapshot).exists());
+
+        cleanup(dir.path(), false)?;
+        assert!(!dir.child(&old_snapshot).exists());
+        assert!(cleanup(dir.path(), true)?.rows().is_empty());
+        Ok(())
+    }
+}
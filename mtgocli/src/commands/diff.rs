@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use mtgoupdater::MtgoCard;
+
+use crate::output::{Cell, Table};
+
+/// Compare two collection snapshots
+///
+/// # Returns
+///
+/// A [Table] with a row for each card that was added, removed, or had its quantity or price changed, ordered by ID
+pub fn diff(old: &[MtgoCard], new: &[MtgoCard]) -> Table {
+    let mut by_id: BTreeMap<u32, (Option<&MtgoCard>, Option<&MtgoCard>)> = BTreeMap::new();
+    for card in old {
+        by_id.entry(card.id).or_default().0 = Some(card);
+    }
+    for card in new {
+        by_id.entry(card.id).or_default().1 = Some(card);
+    }
+
+    let mut table = Table::new(vec![
+        "id",
+        "name",
+        "change",
+        "old_quantity",
+        "new_quantity",
+        "old_goatbots_price",
+        "new_goatbots_price",
+        "old_cardhoarder_price",
+        "new_cardhoarder_price",
+    ]);
+    for (id, (old, new)) in by_id {
+        let change = match (old, new) {
+            (None, Some(_)) => "added",
+            (Some(_), None) => "removed",
+            (Some(o), Some(n)) if o.quantity != n.quantity => "quantity",
+            (Some(o), Some(n))
+                if o.goatbots_price != n.goatbots_price || o.scryfall_price != n.scryfall_price =>
+            {
+                "price"
+            }
+            _ => continue,
+        };
+        let name = new.or(old).map(|c| c.name.as_ref()).unwrap_or_default();
+        let quantity =
+            |c: Option<&MtgoCard>| c.map_or(Cell::Text(String::new()), |c| c.quantity.into());
+        table.push_row(vec![
+            id.into(),
+            name.into(),
+            change.into(),
+            quantity(old),
+            quantity(new),
+            old.map(|c| c.goatbots_price).into(),
+            new.map(|c| c.goatbots_price).into(),
+            old.and_then(|c| c.scryfall_price).into(),
+            new.and_then(|c| c.scryfall_price).into(),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn card(id: u32, quantity: u32, goatbots_price: f32) -> MtgoCard {
+        MtgoCard {
+            id,
+            quantity,
+            name: format!("Card {id}").into(),
+            goatbots_price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = vec![
+            card(1, 10, 1.0),
+            card(2, 1, 0.5),
+            card(3, 1, 2.0),
+            card(4, 4, 0.1),
+        ];
+        let new = vec![
+            card(1, 10, 1.0),
+            card(3, 2, 2.0),
+            card(4, 4, 0.2),
+            card(5, 1, 3.0),
+        ];
+        let table = diff(&old, &new);
+
+        let changes: Vec<(Cell, Cell)> = table
+            .rows()
+            .iter()
+            .map(|r| (r[0].clone(), r[2].clone()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (Cell::Int(2), "removed".into()),
+                (Cell::Int(3), "quantity".into()),
+                (Cell::Int(4), "price".into()),
+                (Cell::Int(5), "added".into()),
+            ]
+        );
+        assert_eq!(table.rows()[0][4], Cell::Text(String::new()));
+    }
+}
@@ -0,0 +1,54 @@
+use std::{error::Error, path::Path};
+
+use mtgoupdater::{Collection, MtgoCard};
+
+use crate::output::Table;
+
+/// Show the quantity and prices of a card in each of the collection snapshots in the appdata directory
+///
+/// # Arguments
+///
+/// * `card` - Card ID or exact card name (case-insensitive), a name can match several IDs e.g. foil and non-foil versions
+///
+/// # Errors
+///
+/// If a snapshot cannot be loaded or the card isn't in any of the snapshots
+pub fn history(appdata_dir: &Path, card: &str) -> Result<Table, Box<dyn Error>> {
+    let id: Option<u32> = card.parse().ok();
+    let matches = |c: &MtgoCard| match id {
+        Some(id) => c.id == id,
+        None => c.name.eq_ignore_ascii_case(card),
+    };
+
+    let mut table = Table::new(vec![
+        "timestamp",
+        "id",
+        "name",
+        "set",
+        "foil",
+        "quantity",
+        "goatbots_price",
+        "cardhoarder_price",
+    ]);
+    for (snapshot, timestamp) in mtgoupdater::collection_snapshots(appdata_dir)? {
+        let collection = Collection::from_json_file(&snapshot)
+            .map_err(|e| format!("Failed to load snapshot {snapshot:?}: {e}"))?;
+        for c in collection.cards().iter().filter(|c| matches(c)) {
+            table.push_row(vec![
+                timestamp.to_rfc3339().into(),
+                c.id.into(),
+                c.name.as_ref().into(),
+                c.set.as_ref().into(),
+                c.foil.into(),
+                c.quantity.into(),
+                c.goatbots_price.into(),
+                c.scryfall_price.into(),
+            ]);
+        }
+    }
+
+    if table.rows().is_empty() {
+        return Err(format!("No card matching {card:?} in any collection snapshot").into());
+    }
+    Ok(table)
+}
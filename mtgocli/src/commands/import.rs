@@ -0,0 +1,40 @@
+use std::{error::Error, fs, path::Path};
+
+use mtgogetter::card_data::CardDataPaths;
+
+use crate::{appdata::CURRENT_FULL_TRADE_LIST, output::Table};
+
+use super::stats::stats;
+
+/// Import a full trade list into the appdata directory and save a new collection snapshot
+///
+/// # Returns
+///
+/// The statistics of the imported collection
+///
+/// # Errors
+///
+/// If the file isn't a valid full trade list, the card data is missing, or the parsing fails
+pub fn import(appdata_dir: &Path, full_trade_list: &Path) -> Result<Table, Box<dyn Error>> {
+    let summary = mtgoupdater::validate_full_trade_list(full_trade_list)
+        .map_err(|e| format!("{full_trade_list:?} is not an MTGO full trade list: {e}"))?;
+    log::info!(
+        "Full trade list validated: {} unique cards, {} total",
+        summary.unique_cards,
+        summary.total_quantity
+    );
+    let card_data =
+        CardDataPaths::find(appdata_dir).map_err(|e| format!("{e}, run `update` first"))?;
+
+    let current_trade_list = appdata_dir.join(CURRENT_FULL_TRADE_LIST);
+    fs::copy(full_trade_list, &current_trade_list)?;
+
+    let cards = mtgoupdater::parse_full(
+        &current_trade_list,
+        card_data.scryfall_path(),
+        card_data.card_definitions_path(),
+        card_data.price_history_path(),
+        Some(appdata_dir),
+    )?;
+    Ok(stats(&cards))
+}
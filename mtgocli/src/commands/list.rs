@@ -0,0 +1,190 @@
+use std::cmp::Ordering;
+
+use mtgoupdater::{set_catalog::cmp_release_order, MtgoCard};
+
+use crate::{
+    cli::{ListArgs, SortKey},
+    output::Table,
+};
+
+/// List the cards that match the filters in the given [ListArgs], sorted as requested
+pub fn list(mut cards: Vec<MtgoCard>, args: &ListArgs) -> Table {
+    cards.retain(|c| matches_filters(c, args));
+    cards.sort_by(|a, b| {
+        let ord = compare(a, b, args.sort);
+        if args.desc {
+            ord.reverse()
+        } else {
+            ord
+        }
+    });
+    if let Some(limit) = args.limit {
+        cards.truncate(limit);
+    }
+
+    let mut table = Table::new(vec![
+        "id",
+        "quantity",
+        "name",
+        "set",
+        "rarity",
+        "foil",
+        "goatbots_price",
+        "cardhoarder_price",
+    ]);
+    for c in cards {
+        table.push_row(vec![
+            c.id.into(),
+            c.quantity.into(),
+            c.name.as_ref().into(),
+            c.set.as_ref().into(),
+            c.rarity.to_string().into(),
+            c.foil.into(),
+            c.goatbots_price.into(),
+            c.scryfall_price.into(),
+        ]);
+    }
+    table
+}
+
+fn matches_filters(card: &MtgoCard, args: &ListArgs) -> bool {
+    if let Some(name) = &args.name {
+        if !card.name.to_lowercase().contains(&name.to_lowercase()) {
+            return false;
+        }
+    }
+    if let Some(set) = &args.set {
+        let name_matches = card
+            .set_name
+            .as_deref()
+            .is_some_and(|name| name.eq_ignore_ascii_case(set));
+        if !card.set.eq_ignore_ascii_case(set) && !name_matches {
+            return false;
+        }
+    }
+    if let Some(rarity) = &args.rarity {
+        if !card.rarity.to_string().eq_ignore_ascii_case(rarity) {
+            return false;
+        }
+    }
+    if (args.foil && !card.foil) || (args.nonfoil && card.foil) {
+        return false;
+    }
+    if args.min_price.is_some_and(|min| card.goatbots_price < min) {
+        return false;
+    }
+    if args.max_price.is_some_and(|max| card.goatbots_price > max) {
+        return false;
+    }
+    true
+}
+
+fn compare(a: &MtgoCard, b: &MtgoCard, key: SortKey) -> Ordering {
+    let ord = match key {
+        SortKey::Id => a.id.cmp(&b.id),
+        SortKey::Name => a.name.cmp(&b.name),
+        SortKey::Quantity => a.quantity.cmp(&b.quantity),
+        SortKey::Set => cmp_release_order(a, b),
+        SortKey::Rarity => a.rarity.cmp(&b.rarity),
+        SortKey::Price => a.goatbots_price.total_cmp(&b.goatbots_price),
+        // Cards without a Cardhoarder price sort first
+        SortKey::CardhoarderPrice => match (a.scryfall_price, b.scryfall_price) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        },
+    };
+    // Ties are broken by ID to make the output deterministic
+    ord.then_with(|| a.id.cmp(&b.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Cell;
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        args: ListArgs,
+    }
+
+    fn args(args: &[&str]) -> ListArgs {
+        TestCli::parse_from(std::iter::once("list").chain(args.iter().copied())).args
+    }
+
+    fn cards() -> Vec<MtgoCard> {
+        vec![
+            MtgoCard {
+                id: 31745,
+                quantity: 1,
+                name: "Noble Hierarch".into(),
+                set: "CON".into(),
+                goatbots_price: 0.37,
+                set_name: Some("Conflux".into()),
+                set_released_at: "2009-02-06".parse().ok(),
+                ..Default::default()
+            },
+            MtgoCard {
+                id: 348,
+                quantity: 4,
+                name: "Black Lotus".into(),
+                set: "VMA".into(),
+                foil: true,
+                goatbots_price: 419.99,
+                set_name: Some("Vintage Masters".into()),
+                set_released_at: "2014-06-16".parse().ok(),
+                ..Default::default()
+            },
+            MtgoCard {
+                id: 40516,
+                quantity: 2,
+                name: "Windfall".into(),
+                set: "CMD".into(),
+                goatbots_price: 1.03,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn ids(table: &Table) -> Vec<Cell> {
+        table.rows().iter().map(|r| r[0].clone()).collect()
+    }
+
+    #[test]
+    fn test_list_sorted_by_name() {
+        let table = list(cards(), &args(&[]));
+        assert_eq!(
+            ids(&table),
+            vec![Cell::Int(348), Cell::Int(31745), Cell::Int(40516)]
+        );
+    }
+
+    #[test]
+    fn test_list_filters_and_sorting() {
+        let table = list(cards(), &args(&["--nonfoil", "--sort", "price", "--desc"]));
+        assert_eq!(ids(&table), vec![Cell::Int(40516), Cell::Int(31745)]);
+
+        let table = list(cards(), &args(&["--name", "LOTUS"]));
+        assert_eq!(ids(&table), vec![Cell::Int(348)]);
+
+        let table = list(
+            cards(),
+            &args(&["--min-price", "0.5", "--sort", "quantity", "--limit", "1"]),
+        );
+        assert_eq!(ids(&table), vec![Cell::Int(40516)]);
+
+        let table = list(cards(), &args(&["--set", "con"]));
+        assert_eq!(ids(&table), vec![Cell::Int(31745)]);
+        let table = list(cards(), &args(&["--set", "vintage masters"]));
+        assert_eq!(ids(&table), vec![Cell::Int(348)]);
+
+        // Cards of sets that aren't in the set catalog sort last
+        let table = list(cards(), &args(&["--sort", "set"]));
+        assert_eq!(
+            ids(&table),
+            vec![Cell::Int(31745), Cell::Int(348), Cell::Int(40516)]
+        );
+    }
+}
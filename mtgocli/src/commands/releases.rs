@@ -0,0 +1,136 @@
+use std::{error::Error, path::Path};
+
+use chrono::Utc;
+use mtgogetter::releases::{NewCard, NewCards, ReleaseCountdown};
+
+use crate::output::Table;
+
+/// Show the countdown to the release of the next MTGO set, as recorded by the last update
+///
+/// # Errors
+///
+/// Returns an error if the fetch log cannot be loaded
+pub fn next_release(appdata_dir: &Path) -> Result<Table, Box<dyn Error>> {
+    let fetch_log = mtgogetter::load_fetch_log(appdata_dir)?;
+    Ok(countdown_table(
+        fetch_log.next_release_countdown(Utc::now().date_naive()),
+    ))
+}
+
+fn countdown_table(countdown: Option<ReleaseCountdown>) -> Table {
+    let mut table = Table::new(vec!["set", "name", "released_at", "days_left"]);
+    if let Some(c) = countdown {
+        table.push_row(vec![
+            c.mtgo_code.to_uppercase().into(),
+            c.name.into(),
+            c.released_at.to_string().into(),
+            // The set is out if the sets weren't updated since the release
+            usize::try_from(c.days_left).unwrap_or(0).into(),
+        ]);
+    }
+    table
+}
+
+/// Show the cards that were added to the card definitions since they were first downloaded, optionally only those of
+/// one set
+///
+/// # Errors
+///
+/// Returns an error if the recorded new cards cannot be loaded
+pub fn new_cards(appdata_dir: &Path, set: Option<&str>) -> Result<Table, Box<dyn Error>> {
+    let new_cards = NewCards::load(appdata_dir)?;
+    Ok(match set {
+        Some(set) => new_cards_table(new_cards.in_set(set)),
+        None => new_cards_table(new_cards.iter()),
+    })
+}
+
+fn new_cards_table<'c>(cards: impl Iterator<Item = (u32, &'c NewCard)>) -> Table {
+    let mut cards: Vec<(u32, &NewCard)> = cards.collect();
+    cards.sort_by(|(a_id, a), (b_id, b)| a.cardset.cmp(&b.cardset).then(a_id.cmp(b_id)));
+
+    let mut table = Table::new(vec![
+        "id",
+        "name",
+        "set",
+        "rarity",
+        "foil",
+        "first_price",
+        "detected_at",
+    ]);
+    for (id, c) in cards {
+        table.push_row(vec![
+            id.into(),
+            c.name.as_str().into(),
+            c.cardset.as_str().into(),
+            c.rarity.to_string().into(),
+            c.foil.into(),
+            c.first_price.into(),
+            c.detected_at.format("%Y-%m-%d %H:%M").to_string().into(),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Cell;
+    use chrono::NaiveDate;
+    use mtgogetter::fetch_log::CardInfoMetaData;
+    use mtgoupdater::Rarity;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_countdown_table() {
+        let countdown = ReleaseCountdown::new(
+            "Modern Horizons 3".to_string(),
+            "mh3".to_string(),
+            NaiveDate::from_ymd_opt(2024, 6, 14).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+        );
+        let table = countdown_table(Some(countdown));
+        assert_eq!(
+            table.rows(),
+            [vec![
+                Cell::Text("MH3".into()),
+                Cell::Text("Modern Horizons 3".into()),
+                Cell::Text("2024-06-14".into()),
+                Cell::Int(13),
+            ]]
+        );
+        let unknown = CardInfoMetaData::new().next_release_countdown(Utc::now().date_naive());
+        assert!(countdown_table(unknown).rows().is_empty());
+    }
+
+    #[test]
+    fn test_new_cards_table_sorted_by_set() {
+        let card = |name: &str, cardset: &str| {
+            mtgogetter::releases::GoatBotsCard::new(
+                name.to_string(),
+                cardset.to_string(),
+                Rarity::Mythic,
+                false,
+            )
+        };
+        let current = HashMap::from([
+            (125002, card("Ocelot Pride", "MH3")),
+            (125000, card("Ugin's Labyrinth", "MH3")),
+            (120000, card("Sheoldred", "DMU")),
+        ]);
+        let mut new_cards = NewCards::default();
+        new_cards.record_added(&HashMap::new(), &current, Utc::now());
+
+        let ids =
+            |table: &Table| -> Vec<Cell> { table.rows().iter().map(|r| r[0].clone()).collect() };
+        assert_eq!(
+            ids(&new_cards_table(new_cards.iter())),
+            [Cell::Int(120000), Cell::Int(125000), Cell::Int(125002)]
+        );
+        assert_eq!(
+            ids(&new_cards_table(new_cards.in_set("mh3"))),
+            [Cell::Int(125000), Cell::Int(125002)]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use mtgoupdater::{set_catalog::group_by_set, MtgoCard};
+
+use crate::output::Table;
+
+/// Summarize the cards of each set, in the order the sets were released
+///
+/// Sets without a name or release date weren't found in the set catalog, e.g. because `update` hasn't been run since
+/// the catalog was introduced.
+pub fn sets(cards: &[MtgoCard]) -> Table {
+    let mut table = Table::new(vec![
+        "set",
+        "name",
+        "released_at",
+        "unique_cards",
+        "total_cards",
+        "goatbots_value",
+    ]);
+    for group in group_by_set(cards) {
+        let total_cards: u32 = group.cards.iter().map(|c| c.quantity).sum();
+        let goatbots_value: f32 = group
+            .cards
+            .iter()
+            .map(|c| c.goatbots_price * c.quantity as f32)
+            .sum();
+        table.push_row(vec![
+            group.set.into(),
+            group.name.unwrap_or_default().into(),
+            group
+                .released_at
+                .map(|d| d.to_string())
+                .unwrap_or_default()
+                .into(),
+            group.cards.len().into(),
+            total_cards.into(),
+            ((goatbots_value * 100.).round() / 100.).into(),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Cell;
+    use pretty_assertions::assert_eq;
+
+    fn card(id: u32, set: &str, released_at: Option<&str>) -> MtgoCard {
+        MtgoCard {
+            id,
+            quantity: 2,
+            set: set.into(),
+            goatbots_price: 0.5,
+            set_name: released_at.map(|_| format!("Set {set}").into()),
+            set_released_at: released_at.map(|d| d.parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sets_in_release_order() {
+        let cards = vec![
+            card(1, "EVENT", None),
+            card(2, "MH3", Some("2024-06-14")),
+            card(3, "1E", Some("1993-08-05")),
+            card(4, "MH3", Some("2024-06-14")),
+        ];
+        let table = sets(&cards);
+        let rows: Vec<(Cell, Cell, Cell, Cell)> = table
+            .rows()
+            .iter()
+            .map(|r| (r[0].clone(), r[2].clone(), r[3].clone(), r[5].clone()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("1E".into(), "1993-08-05".into(), 1usize.into(), 1.0.into()),
+                ("MH3".into(), "2024-06-14".into(), 2usize.into(), 2.0.into()),
+                ("EVENT".into(), "".into(), 1usize.into(), 1.0.into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use mtgoupdater::{MtgoCard, Rarity};
+
+use crate::output::Table;
+
+/// Summarize a collection
+pub fn stats(cards: &[MtgoCard]) -> Table {
+    // Event tickets (ID 1) are included in the totals but not in the rarity breakdown
+    let total_cards: u32 = cards.iter().map(|c| c.quantity).sum();
+    let event_tickets: u32 = cards.iter().filter(|c| c.id == 1).map(|c| c.quantity).sum();
+    let foils: u32 = cards.iter().filter(|c| c.foil).map(|c| c.quantity).sum();
+    let goatbots_value: f32 = cards
+        .iter()
+        .map(|c| c.goatbots_price * c.quantity as f32)
+        .sum();
+    let cardhoarder_value: f32 = cards
+        .iter()
+        .filter_map(|c| c.scryfall_price.map(|p| p * c.quantity as f32))
+        .sum();
+
+    let mut table = Table::new(vec!["stat", "value"]);
+    table.push_row(vec!["Unique cards".into(), cards.len().into()]);
+    table.push_row(vec!["Total cards".into(), total_cards.into()]);
+    table.push_row(vec!["Event tickets".into(), event_tickets.into()]);
+    table.push_row(vec!["Foil cards".into(), foils.into()]);
+    for rarity in [
+        Rarity::Common,
+        Rarity::Uncommon,
+        Rarity::Rare,
+        Rarity::Mythic,
+        Rarity::Booster,
+    ] {
+        let count: u32 = cards
+            .iter()
+            .filter(|c| c.id != 1 && c.rarity == rarity)
+            .map(|c| c.quantity)
+            .sum();
+        table.push_row(vec![format!("{rarity} cards").into(), count.into()]);
+    }
+    table.push_row(vec![
+        "Total value Goatbots (tix)".into(),
+        round_tix(goatbots_value).into(),
+    ]);
+    table.push_row(vec![
+        "Total value Cardhoarder (tix)".into(),
+        round_tix(cardhoarder_value).into(),
+    ]);
+    table
+}
+
+/// Round to 2 decimals to avoid float noise from summing many prices
+fn round_tix(tix: f32) -> f32 {
+    (tix * 100.).round() / 100.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Cell;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_stats() {
+        let cards = vec![
+            MtgoCard {
+                id: 1,
+                quantity: 10,
+                name: "Event Ticket".into(),
+                rarity: Rarity::None,
+                goatbots_price: 1.0,
+                scryfall_price: Some(1.0),
+                ..Default::default()
+            },
+            MtgoCard {
+                id: 348,
+                quantity: 2,
+                name: "Black Lotus".into(),
+                rarity: Rarity::Mythic,
+                foil: true,
+                goatbots_price: 0.5,
+                scryfall_price: None,
+                ..Default::default()
+            },
+        ];
+        let table = stats(&cards);
+        let value = |name: &str| {
+            table
+                .rows()
+                .iter()
+                .find(|r| r[0] == Cell::from(name))
+                .map(|r| r[1].clone())
+                .unwrap()
+        };
+        assert_eq!(value("Total cards"), Cell::Int(12));
+        assert_eq!(value("Event tickets"), Cell::Int(10));
+        assert_eq!(value("Foil cards"), Cell::Int(2));
+        assert_eq!(value("Mythic cards"), Cell::Int(2));
+        assert_eq!(value("Total value Goatbots (tix)"), Cell::Price(Some(11.0)));
+        assert_eq!(
+            value("Total value Cardhoarder (tix)"),
+            Cell::Price(Some(10.0))
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use std::{error::Error, fmt::Display, fs, path::Path};
+
+use mtgogetter::fetch_log::CardInfoMetaData;
+
+use crate::output::{Cell, Table};
+
+/// Download the card data that needs updating to the appdata directory
+///
+/// # Returns
+///
+/// A [Table] with the timestamps of the card data after the update
+pub fn update(appdata_dir: &Path) -> Result<Table, Box<dyn Error>> {
+    fs::create_dir_all(appdata_dir)?;
+    mtgogetter::fetch_all(appdata_dir.to_path_buf())?;
+
+    let fetch_log =
+        CardInfoMetaData::from_toml_file(&appdata_dir.join(CardInfoMetaData::FILENAME))?;
+    let mut table = Table::new(vec!["data", "updated_at"]);
+    table.push_row(vec![
+        "Goatbots prices".into(),
+        timestamp(fetch_log.goatbots_prices_updated_at()),
+    ]);
+    table.push_row(vec![
+        "Scryfall bulk data".into(),
+        timestamp(fetch_log.scryfall_bulk_data_updated_at()),
+    ]);
+    Ok(table)
+}
+
+fn timestamp(t: Option<impl Display>) -> Cell {
+    t.map(|t| t.to_string()).unwrap_or_default().into()
+}
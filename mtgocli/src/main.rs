@@ -0,0 +1,32 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use cli::Cli;
+
+mod appdata;
+mod cli;
+mod commands;
+mod output;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Warnings and errors are always logged to stderr, stdout is reserved for the command output
+    if let Err(e) = stderrlog::new()
+        .verbosity(usize::from(cli.verbose) + 1)
+        .init()
+    {
+        eprintln!("Failed to initialize logging: {e}");
+    }
+
+    let format = cli.format;
+    match commands::run(cli) {
+        Ok(table) => {
+            print!("{}", table.render(format));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
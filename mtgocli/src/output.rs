@@ -0,0 +1,276 @@
+use std::fmt::Display;
+
+use serde_json::{Map, Number, Value};
+
+use crate::cli::OutputFormat;
+
+/// A single value in an output [Table]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Int(u64),
+    /// A price in tix, [None] if it's not available
+    Price(Option<f32>),
+    Bool(bool),
+}
+
+impl Cell {
+    fn is_numeric(&self) -> bool {
+        matches!(self, Cell::Int(_) | Cell::Price(_))
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Cell::Text(s) => Value::String(s.clone()),
+            Cell::Int(i) => Value::Number((*i).into()),
+            // Go through the shortest string representation of the f32 to avoid e.g. 0.3700000047683716
+            Cell::Price(Some(p)) => p
+                .to_string()
+                .parse()
+                .ok()
+                .and_then(Number::from_f64)
+                .map_or(Value::Null, Value::Number),
+            Cell::Price(None) => Value::Null,
+            Cell::Bool(b) => Value::Bool(*b),
+        }
+    }
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cell::Text(s) => f.write_str(s),
+            Cell::Int(i) => write!(f, "{i}"),
+            Cell::Price(Some(p)) => write!(f, "{p}"),
+            Cell::Price(None) => Ok(()),
+            Cell::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(s: &str) -> Self {
+        Cell::Text(s.to_owned())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(s: String) -> Self {
+        Cell::Text(s)
+    }
+}
+
+impl From<u32> for Cell {
+    fn from(i: u32) -> Self {
+        Cell::Int(i.into())
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(i: usize) -> Self {
+        Cell::Int(i as u64)
+    }
+}
+
+impl From<f32> for Cell {
+    fn from(p: f32) -> Self {
+        Cell::Price(Some(p))
+    }
+}
+
+impl From<Option<f32>> for Cell {
+    fn from(p: Option<f32>) -> Self {
+        Cell::Price(p)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(b: bool) -> Self {
+        Cell::Bool(b)
+    }
+}
+
+/// Tabular output of a command that can be rendered in any [OutputFormat]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new(headers: Vec<&'static str>) -> Self {
+        Self {
+            headers,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row, it must have a cell for each header
+    pub fn push_row(&mut self, row: Vec<Cell>) {
+        debug_assert_eq!(row.len(), self.headers.len());
+        self.rows.push(row);
+    }
+
+    pub fn rows(&self) -> &[Vec<Cell>] {
+        &self.rows
+    }
+
+    /// Render the table in the given [OutputFormat]
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Table => self.render_table(),
+            OutputFormat::Json => self.render_json(),
+            OutputFormat::Csv => self.render_csv(),
+        }
+    }
+
+    fn render_table(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|c| c.to_string()).collect())
+            .collect();
+        let widths: Vec<usize> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                cells
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([h.chars().count()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let mut out = String::new();
+        let header: Vec<String> = self
+            .headers
+            .iter()
+            .zip(&widths)
+            .map(|(h, w)| format!("{h:<w$}"))
+            .collect();
+        out.push_str(header.join("  ").trim_end());
+        out.push('\n');
+        let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        out.push_str(&separator.join("  "));
+        out.push('\n');
+        for (row, row_cells) in cells.iter().zip(&self.rows) {
+            let line: Vec<String> = row
+                .iter()
+                .zip(row_cells)
+                .zip(&widths)
+                .map(|((s, cell), w)| {
+                    if cell.is_numeric() {
+                        format!("{s:>w$}")
+                    } else {
+                        format!("{s:<w$}")
+                    }
+                })
+                .collect();
+            out.push_str(line.join("  ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    fn render_json(&self) -> String {
+        let objects: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let obj: Map<String, Value> = self
+                    .headers
+                    .iter()
+                    .zip(row)
+                    .map(|(h, c)| ((*h).to_owned(), c.to_json()))
+                    .collect();
+                Value::Object(obj)
+            })
+            .collect();
+        let mut out = serde_json::to_string_pretty(&objects).unwrap_or_default();
+        out.push('\n');
+        out
+    }
+
+    fn render_csv(&self) -> String {
+        let mut out = String::new();
+        let header: Vec<String> = self.headers.iter().map(|h| csv_field(h)).collect();
+        out.push_str(&header.join(","));
+        out.push('\n');
+        for row in &self.rows {
+            let fields: Vec<String> = row.iter().map(|c| csv_field(&c.to_string())).collect();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn table() -> Table {
+        let mut table = Table::new(vec!["id", "name", "price"]);
+        table.push_row(vec![1u32.into(), "Event Ticket".into(), 1.0f32.into()]);
+        table.push_row(vec![
+            31745u32.into(),
+            "Noble Hierarch, \"Exalted\"".into(),
+            0.37f32.into(),
+        ]);
+        table.push_row(vec![235u32.into(), "Swamp".into(), None.into()]);
+        table
+    }
+
+    #[test]
+    fn test_render_table() {
+        assert_eq!(
+            table().render(OutputFormat::Table),
+            "\
+id     name                       price
+-----  -------------------------  -----
+    1  Event Ticket                   1
+31745  Noble Hierarch, \"Exalted\"   0.37
+  235  Swamp
+"
+        );
+    }
+
+    #[test]
+    fn test_render_csv() {
+        assert_eq!(
+            table().render(OutputFormat::Csv),
+            "\
+id,name,price
+1,Event Ticket,1
+31745,\"Noble Hierarch, \"\"Exalted\"\"\",0.37
+235,Swamp,
+"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json: Value = serde_json::from_str(&table().render(OutputFormat::Json)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"id": 1, "name": "Event Ticket", "price": 1.0},
+                {"id": 31745, "name": "Noble Hierarch, \"Exalted\"", "price": 0.37},
+                {"id": 235, "name": "Swamp", "price": null},
+            ])
+        );
+    }
+}
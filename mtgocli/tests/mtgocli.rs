@@ -0,0 +1,135 @@
+use std::fs;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use temp_dir::TempDir;
+use testresult::TestResult;
+
+const FULL_TRADE_LIST: &str = "../test/test-data/mtgo/Full Trade List-small-5cards.dek";
+
+/// Create an appdata directory with the small card data fixtures
+fn setup_appdata() -> TestResult<TempDir> {
+    let appdata = TempDir::new()?;
+    for (src, dst) in [
+        (
+            "../test/test-data/scryfall/default-cards-small-5cards.json",
+            "scryfall-cards.json",
+        ),
+        (
+            "../test/test-data/goatbots/card-defs-small-5cards.json",
+            "card-definitions.json",
+        ),
+        (
+            "../test/test-data/goatbots/price-hist-small-5cards.json",
+            "price-history.json",
+        ),
+        (
+            "../test/test-data/mtgogetter-out/fetch_log.toml",
+            "fetch_log.toml",
+        ),
+    ] {
+        fs::copy(src, appdata.child(dst))?;
+    }
+    Ok(appdata)
+}
+
+fn mtgocli(appdata: &TempDir) -> TestResult<Command> {
+    let mut cmd = Command::cargo_bin("mtgocli")?;
+    cmd.arg("--appdata-dir").arg(appdata.path());
+    Ok(cmd)
+}
+
+#[test]
+fn test_import_then_list_stats_and_history() -> TestResult {
+    let appdata = setup_appdata()?;
+
+    mtgocli(&appdata)?
+        .args(["import", FULL_TRADE_LIST])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total cards"));
+    assert!(appdata.child("current-full-trade-list.dek").exists());
+
+    let out = mtgocli(&appdata)?
+        .args([
+            "list", "--format", "csv", "--sort", "price", "--desc", "--limit", "2",
+        ])
+        .output()?;
+    assert!(out.status.success());
+    let csv = String::from_utf8(out.stdout)?;
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("id,quantity,name,set,rarity,foil,goatbots_price,cardhoarder_price")
+    );
+    assert!(
+        lines
+            .next()
+            .is_some_and(|l| l.starts_with("1,453,Event Ticket,")),
+        "{csv}"
+    );
+    assert_eq!(lines.count(), 1);
+
+    let out = mtgocli(&appdata)?
+        .args(["stats", "--format", "json"])
+        .output()?;
+    assert!(out.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(
+        stats[1],
+        serde_json::json!({"stat": "Total cards", "value": 457})
+    );
+
+    mtgocli(&appdata)?
+        .args(["history", "noble hierarch"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("31745"));
+    mtgocli(&appdata)?
+        .args(["history", "No Such Card"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No card matching"));
+
+    let snapshot = mtgoupdater::most_recent_collection_snapshot(appdata.path())?.unwrap();
+    mtgocli(&appdata)?
+        .args(["diff", "--format", "csv"])
+        .arg(&snapshot)
+        .arg(snapshot.file_name().unwrap())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::starts_with("id,name,change,")
+                .and(predicate::str::contains("\n").count(1)),
+        );
+
+    Ok(())
+}
+
+#[test]
+fn test_import_rejects_invalid_trade_list() -> TestResult {
+    let appdata = setup_appdata()?;
+    let not_a_trade_list = appdata.child("deck.dek");
+    fs::write(&not_a_trade_list, "<Deck></Deck>")?;
+
+    mtgocli(&appdata)?
+        .arg("import")
+        .arg(&not_a_trade_list)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not an MTGO full trade list"));
+    assert!(!appdata.child("current-full-trade-list.dek").exists());
+    Ok(())
+}
+
+#[test]
+fn test_commands_require_snapshot() -> TestResult {
+    let appdata = setup_appdata()?;
+    mtgocli(&appdata)?
+        .arg("stats")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run `import` first"));
+    Ok(())
+}
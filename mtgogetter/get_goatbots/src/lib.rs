@@ -1,43 +1,100 @@
 use std::io::{self, Read};
 
-use reqwest::blocking;
+use get_http::{Conditional, FetchError, Url, Validators};
 
-const GOATBOTS_PRICE_HISTORY_URL: &str = "https://www.goatbots.com/download/price-history.zip";
-const GOATBOTS_CARD_DEFINITIONS_URL: &str =
+pub const GOATBOTS_PRICE_HISTORY_URL: &str = "https://www.goatbots.com/download/price-history.zip";
+pub const GOATBOTS_CARD_DEFINITIONS_URL: &str =
     "https://www.goatbots.com/download/card-definitions.zip";
 
-fn fetch_and_extract_content(url: &str) -> String {
-    let resp = blocking::get(url).unwrap();
-    let bytes = resp.bytes().unwrap();
+/// Download the zip archive at `url` and extract the contents of its first file
+fn fetch_and_extract_content(url: &Url) -> Result<String, FetchError> {
+    extract_first_file(url, get_http::get_bytes(url)?)
+}
+
+/// Like [fetch_and_extract_content] but only if the archive changed since it was downloaded with the given [Validators]
+fn fetch_and_extract_content_if_modified(
+    url: &Url,
+    validators: &Validators,
+    on_bytes: &dyn Fn(u64, Option<u64>),
+) -> Result<Conditional<String>, FetchError> {
+    get_http::get_bytes_if_modified(url, validators, on_bytes)?
+        .try_map(|bytes| extract_first_file(url, bytes))
+}
+
+/// Extract the contents of the first file in the zip archive downloaded from `url`
+fn extract_first_file(url: &Url, bytes: Vec<u8>) -> Result<String, FetchError> {
+    let archive_err = |e: zip::result::ZipError| FetchError::Archive {
+        url: url.to_string(),
+        source: e.into(),
+    };
 
-    log::info!("Got {} bytes", bytes.len());
     let cursor = io::Cursor::new(bytes);
-    let mut archive = zip::ZipArchive::new(cursor).unwrap();
+    let mut archive = zip::ZipArchive::new(cursor).map_err(archive_err)?;
 
-    let mut file = archive.by_index(0).unwrap();
+    let mut file = archive.by_index(0).map_err(archive_err)?;
     let mut contents = String::with_capacity(512 * 1024);
-    file.read_to_string(&mut contents).unwrap();
-    contents
+    file.read_to_string(&mut contents)
+        .map_err(|e| archive_err(e.into()))?;
+    Ok(contents)
 }
 
 /// Get Goatbots Price History
 ///
-/// TODO: Error handling, this should return a result
-pub fn get_goatbots_price_history() -> String {
-    fetch_and_extract_content(GOATBOTS_PRICE_HISTORY_URL)
+/// # Errors
+///
+/// Returns a [FetchError] if the download fails or the archive cannot be extracted
+pub fn get_goatbots_price_history() -> Result<String, FetchError> {
+    get_goatbots_price_history_from(&get_http::parse_url(GOATBOTS_PRICE_HISTORY_URL)?)
 }
 
 /// Get Goatbots Card Definitions
 ///
-/// TODO: Error handling, this should return a result
-pub fn get_goatbots_card_definitions() -> String {
-    fetch_and_extract_content(GOATBOTS_CARD_DEFINITIONS_URL)
+/// # Errors
+///
+/// Returns a [FetchError] if the download fails or the archive cannot be extracted
+pub fn get_goatbots_card_definitions() -> Result<String, FetchError> {
+    get_goatbots_card_definitions_from(&get_http::parse_url(GOATBOTS_CARD_DEFINITIONS_URL)?)
+}
+
+/// Get Goatbots Price History from the given URL, e.g. a mirror of [GOATBOTS_PRICE_HISTORY_URL]
+pub fn get_goatbots_price_history_from(url: &Url) -> Result<String, FetchError> {
+    fetch_and_extract_content(url)
+}
+
+/// Get Goatbots Card Definitions from the given URL, e.g. a mirror of [GOATBOTS_CARD_DEFINITIONS_URL]
+pub fn get_goatbots_card_definitions_from(url: &Url) -> Result<String, FetchError> {
+    fetch_and_extract_content(url)
+}
+
+/// Get Goatbots Price History from the given URL, unless it hasn't changed since it was downloaded with the given
+/// [Validators]
+///
+/// `on_bytes` receives the number of bytes downloaded so far and the total size if known.
+pub fn get_goatbots_price_history_if_modified(
+    url: &Url,
+    validators: &Validators,
+    on_bytes: &dyn Fn(u64, Option<u64>),
+) -> Result<Conditional<String>, FetchError> {
+    fetch_and_extract_content_if_modified(url, validators, on_bytes)
+}
+
+/// Get Goatbots Card Definitions from the given URL, unless it hasn't changed since it was downloaded with the given
+/// [Validators]
+///
+/// `on_bytes` receives the number of bytes downloaded so far and the total size if known.
+pub fn get_goatbots_card_definitions_if_modified(
+    url: &Url,
+    validators: &Validators,
+    on_bytes: &dyn Fn(u64, Option<u64>),
+) -> Result<Conditional<String>, FetchError> {
+    fetch_and_extract_content_if_modified(url, validators, on_bytes)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use reqwest::Url;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
 
     #[test]
     fn test_urls() {
@@ -47,17 +104,33 @@ mod tests {
 
     #[ignore = "Will download data from the goatbots website"]
     #[test]
-    fn test_get_goatbots_price_history() {
-        let res = get_goatbots_price_history();
+    fn test_get_goatbots_price_history() -> TestResult {
+        let res = get_goatbots_price_history()?;
         eprintln!("{res}");
         assert_ne!(res.len(), 0);
+        Ok(())
     }
 
     #[ignore = "Will download data from the goatbots website"]
     #[test]
-    fn test_get_goatbots_card_definitions() {
-        let res = get_goatbots_card_definitions();
+    fn test_get_goatbots_card_definitions() -> TestResult {
+        let res = get_goatbots_card_definitions()?;
         eprintln!("{res}");
         assert_ne!(res.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_archive_is_error() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.child("price-history.zip");
+        std::fs::write(&path, "not a zip archive")?;
+
+        let url = Url::from_file_path(&path).unwrap();
+        assert!(matches!(
+            get_goatbots_price_history_from(&url),
+            Err(FetchError::Archive { .. })
+        ));
+        Ok(())
     }
 }
@@ -0,0 +1,281 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+use reqwest::{blocking, Certificate, NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+
+use crate::FetchError;
+
+/// User-Agent sent with every request, the Scryfall API requires one
+pub const USER_AGENT: &str = concat!(
+    "mtgo-collection-manager/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/CramBL/mtgo-collection-manager)"
+);
+/// Maximum time to establish a connection
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Maximum time to wait for the response headers and for each read of the response body
+pub const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Settings of the shared HTTP client
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    /// Proxy for all HTTP(S) requests, e.g. `http://proxy.example.com:8080`.
+    /// If not set, the `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` environment variables are used.
+    pub proxy: Option<String>,
+    /// Hosts that bypass `proxy`, comma separated in the format of the `NO_PROXY` environment variable
+    pub no_proxy: Option<String>,
+    /// PEM file with CA certificates to trust in addition to the built-in ones, e.g. of a TLS-inspecting proxy
+    pub ca_bundle: Option<PathBuf>,
+    /// Open a new connection for every request instead of reusing idle ones, for proxies that drop them
+    pub disable_connection_reuse: bool,
+}
+
+/// The HTTP client shared by all getters and the settings it was built with, built on first use
+static CLIENT: Mutex<Option<(ClientSettings, blocking::Client)>> = Mutex::new(None);
+
+/// Use the given settings for all requests from now on, the shared client is rebuilt if they changed
+///
+/// # Errors
+///
+/// Returns an error if the client cannot be built with the settings, the previous client is kept in that case
+pub fn configure(settings: ClientSettings) -> Result<(), FetchError> {
+    let mut shared = CLIENT.lock().unwrap_or_else(PoisonError::into_inner);
+    if shared
+        .as_ref()
+        .is_some_and(|(current, _)| *current == settings)
+    {
+        return Ok(());
+    }
+    log::debug!("Configuring the HTTP client with {settings:?}");
+    let client = build_client(&settings)?;
+    *shared = Some((settings, client));
+    Ok(())
+}
+
+/// Get the shared HTTP client, building it with the default settings if it wasn't [configured](configure)
+///
+/// # Errors
+///
+/// Returns an error if the client cannot be built, e.g. if the TLS backend fails to initialize
+pub fn client() -> Result<blocking::Client, FetchError> {
+    let mut shared = CLIENT.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((_, client)) = shared.as_ref() {
+        // Clones share the connection pool
+        return Ok(client.clone());
+    }
+    let settings = ClientSettings::default();
+    let client = build_client(&settings)?;
+    *shared = Some((settings, client.clone()));
+    Ok(client)
+}
+
+/// Build a client with the given settings
+///
+/// # Errors
+///
+/// Returns [FetchError::Local] if the CA bundle cannot be read, and [FetchError::Client] if it isn't valid PEM, the
+/// proxy is invalid, or the TLS backend fails to initialize
+pub fn build_client(settings: &ClientSettings) -> Result<blocking::Client, FetchError> {
+    let client_err = |source| FetchError::Client { source };
+    let mut builder = blocking::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(READ_TIMEOUT);
+    if let Some(proxy) = &settings.proxy {
+        let no_proxy = settings.no_proxy.as_deref().and_then(NoProxy::from_string);
+        builder = builder.proxy(Proxy::all(proxy).map_err(client_err)?.no_proxy(no_proxy));
+    }
+    if let Some(ca_bundle) = &settings.ca_bundle {
+        let pem = fs::read(ca_bundle).map_err(FetchError::local(ca_bundle))?;
+        for cert in Certificate::from_pem_bundle(&pem).map_err(client_err)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if settings.disable_connection_reuse {
+        builder = builder.pool_max_idle_per_host(0);
+    }
+    builder.build().map_err(client_err)
+}
+
+/// How failed requests are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each subsequent retry
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between retries
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before the given retry (1 is the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Call `f` until it succeeds, fails with an error that isn't [retryable](FetchError::is_retryable),
+    /// or [Self::max_attempts] is reached, sleeping with exponential backoff between attempts
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt
+    pub fn run<T>(&self, mut f: impl FnMut() -> Result<T, FetchError>) -> Result<T, FetchError> {
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Ok(v) => return Ok(v),
+                Err(e) if e.is_retryable() && attempt < self.max_attempts => {
+                    let delay = self.backoff(attempt);
+                    log::warn!(
+                        "{e} - retrying in {delay:?} (attempt {}/{})",
+                        attempt + 1,
+                        self.max_attempts
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{response, serve};
+    use pretty_assertions::assert_eq;
+    use std::{cell::Cell, io};
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    fn no_delay(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    fn status(status: u16) -> FetchError {
+        FetchError::Status {
+            url: "https://example.com".into(),
+            status,
+        }
+    }
+
+    #[test]
+    fn test_requests_go_through_the_proxy() -> TestResult {
+        let (proxy_url, heads) = serve(vec![Box::new(|_| response("200 OK", &[], b"[]"))]);
+        // Built directly, as configuring the shared client would affect the other tests
+        let client = build_client(&ClientSettings {
+            proxy: Some(proxy_url.to_string()),
+            disable_connection_reuse: true,
+            ..Default::default()
+        })?;
+        let resp = client.get("http://mtgo.invalid/data.json").send()?;
+        assert_eq!(resp.text()?, "[]");
+        let head = heads.recv()?;
+        assert!(
+            head.starts_with("get http://mtgo.invalid/data.json http/1.1"),
+            "{head}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_client_settings() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let res = build_client(&ClientSettings {
+            ca_bundle: Some(temp_dir.child("missing.pem")),
+            ..Default::default()
+        });
+        assert!(matches!(res, Err(FetchError::Local { .. })), "{res:?}");
+
+        let res = build_client(&ClientSettings {
+            proxy: Some("not a proxy".to_string()),
+            ..Default::default()
+        });
+        assert!(matches!(res, Err(FetchError::Client { .. })), "{res:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+        };
+        let delays: Vec<u64> = (1..=5).map(|r| policy.backoff(r).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+    }
+
+    #[test]
+    fn test_retries_server_errors_until_success() {
+        let calls = Cell::new(0);
+        let res = no_delay(4).run(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Err(status(503))
+            } else {
+                Ok(calls.get())
+            }
+        });
+        assert_eq!(res.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let calls = Cell::new(0);
+        let res: Result<(), _> = no_delay(3).run(|| {
+            calls.set(calls.get() + 1);
+            Err(status(500))
+        });
+        assert!(matches!(res, Err(FetchError::Status { status: 500, .. })));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_does_not_retry_permanent_errors() {
+        let calls = Cell::new(0);
+        let res: Result<(), _> = no_delay(3).run(|| {
+            calls.set(calls.get() + 1);
+            Err(status(404))
+        });
+        assert!(res.is_err());
+        assert_eq!(calls.get(), 1);
+
+        assert!(!FetchError::Io {
+            url: "file:///missing.json".into(),
+            source: io::ErrorKind::NotFound.into(),
+        }
+        .is_retryable());
+        assert!(FetchError::Io {
+            url: "https://example.com".into(),
+            source: io::ErrorKind::ConnectionReset.into(),
+        }
+        .is_retryable());
+    }
+}
@@ -0,0 +1,236 @@
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{copy_with_progress, send, status_error, FetchError, RetryPolicy, Url};
+
+/// The `ETag` and `Last-Modified` of a downloaded resource, used to ask the server whether it has changed since
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Returns true if the server provided neither an `ETag` nor a `Last-Modified`
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// The `If-None-Match` and `If-Modified-Since` headers of a conditional request
+    pub(crate) fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let pairs = [
+            (IF_NONE_MATCH, &self.etag),
+            (IF_MODIFIED_SINCE, &self.last_modified),
+        ];
+        for (name, value) in pairs {
+            if let Some(v) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, v);
+            }
+        }
+        headers
+    }
+
+    /// The value of an `If-Range` header, the `ETag` is preferred as it's a strong validator
+    pub(crate) fn if_range(&self) -> Option<HeaderValue> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+            .and_then(|v| HeaderValue::from_str(v).ok())
+    }
+}
+
+/// The result of a conditional request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conditional<T> {
+    /// The resource changed (or the server doesn't support conditional requests)
+    Modified {
+        body: T,
+        validators: Validators,
+        /// The status of the HTTP response, [None] for `file://` URLs
+        status: Option<u16>,
+    },
+    /// The server responded `304 Not Modified`, the previously downloaded copy is still fresh
+    NotModified,
+}
+
+impl<T> Conditional<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Conditional<U> {
+        match self {
+            Conditional::Modified {
+                body,
+                validators,
+                status,
+            } => Conditional::Modified {
+                body: f(body),
+                validators,
+                status,
+            },
+            Conditional::NotModified => Conditional::NotModified,
+        }
+    }
+
+    /// Like [Self::map] but `f` can fail
+    pub fn try_map<U, E>(self, f: impl FnOnce(T) -> Result<U, E>) -> Result<Conditional<U>, E> {
+        Ok(match self {
+            Conditional::Modified {
+                body,
+                validators,
+                status,
+            } => Conditional::Modified {
+                body: f(body)?,
+                validators,
+                status,
+            },
+            Conditional::NotModified => Conditional::NotModified,
+        })
+    }
+}
+
+/// The most bytes that are allocated up front for a response body, a bogus `Content-Length` shouldn't exhaust the
+/// memory before any data is received
+const MAX_PREALLOCATED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Read the whole resource at the given URL into memory, unless it hasn't changed since it was downloaded with the
+/// given [Validators]
+///
+/// `file://` URLs are always [Conditional::Modified] with empty [Validators].
+///
+/// # Arguments
+///
+/// * `url` - The resource to get
+/// * `validators` - The [Validators] of the previously downloaded copy
+/// * `on_bytes` - Receives the number of bytes downloaded so far and the total size if known
+///
+/// # Errors
+///
+/// See [open](crate::open)
+pub fn get_bytes_if_modified(
+    url: &Url,
+    validators: &Validators,
+    on_bytes: &dyn Fn(u64, Option<u64>),
+) -> Result<Conditional<Vec<u8>>, FetchError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        let body = crate::get_bytes(url)?;
+        on_bytes(body.len() as u64, Some(body.len() as u64));
+        return Ok(Conditional::Modified {
+            body,
+            validators: Validators::default(),
+            status: None,
+        });
+    }
+
+    RetryPolicy::default().run(|| {
+        let resp = send(url, validators.conditional_headers())?;
+        match resp.status() {
+            StatusCode::NOT_MODIFIED => {
+                log::info!("{url} is not modified");
+                Ok(Conditional::NotModified)
+            }
+            status if status.is_success() => {
+                let new_validators = Validators::from_headers(resp.headers());
+                let total = resp.content_length();
+                let capacity = total.unwrap_or_default().min(MAX_PREALLOCATED_BYTES);
+                let mut body = Vec::with_capacity(capacity as usize);
+                copy_with_progress(url, resp, 0, total, on_bytes, |chunk| {
+                    body.extend_from_slice(chunk);
+                    Ok(())
+                })?;
+                log::info!("Got {} bytes", body.len());
+                Ok(Conditional::Modified {
+                    body,
+                    validators: new_validators,
+                    status: Some(status.as_u16()),
+                })
+            }
+            status => Err(status_error(url, status)),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{response, serve};
+    use pretty_assertions::assert_eq;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_validators_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        let validators = Validators::from_headers(&headers);
+        assert_eq!(validators.etag.as_deref(), Some("\"abc\""));
+
+        let conditional = validators.conditional_headers();
+        assert_eq!(conditional[IF_NONE_MATCH], "\"abc\"");
+        assert_eq!(
+            conditional[IF_MODIFIED_SINCE],
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+        assert_eq!(validators.if_range().unwrap(), "\"abc\"");
+
+        let weak = Validators {
+            etag: Some("W/\"abc\"".into()),
+            last_modified: None,
+        };
+        assert_eq!(weak.if_range(), None);
+        assert!(Validators::default().conditional_headers().is_empty());
+    }
+
+    #[test]
+    fn test_get_bytes_if_modified() -> TestResult {
+        let handler = |head: &str| {
+            if head.contains("if-none-match: \"v1\"") {
+                response("304 Not Modified", &["ETag: \"v1\""], b"")
+            } else {
+                response("200 OK", &["ETag: \"v1\""], b"zip contents")
+            }
+        };
+        let (url, _) = serve(vec![Box::new(handler), Box::new(handler)]);
+
+        let progress = std::cell::Cell::new((0, None));
+        let on_bytes = |read, total| progress.set((read, total));
+        let validators = match get_bytes_if_modified(&url, &Validators::default(), &on_bytes)? {
+            Conditional::Modified {
+                body,
+                validators,
+                status,
+            } => {
+                assert_eq!(body, b"zip contents");
+                assert_eq!(status, Some(200));
+                assert_eq!(progress.get(), (12, Some(12)));
+                validators
+            }
+            Conditional::NotModified => panic!("expected the resource to be modified"),
+        };
+        assert_eq!(
+            get_bytes_if_modified(&url, &validators, &on_bytes)?,
+            Conditional::NotModified
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use reqwest::{
+    header::{HeaderMap, CONTENT_RANGE, IF_RANGE, RANGE},
+    StatusCode,
+};
+
+use crate::{
+    copy_with_progress, file_path, io_error, send, status_error, FetchError, RetryPolicy, Url,
+    Validators,
+};
+
+/// The result of [download_to_file]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Downloaded {
+    /// The [Validators] of the downloaded resource, empty for `file://` URLs
+    pub validators: Validators,
+    /// The status of the last HTTP response, e.g. `206 Partial Content` for a resumed download. [None] for `file://`
+    /// URLs
+    pub status: Option<u16>,
+}
+
+/// Download the resource at the given URL to `dst`, resuming an earlier interrupted download if possible
+///
+/// The data is written to `<dst>.part` and moved to `dst` once complete. If the download is interrupted, the partial
+/// file is kept along with the validators of the response in `<dst>.part.json`, and the next attempt (in the same or
+/// a later run) requests only the remaining bytes with a `Range` request. If the resource changed in the meantime the
+/// server sends all of it instead.
+///
+/// # Arguments
+///
+/// * `url` - The resource to download
+/// * `dst` - Where to store it
+/// * `on_bytes` - Receives the number of bytes downloaded so far (including a resumed partial download)
+///   and the total size if known
+///
+/// # Returns
+///
+/// The [Validators] of the downloaded resource and the status of the response, see [Downloaded]
+///
+/// # Errors
+///
+/// See [open](crate::open), and [FetchError::Local] if the file cannot be written
+pub fn download_to_file(
+    url: &Url,
+    dst: &Path,
+    on_bytes: &dyn Fn(u64, Option<u64>),
+) -> Result<Downloaded, FetchError> {
+    match url.scheme() {
+        "http" | "https" => {
+            let part = with_suffix(dst, ".part");
+            let part_meta = with_suffix(dst, ".part.json");
+            let downloaded = RetryPolicy::default()
+                .run(|| download_attempt(url, &part, &part_meta, on_bytes, false))?;
+            fs::rename(&part, dst).map_err(FetchError::local(dst))?;
+            remove_if_exists(&part_meta)?;
+            Ok(downloaded)
+        }
+        "file" => {
+            let src = file_path(url)?;
+            log::info!("Copying {src:?} to {dst:?}");
+            let len = fs::copy(&src, dst).map_err(|e| io_error(url, e))?;
+            on_bytes(len, Some(len));
+            Ok(Downloaded {
+                validators: Validators::default(),
+                status: None,
+            })
+        }
+        scheme => Err(crate::unsupported_scheme(url, scheme)),
+    }
+}
+
+/// Download the resource at `url` to `part`, resuming the partial download in it if possible
+///
+/// If the partial download cannot be resumed, it's removed and the download is `restarted` from the first byte. A
+/// server that doesn't send what's requested after the restart either, e.g. a proxy that answers every request with
+/// `206 Partial Content`, fails the download.
+fn download_attempt(
+    url: &Url,
+    part: &Path,
+    part_meta: &Path,
+    on_bytes: &dyn Fn(u64, Option<u64>),
+    restarted: bool,
+) -> Result<Downloaded, FetchError> {
+    let resume_from = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    let part_validators = read_part_meta(part_meta);
+
+    let mut headers = HeaderMap::new();
+    let if_range = part_validators.if_range();
+    if let (true, Some(if_range)) = (resume_from > 0, if_range) {
+        log::info!("Resuming download of {url} from byte {resume_from}");
+        headers.insert(
+            RANGE,
+            format!("bytes={resume_from}-")
+                .parse()
+                .expect("valid header value"),
+        );
+        headers.insert(IF_RANGE, if_range);
+    }
+    let resp = send(url, headers)?;
+
+    let status = resp.status();
+    let (mut file, offset, validators) = match status {
+        StatusCode::PARTIAL_CONTENT if content_range_start(resp.headers()) != Some(resume_from) => {
+            // The server didn't send the rest of the partial file, appending it would corrupt the file
+            log::warn!(
+                "Cannot resume download of {url} from byte {resume_from}, got Content-Range {:?}",
+                resp.headers().get(CONTENT_RANGE)
+            );
+            if restarted {
+                return Err(status_error(url, StatusCode::PARTIAL_CONTENT));
+            }
+            remove_if_exists(part)?;
+            remove_if_exists(part_meta)?;
+            return download_attempt(url, part, part_meta, on_bytes, true);
+        }
+        StatusCode::PARTIAL_CONTENT => {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(part)
+                .map_err(FetchError::local(part))?;
+            (file, resume_from, part_validators)
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE if resume_from > 0 && !restarted => {
+            // The partial file doesn't match the resource, start over
+            log::warn!("Cannot resume download of {url}, restarting it");
+            remove_if_exists(part)?;
+            remove_if_exists(part_meta)?;
+            return download_attempt(url, part, part_meta, on_bytes, true);
+        }
+        status if status.is_success() => {
+            let validators = Validators::from_headers(resp.headers());
+            let file = fs::File::create(part).map_err(FetchError::local(part))?;
+            write_part_meta(part_meta, &validators)?;
+            (file, 0, validators)
+        }
+        status => return Err(status_error(url, status)),
+    };
+
+    let total = resp.content_length().map(|len| len + offset);
+    copy_with_progress(url, resp, offset, total, on_bytes, |chunk| {
+        file.write_all(chunk).map_err(FetchError::local(part))
+    })?;
+    file.flush().map_err(FetchError::local(part))?;
+    Ok(Downloaded {
+        validators,
+        status: Some(status.as_u16()),
+    })
+}
+
+/// The first byte position of a `Content-Range: bytes <start>-<end>/<size>` header
+fn content_range_start(headers: &HeaderMap) -> Option<u64> {
+    let range = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (start, _) = range.strip_prefix("bytes ")?.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+fn read_part_meta(part_meta: &Path) -> Validators {
+    fs::read(part_meta)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+fn write_part_meta(part_meta: &Path, validators: &Validators) -> Result<(), FetchError> {
+    let json =
+        serde_json::to_vec(validators).map_err(|e| FetchError::local(part_meta)(e.into()))?;
+    fs::write(part_meta, json).map_err(FetchError::local(part_meta))
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), FetchError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(FetchError::local(path)(e)),
+        _ => Ok(()),
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{response, serve};
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_resume_interrupted_download() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let dst = temp_dir.child("default-cards.json");
+        let (url, requests) = serve(vec![
+            Box::new(|_| {
+                // Drop the connection after the first 5 bytes of the body
+                let mut resp = response("200 OK", &["ETag: \"v1\""], b"hello world");
+                resp.truncate(resp.len() - 6);
+                resp
+            }),
+            Box::new(|head| {
+                assert!(head.contains("range: bytes=5-"), "{head}");
+                assert!(head.contains("if-range: \"v1\""), "{head}");
+                response(
+                    "206 Partial Content",
+                    &["ETag: \"v1\"", "Content-Range: bytes 5-10/11"],
+                    b" world",
+                )
+            }),
+        ]);
+
+        let progress = std::cell::RefCell::new(Vec::new());
+        let downloaded = download_to_file(&url, &dst, &|read, total| {
+            progress.borrow_mut().push((read, total))
+        })?;
+
+        assert_eq!(fs::read_to_string(&dst)?, "hello world");
+        assert_eq!(progress.borrow().last(), Some(&(11, Some(11))));
+        assert_eq!(downloaded.validators.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(downloaded.status, Some(206));
+        assert!(!with_suffix(&dst, ".part").exists());
+        assert!(!with_suffix(&dst, ".part.json").exists());
+        assert_eq!(requests.iter().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_restart_if_resource_changed() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let dst = temp_dir.child("default-cards.json");
+        fs::write(with_suffix(&dst, ".part"), "stale")?;
+        write_part_meta(
+            &with_suffix(&dst, ".part.json"),
+            &Validators {
+                etag: Some("\"v1\"".into()),
+                last_modified: None,
+            },
+        )?;
+        // The server ignores the range as the ETag doesn't match anymore
+        let (url, _) = serve(vec![Box::new(|_| {
+            response("200 OK", &["ETag: \"v2\""], b"new content")
+        })]);
+
+        let downloaded = download_to_file(&url, &dst, &|_, _| {})?;
+
+        assert_eq!(fs::read_to_string(&dst)?, "new content");
+        assert_eq!(downloaded.validators.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(downloaded.status, Some(200));
+        Ok(())
+    }
+
+    #[test]
+    fn test_restart_if_range_does_not_match() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let dst = temp_dir.child("default-cards.json");
+        fs::write(with_suffix(&dst, ".part"), "hello")?;
+        write_part_meta(
+            &with_suffix(&dst, ".part.json"),
+            &Validators {
+                etag: Some("\"v1\"".into()),
+                last_modified: None,
+            },
+        )?;
+        let (url, requests) = serve(vec![
+            // The server sends a range that doesn't start at the end of the partial file
+            Box::new(|_| {
+                response(
+                    "206 Partial Content",
+                    &["ETag: \"v1\"", "Content-Range: bytes 0-10/11"],
+                    b"hello world",
+                )
+            }),
+            Box::new(|head| {
+                assert!(!head.contains("range:"), "{head}");
+                response("200 OK", &["ETag: \"v1\""], b"hello world")
+            }),
+        ]);
+
+        download_to_file(&url, &dst, &|_, _| {})?;
+
+        assert_eq!(fs::read_to_string(&dst)?, "hello world");
+        assert_eq!(requests.iter().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fail_if_range_does_not_match_after_restart() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let dst = temp_dir.child("default-cards.json");
+        fs::write(with_suffix(&dst, ".part"), "hello")?;
+        write_part_meta(
+            &with_suffix(&dst, ".part.json"),
+            &Validators {
+                etag: Some("\"v1\"".into()),
+                last_modified: None,
+            },
+        )?;
+        // A proxy answers every request with a range of its own, even the one without a Range header
+        let partial = |_: &str| {
+            response(
+                "206 Partial Content",
+                &["ETag: \"v1\"", "Content-Range: bytes 6-10/11"],
+                b"world",
+            )
+        };
+        let (url, requests) = serve(vec![Box::new(partial), Box::new(partial)]);
+
+        let res = download_to_file(&url, &dst, &|_, _| {});
+        assert!(
+            matches!(res, Err(FetchError::Status { status: 206, .. })),
+            "{res:?}"
+        );
+        assert_eq!(requests.iter().count(), 2);
+        assert!(!dst.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_file_url() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let src = temp_dir.child("src.json");
+        let dst = temp_dir.child("dst.json");
+        fs::write(&src, "[]")?;
+
+        let downloaded = download_to_file(&Url::from_file_path(&src).unwrap(), &dst, &|_, _| {})?;
+        assert_eq!(fs::read_to_string(&dst)?, "[]");
+        assert!(downloaded.validators.is_empty());
+        assert_eq!(downloaded.status, None);
+        Ok(())
+    }
+}
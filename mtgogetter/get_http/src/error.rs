@@ -0,0 +1,106 @@
+use std::{error::Error, fmt::Display, io, path::PathBuf};
+
+/// Boxed error of a decoder, e.g. from `serde_json` or `zip`
+pub type DecodeError = Box<dyn Error + Send + Sync>;
+
+/// Error from fetching a resource
+#[derive(Debug)]
+pub enum FetchError {
+    /// The URL couldn't be parsed or isn't supported
+    InvalidUrl { url: String, reason: String },
+    /// Reading the resource failed
+    Io { url: String, source: io::Error },
+    /// The HTTP request failed
+    Http { url: String, source: reqwest::Error },
+    /// The server responded with a non-success status code
+    Status { url: String, status: u16 },
+    /// The resource was fetched but its contents couldn't be decoded, e.g. invalid JSON
+    Decode { url: String, source: DecodeError },
+    /// The resource was fetched but it isn't a valid archive or the archive is empty
+    Archive { url: String, source: DecodeError },
+    /// Reading or writing a local file (the fetched data, the fetch log or the config) failed
+    Local { path: PathBuf, source: io::Error },
+    /// The HTTP client couldn't be built with the configured settings, e.g. an invalid proxy
+    Client { source: reqwest::Error },
+}
+
+impl FetchError {
+    /// Returns true if the error is likely transient and the request is worth retrying,
+    /// i.e. network errors, timeouts, and 5xx responses
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FetchError::Http { source, .. } => {
+                source.is_connect() || source.is_timeout() || source.is_request()
+            }
+            FetchError::Status { status, .. } => (500..600).contains(status),
+            // Reading the body of an HTTP response fails if the connection drops or times out
+            FetchError::Io { url, source } => {
+                url.starts_with("http")
+                    || matches!(
+                        source.kind(),
+                        io::ErrorKind::TimedOut
+                            | io::ErrorKind::ConnectionReset
+                            | io::ErrorKind::ConnectionAborted
+                            | io::ErrorKind::Interrupted
+                    )
+            }
+            FetchError::InvalidUrl { .. }
+            | FetchError::Decode { .. }
+            | FetchError::Archive { .. }
+            | FetchError::Local { .. }
+            | FetchError::Client { .. } => false,
+        }
+    }
+
+    /// Convenience for mapping a local [io::Error] at `path`
+    pub fn local(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| FetchError::Local { path, source }
+    }
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::InvalidUrl { url, reason } => write!(f, "Invalid URL {url}: {reason}"),
+            FetchError::Io { url, source } => write!(f, "Failed to read {url}: {source}"),
+            FetchError::Http { url, source } => write!(f, "Request to {url} failed: {source}"),
+            FetchError::Status { url, status } => {
+                write!(f, "Request to {url} failed with status {status}")
+            }
+            FetchError::Decode { url, source } => {
+                write!(f, "Failed to decode the contents of {url}: {source}")
+            }
+            FetchError::Archive { url, source } => {
+                write!(f, "Failed to extract the archive from {url}: {source}")
+            }
+            FetchError::Local { path, source } => write!(f, "Failed to access {path:?}: {source}"),
+            FetchError::Client { source } => write!(f, "Failed to build the HTTP client: {source}"),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FetchError::Io { source, .. } | FetchError::Local { source, .. } => Some(source),
+            FetchError::Http { source, .. } | FetchError::Client { source } => Some(source),
+            FetchError::Decode { source, .. } | FetchError::Archive { source, .. } => {
+                Some(source.as_ref())
+            }
+            FetchError::InvalidUrl { .. } | FetchError::Status { .. } => None,
+        }
+    }
+}
+
+impl From<FetchError> for io::Error {
+    fn from(e: FetchError) -> Self {
+        match e {
+            // Keep the kind (e.g. NotFound) of local errors
+            FetchError::Local { path, source } => {
+                io::Error::new(source.kind(), FetchError::Local { path, source })
+            }
+            e => io::Error::other(e),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    fs,
+    io::{self, BufReader, Read},
+    path::PathBuf,
+};
+
+pub use reqwest::Url;
+use reqwest::{blocking, header::HeaderMap, StatusCode};
+
+pub mod client;
+mod conditional;
+mod download;
+mod error;
+/// Minimal HTTP/1.1 servers for testing requests without network access
+#[cfg(any(test, feature = "test-server"))]
+pub mod test_server;
+pub use client::{ClientSettings, RetryPolicy};
+pub use conditional::{get_bytes_if_modified, Conditional, Validators};
+pub use download::{download_to_file, Downloaded};
+pub use error::{DecodeError, FetchError};
+
+/// Parse a URL, mapping failure to [FetchError::InvalidUrl]
+pub fn parse_url(url: &str) -> Result<Url, FetchError> {
+    Url::parse(url).map_err(|e| FetchError::InvalidUrl {
+        url: url.to_owned(),
+        reason: e.to_string(),
+    })
+}
+
+/// Open the resource at the given URL for reading
+///
+/// HTTP(S) requests go through the shared [client](client::client) and are retried with the default [RetryPolicy].
+///
+/// # Errors
+///
+/// Returns a [FetchError] if the scheme is unsupported, the file cannot be opened, the request fails,
+/// or the server responds with an error status
+pub fn open(url: &Url) -> Result<Box<dyn Read + Send>, FetchError> {
+    match url.scheme() {
+        "http" | "https" => RetryPolicy::default().run(|| open_once(url)),
+        _ => open_once(url),
+    }
+    .map(|(reader, _)| reader)
+}
+
+/// Read the whole resource at the given URL into memory
+///
+/// Unlike [open], a failure while reading the response body is also retried.
+///
+/// # Errors
+///
+/// See [open]
+pub fn get_bytes(url: &Url) -> Result<Vec<u8>, FetchError> {
+    let read_all = || {
+        let mut bytes = Vec::new();
+        open_once(url)?
+            .0
+            .read_to_end(&mut bytes)
+            .map_err(|e| io_error(url, e))?;
+        Ok(bytes)
+    };
+    let bytes = match url.scheme() {
+        "http" | "https" => RetryPolicy::default().run(read_all)?,
+        _ => read_all()?,
+    };
+    log::info!("Got {} bytes", bytes.len());
+    Ok(bytes)
+}
+
+/// Decode the JSON resource at the given URL
+///
+/// # Errors
+///
+/// See [open], and [FetchError::Decode] if the contents are not the expected JSON
+pub fn get_json<T: serde::de::DeserializeOwned>(url: &Url) -> Result<T, FetchError> {
+    get_json_with_status(url).map(|(json, _)| json)
+}
+
+/// Like [get_json] but also returns the status of the HTTP response, [None] for `file://` URLs
+///
+/// # Errors
+///
+/// See [get_json]
+pub fn get_json_with_status<T: serde::de::DeserializeOwned>(
+    url: &Url,
+) -> Result<(T, Option<u16>), FetchError> {
+    let (reader, status) = match url.scheme() {
+        "http" | "https" => RetryPolicy::default().run(|| open_once(url))?,
+        _ => open_once(url)?,
+    };
+    let json = serde_json::from_reader(reader).map_err(|e| FetchError::Decode {
+        url: url.to_string(),
+        source: e.into(),
+    })?;
+    Ok((json, status))
+}
+
+/// A single attempt at opening the resource, along with the status of the HTTP response
+fn open_once(url: &Url) -> Result<(Box<dyn Read + Send>, Option<u16>), FetchError> {
+    match url.scheme() {
+        "file" => {
+            let path = file_path(url)?;
+            log::info!("Reading {path:?}");
+            let file = fs::File::open(&path).map_err(|e| io_error(url, e))?;
+            Ok((Box::new(BufReader::new(file)), None))
+        }
+        "http" | "https" => {
+            let resp = send(url, HeaderMap::new())?;
+            if !resp.status().is_success() {
+                return Err(status_error(url, resp.status()));
+            }
+            let status = resp.status().as_u16();
+            Ok((Box::new(BufReader::new(resp)), Some(status)))
+        }
+        scheme => Err(unsupported_scheme(url, scheme)),
+    }
+}
+
+/// Copy the body of the response to `sink` in chunks, reporting progress to `on_bytes`
+///
+/// `on_bytes` receives the number of bytes read so far plus `offset` (e.g. the size of a partial download being
+/// resumed), and the total size if known.
+fn copy_with_progress(
+    url: &Url,
+    mut body: impl Read,
+    offset: u64,
+    total: Option<u64>,
+    on_bytes: &dyn Fn(u64, Option<u64>),
+    mut sink: impl FnMut(&[u8]) -> Result<(), FetchError>,
+) -> Result<(), FetchError> {
+    let mut buf = vec![0; 64 * 1024];
+    let mut read = offset;
+    loop {
+        let n = match body.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(io_error(url, e)),
+        };
+        sink(&buf[..n])?;
+        read += n as u64;
+        on_bytes(read, total);
+    }
+}
+
+/// Send a GET request with the given extra headers through the shared [client](client::client)
+///
+/// The status code is not checked, as e.g. `304 Not Modified` is expected for conditional requests.
+fn send(url: &Url, headers: HeaderMap) -> Result<blocking::Response, FetchError> {
+    log::info!("Requesting {url}");
+    let http_err = |e| FetchError::Http {
+        url: url.to_string(),
+        source: e,
+    };
+    let resp = client::client()?
+        .get(url.clone())
+        .headers(headers)
+        .send()
+        .map_err(http_err)?;
+    log::debug!("Response status: {}", resp.status());
+    if let Some(len) = resp.content_length() {
+        log::info!("Response content length: {len}");
+    }
+    Ok(resp)
+}
+
+fn status_error(url: &Url, status: StatusCode) -> FetchError {
+    FetchError::Status {
+        url: url.to_string(),
+        status: status.as_u16(),
+    }
+}
+
+fn file_path(url: &Url) -> Result<PathBuf, FetchError> {
+    url.to_file_path().map_err(|_| FetchError::InvalidUrl {
+        url: url.to_string(),
+        reason: "not a valid file path".to_owned(),
+    })
+}
+
+fn unsupported_scheme(url: &Url, scheme: &str) -> FetchError {
+    FetchError::InvalidUrl {
+        url: url.to_string(),
+        reason: format!("unsupported scheme {scheme:?}"),
+    }
+}
+
+/// Convert an [io::Error] that occurred while reading the resource at `url` to a [FetchError]
+pub fn io_error(url: &Url, e: io::Error) -> FetchError {
+    FetchError::Io {
+        url: url.to_string(),
+        source: e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_get_bytes_from_file_url() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.child("data.json");
+        fs::write(&path, "[1, 2, 3]")?;
+
+        let url = Url::from_file_path(&path).unwrap();
+        assert_eq!(get_bytes(&url)?, b"[1, 2, 3]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_file_is_io_error() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let url = Url::from_file_path(temp_dir.child("missing.json")).unwrap();
+        assert!(matches!(open(&url), Err(FetchError::Io { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_json_decode_error() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.child("data.json");
+        fs::write(&path, "[1, 2, 3]")?;
+        let url = Url::from_file_path(&path).unwrap();
+        assert_eq!(get_json::<Vec<u8>>(&url)?, [1, 2, 3]);
+
+        fs::write(&path, "{ not json")?;
+        assert!(matches!(
+            get_json::<Vec<u8>>(&url),
+            Err(FetchError::Decode { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_json_with_status() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.child("data.json");
+        fs::write(&path, "[1]")?;
+        let (url, _requests) = test_server::serve(vec![Box::new(|_| {
+            test_server::response("203 Non-Authoritative Information", &[], b"[2]")
+        })]);
+
+        assert_eq!(get_json_with_status::<Vec<u8>>(&url)?, (vec![2], Some(203)));
+        let file_url = Url::from_file_path(&path).unwrap();
+        assert_eq!(get_json_with_status::<Vec<u8>>(&file_url)?, (vec![1], None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_scheme() -> TestResult {
+        let url = parse_url("ftp://example.com/data.json")?;
+        assert!(matches!(open(&url), Err(FetchError::InvalidUrl { .. })));
+        assert!(matches!(
+            parse_url("not a url"),
+            Err(FetchError::InvalidUrl { .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::Url;
+
+/// Produces the raw response to a request given its head
+pub type Handler = Box<dyn FnOnce(&str) -> Vec<u8> + Send>;
+
+/// Serve one request per response handler in order, closing the connection after each response
+///
+/// Each handler gets the request head (request line and headers, lowercased) and returns the raw response.
+/// The request heads are sent on the returned channel.
+pub fn serve(handlers: Vec<Handler>) -> (Url, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for handler in handlers {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line.to_lowercase());
+            }
+            let response = handler(&head);
+            let _ = tx.send(head);
+            // The client may hang up early, e.g. when testing interrupted downloads
+            let _ = stream.write_all(&response);
+        }
+    });
+    (url, rx)
+}
+
+/// A raw response with the given status line, extra headers and body
+pub fn response(status: &str, headers: &[&str], body: &[u8]) -> Vec<u8> {
+    let mut resp = format!(
+        "HTTP/1.1 {status}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        body.len()
+    );
+    for h in headers {
+        resp.push_str(h);
+        resp.push_str("\r\n");
+    }
+    resp.push_str("\r\n");
+    let mut resp = resp.into_bytes();
+    resp.extend_from_slice(body);
+    resp
+}
+
+/// Serves the files of a directory over HTTP until dropped, e.g. a mirror of the data sources
+///
+/// Responses carry an `ETag` derived from the file contents, and a request with a matching `If-None-Match` gets
+/// `304 Not Modified`. Missing files get `404 Not Found`. The files can be changed while the server is running.
+pub struct MirrorServer {
+    url: Url,
+    requests: Arc<Mutex<Vec<String>>>,
+    shutdown: Arc<AtomicBool>,
+    acceptor: Option<JoinHandle<()>>,
+}
+
+impl MirrorServer {
+    /// Start serving the files in `dir` on a free local port
+    ///
+    /// # Errors
+    ///
+    /// Returns an [io::Error] if no local port can be bound
+    pub fn serve_dir(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = Url::parse(&format!("http://{}/", listener.local_addr()?))
+            .expect("a socket address is a valid host");
+        let requests: Arc<Mutex<Vec<String>>> = Arc::default();
+        let shutdown: Arc<AtomicBool> = Arc::default();
+        let acceptor = {
+            let requests = Arc::clone(&requests);
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    let dir = dir.clone();
+                    let requests = Arc::clone(&requests);
+                    thread::spawn(move || {
+                        // The client may hang up early, which is not an error of the server
+                        let _ = serve_file(&dir, stream, &requests);
+                    });
+                }
+            })
+        };
+        Ok(Self {
+            url,
+            requests,
+            shutdown,
+            acceptor: Some(acceptor),
+        })
+    }
+
+    /// The URL of the served directory, ending with a `/`
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// The paths of the requests received so far, in the order they were received
+    pub fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Forget the requests received so far
+    pub fn clear_requests(&self) {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl Drop for MirrorServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the acceptor so it sees the shutdown flag
+        if let Some(addr) = self
+            .url
+            .socket_addrs(|| None)
+            .ok()
+            .and_then(|a| a.first().copied())
+        {
+            let _ = TcpStream::connect(addr);
+        }
+        if let Some(acceptor) = self.acceptor.take() {
+            let _ = acceptor.join();
+        }
+    }
+}
+
+/// Respond to a single GET request with the requested file in `dir`
+fn serve_file(dir: &Path, mut stream: TcpStream, requests: &Mutex<Vec<String>>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut if_none_match = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("if-none-match") {
+                if_none_match = Some(value.trim().to_owned());
+            }
+        }
+    }
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    requests
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(path.clone());
+
+    let file = path
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .fold(dir.to_path_buf(), |file, segment| file.join(segment));
+    let response = match fs::read(&file) {
+        Ok(body) => {
+            let mut hasher = DefaultHasher::new();
+            body.hash(&mut hasher);
+            let etag = format!("\"{:016x}\"", hasher.finish());
+            let etag_header = format!("ETag: {etag}");
+            if if_none_match.as_deref() == Some(etag.as_str()) {
+                response("304 Not Modified", &[&etag_header], b"")
+            } else {
+                response("200 OK", &[&etag_header], &body)
+            }
+        }
+        Err(_) => response("404 Not Found", &[], b""),
+    };
+    stream.write_all(&response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_bytes, get_bytes_if_modified, Conditional, FetchError, Validators};
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_mirror_server() -> TestResult {
+        let dir = TempDir::new()?;
+        fs::create_dir(dir.child("goatbots"))?;
+        fs::write(dir.child("goatbots/prices.json"), "{}")?;
+        let server = MirrorServer::serve_dir(dir.path())?;
+
+        let url = server.url().join("goatbots/prices.json")?;
+        assert_eq!(get_bytes(&url)?, b"{}");
+        let validators = match get_bytes_if_modified(&url, &Validators::default(), &|_, _| ())? {
+            Conditional::Modified { validators, .. } => validators,
+            Conditional::NotModified => panic!("first request can't be conditional"),
+        };
+        assert!(matches!(
+            get_bytes_if_modified(&url, &validators, &|_, _| ())?,
+            Conditional::NotModified
+        ));
+        fs::write(dir.child("goatbots/prices.json"), "[]")?;
+        assert!(matches!(
+            get_bytes_if_modified(&url, &validators, &|_, _| ())?,
+            Conditional::Modified { .. }
+        ));
+
+        let missing = server.url().join("scryfall/sets.json")?;
+        assert!(matches!(
+            get_bytes(&missing),
+            Err(FetchError::Status { status: 404, .. })
+        ));
+        assert_eq!(
+            server.requests(),
+            [
+                "/goatbots/prices.json",
+                "/goatbots/prices.json",
+                "/goatbots/prices.json",
+                "/goatbots/prices.json",
+                "/scryfall/sets.json"
+            ]
+        );
+        server.clear_requests();
+        assert_eq!(server.requests(), Vec::<String>::new());
+        Ok(())
+    }
+}
@@ -1,56 +1,108 @@
-use std::io::BufReader;
+use std::{
+    fs,
+    io::{self, BufReader},
+    path::Path,
+};
 
-use crate::util::format_datetime_utc_for_url;
+use crate::{util::format_datetime_utc_for_url, ScryfallBulkDataInfo};
 use chrono::{DateTime, Utc};
-use parse_scryfall::{ScryfallCard, ScryfallMtgoCards};
-use reqwest::{blocking, Url};
+use get_http::{FetchError, Url};
+use parse_scryfall::{ScryfallCard, ScryfallMtgoCards, SkippedObjects};
 
 #[derive(Debug)]
 pub struct ScryfallBulkData {
     endpoint: Url,
     updated_at: DateTime<Utc>,
     cards: Vec<ScryfallCard>,
-    // Name is default-cards-<timestamp>.json
+    http_status: Option<u16>,
+    // The file name of the endpoint, e.g. default-cards-<timestamp>.json
     pub filename: String,
 }
 
 impl ScryfallBulkData {
+    /// Name of the file the MTGO cards are cached in by `mtgogetter`, as JSON lines sorted by MTGO ID (see
+    /// [parse_scryfall::SortedCards])
     pub const FILENAME: &'static str = "scryfall-cards.json";
-    const URL_PREFIX: &'static str = "https://data.scryfall.io/default-cards/default-cards-";
-
-    /// Get bulk data from the supplied date
-    pub fn get(date: DateTime<Utc>) -> Result<Self, reqwest::Error> {
-        let date_url_part = format_datetime_utc_for_url(date);
-        let url_str = String::from(Self::URL_PREFIX) + &date_url_part + ".json";
-        log::info!("Getting bulk cards from {url_str}");
-        let url_endpoint = Url::parse(&url_str).unwrap();
-        let resp = blocking::get(url_endpoint.clone())?;
-
-        let content_lenght: String = match resp.content_length() {
-            Some(l) => l.to_string(),
-            // Why could it be unknown? Read more: https://docs.rs/reqwest/latest/reqwest/blocking/struct.Response.html#method.content_length
-            None => String::from("Unknown Response Content length"),
-        };
-        log::info!("Response content length: {content_lenght}",);
 
-        // Todo: Optimize
-        let stream = BufReader::new(resp);
+    /// Get the bulk data from the URI advertised by the bulk data info
+    pub fn get(info: &ScryfallBulkDataInfo) -> Result<Self, FetchError> {
+        Self::get_from(info.download_url(), info.updated_at())
+    }
+
+    /// Get the bulk data updated at the supplied date from the given URL, e.g. a mirror of
+    /// [ScryfallBulkDataInfo::download_url]
+    ///
+    /// The response is streamed and only the objects of MTGO cards are kept, see [ScryfallMtgoCards].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [FetchError] if the download fails or the response isn't the expected JSON
+    pub fn get_from(url: &Url, updated_at: DateTime<Utc>) -> Result<Self, FetchError> {
+        log::info!("Getting bulk cards from {url}");
+        let (ScryfallMtgoCards { cards, skipped }, http_status) =
+            get_http::get_json_with_status(url)?;
+        log_cards(&cards, &skipped);
 
-        let deserialized: Vec<ScryfallCard> =
-            serde_json::from_reader::<_, ScryfallMtgoCards>(stream)
-                .unwrap()
-                .0;
+        Ok(Self {
+            endpoint: url.clone(),
+            updated_at,
+            cards,
+            http_status,
+            filename: Self::filename_of(url, updated_at),
+        })
+    }
 
-        log::info!("Got {} MTGO Scryfall cards", deserialized.len());
+    /// Like [Self::get_from] but the bulk data is first downloaded to `download_dir`, so an interrupted download can
+    /// be resumed by the next call. The downloaded file is removed once it's parsed, or if it can't be parsed, and so
+    /// are the partial downloads of bulk data that was replaced by a newer file before they were completed.
+    ///
+    /// `on_bytes` receives the number of bytes downloaded so far and the total size if known.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [FetchError] if the download fails, the file cannot be stored, or it isn't the expected JSON
+    pub fn download_from(
+        url: &Url,
+        updated_at: DateTime<Utc>,
+        download_dir: &Path,
+        on_bytes: &dyn Fn(u64, Option<u64>),
+    ) -> Result<Self, FetchError> {
+        let filename = Self::filename_of(url, updated_at);
+        let dst = download_dir.join(&filename);
+        remove_stale_partial_downloads(download_dir, &filename)?;
+        log::info!("Downloading bulk cards from {url} to {dst:?}");
+        let downloaded = get_http::download_to_file(url, &dst, on_bytes)?;
+
+        let file = fs::File::open(&dst).map_err(FetchError::local(&dst))?;
+        let parsed: Result<ScryfallMtgoCards, _> = serde_json::from_reader(BufReader::new(file));
+        // A file that can't be parsed is downloaded again from scratch by the next call
+        fs::remove_file(&dst).map_err(FetchError::local(&dst))?;
+        let ScryfallMtgoCards { cards, skipped } = parsed.map_err(|e| FetchError::Decode {
+            url: url.to_string(),
+            source: e.into(),
+        })?;
+        log_cards(&cards, &skipped);
 
         Ok(Self {
-            endpoint: url_endpoint,
-            updated_at: date,
-            cards: deserialized,
-            filename: format!("default-cards-{date_url_part}.json"),
+            endpoint: url.clone(),
+            updated_at,
+            cards,
+            http_status: downloaded.status,
+            filename,
         })
     }
 
+    /// The last path segment of the URL, or `bulk-data-<timestamp>.json` if it has none
+    fn filename_of(url: &Url, updated_at: DateTime<Utc>) -> String {
+        url.path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned)
+            .unwrap_or_else(|| {
+                format!("bulk-data-{}.json", format_datetime_utc_for_url(updated_at))
+            })
+    }
+
     pub fn endpoint(&self) -> &Url {
         &self.endpoint
     }
@@ -59,6 +111,11 @@ impl ScryfallBulkData {
         self.updated_at
     }
 
+    /// The status of the last HTTP response the bulk data was received in, [None] for `file://` URLs
+    pub fn http_status(&self) -> Option<u16> {
+        self.http_status
+    }
+
     pub fn cards(&self) -> &[ScryfallCard] {
         &self.cards
     }
@@ -69,21 +126,118 @@ impl ScryfallBulkData {
     }
 }
 
+/// Remove the partial downloads (`<name>.part` and `<name>.part.json`) of bulk data files in `download_dir` other than
+/// `filename`, they can't be resumed once Scryfall has published a newer file
+fn remove_stale_partial_downloads(download_dir: &Path, filename: &str) -> Result<(), FetchError> {
+    let entries = match fs::read_dir(download_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(FetchError::local(download_dir)(e)),
+    };
+    for entry in entries {
+        let path = entry.map_err(FetchError::local(download_dir))?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Some(downloading) = name
+            .strip_suffix(".part")
+            .or_else(|| name.strip_suffix(".part.json"))
+        else {
+            continue;
+        };
+        if downloading != filename && downloading.ends_with(".json") {
+            log::info!("Removing stale partial download {path:?}");
+            fs::remove_file(&path).map_err(FetchError::local(&path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Log the number of MTGO cards and the skipped objects by reason, reasons that hint at a format change are warnings
+fn log_cards(cards: &[ScryfallCard], skipped: &SkippedObjects) {
+    log::info!(
+        "Got {} MTGO Scryfall cards, skipped {} objects",
+        cards.len(),
+        skipped.values().sum::<usize>()
+    );
+    for (reason, count) in skipped {
+        if reason.is_expected() {
+            log::info!("Skipped {count} objects: {reason}");
+        } else {
+            log::warn!("Skipped {count} objects: {reason}");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
     use testresult::TestResult;
 
     use crate::util::init_debug_logging;
 
     use super::*;
 
+    #[test]
+    fn test_filename_of() -> TestResult {
+        let date = Utc.with_ymd_and_hms(2024, 5, 19, 9, 5, 48).unwrap();
+        let url = get_http::parse_url(
+            "https://data.scryfall.io/oracle-cards/oracle-cards-20240519090548.json",
+        )?;
+        assert_eq!(
+            ScryfallBulkData::filename_of(&url, date),
+            "oracle-cards-20240519090548.json"
+        );
+        let url = get_http::parse_url("https://mirror.example.com/")?;
+        assert_eq!(
+            ScryfallBulkData::filename_of(&url, date),
+            "bulk-data-20240519090548.json"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_from_removes_stale_and_invalid_files() -> TestResult {
+        let dir = temp_dir::TempDir::new()?;
+        let date = Utc.with_ymd_and_hms(2024, 5, 19, 9, 5, 48).unwrap();
+        let src = dir.child("default-cards-20240519090548.json");
+        fs::write(&src, "[]")?;
+        let download_dir = dir.child("appdata");
+        fs::create_dir(&download_dir)?;
+        for stale in [
+            "default-cards-20240518090548.json.part",
+            "default-cards-20240518090548.json.part.json",
+        ] {
+            fs::write(download_dir.join(stale), "{")?;
+        }
+        fs::write(download_dir.join("price-history.json"), "{}")?;
+
+        let url = Url::from_file_path(&src).unwrap();
+        let bulk_data = ScryfallBulkData::download_from(&url, date, &download_dir, &|_, _| {})?;
+        assert!(bulk_data.cards().is_empty());
+        let mut names: Vec<_> = fs::read_dir(&download_dir)?
+            .map(|entry| entry.map(|e| e.file_name()))
+            .collect::<Result<_, _>>()?;
+        names.sort();
+        assert_eq!(names, ["price-history.json"]);
+
+        // The downloaded file is removed if it's not the expected JSON
+        fs::write(&src, "[{")?;
+        let res = ScryfallBulkData::download_from(&url, date, &download_dir, &|_, _| {});
+        assert!(matches!(res, Err(FetchError::Decode { .. })), "{res:?}");
+        assert!(!download_dir
+            .join("default-cards-20240519090548.json")
+            .exists());
+        Ok(())
+    }
+
     #[ignore = "Will download A LOT of data from the Scryfall API"]
     #[test]
     fn test_get_scryfall_bulk_data() -> TestResult {
         init_debug_logging(3);
-        let date = Utc.with_ymd_and_hms(2024, 5, 19, 9, 5, 48).unwrap();
-        let bulk_data = ScryfallBulkData::get(date)?;
+        let info = ScryfallBulkDataInfo::get(Default::default())?;
+        let bulk_data = ScryfallBulkData::get(&info)?;
 
         eprintln!("{bulk_data:?}");
 
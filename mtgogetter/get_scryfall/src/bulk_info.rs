@@ -1,37 +1,137 @@
+use std::{fmt, str::FromStr};
+
 use chrono::{DateTime, Utc};
-use reqwest::{blocking, Url};
+use get_http::{FetchError, Url};
 use serde::{Deserialize, Serialize};
 
+/// The bulk datasets of Scryfall that contain MTGO IDs, see <https://scryfall.com/docs/api/bulk-data>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScryfallBulkDataset {
+    /// Every card object on Scryfall in English or the printed language if it's only available in one language
+    #[default]
+    DefaultCards,
+    /// One card object for each Oracle ID, the smallest dataset
+    OracleCards,
+    /// Every card object on Scryfall in every language, the largest dataset
+    AllCards,
+}
+
+impl ScryfallBulkDataset {
+    pub const ALL: [Self; 3] = [Self::DefaultCards, Self::OracleCards, Self::AllCards];
+
+    /// The `type` of the dataset in the bulk data list
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DefaultCards => "default_cards",
+            Self::OracleCards => "oracle_cards",
+            Self::AllCards => "all_cards",
+        }
+    }
+}
+
+impl fmt::Display for ScryfallBulkDataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ScryfallBulkDataset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|d| d.as_str() == s)
+            .ok_or_else(|| {
+                format!("Unknown Scryfall bulk dataset {s:?}, expected default_cards, oracle_cards or all_cards")
+            })
+    }
+}
+
 #[derive(Debug)]
 pub struct ScryfallBulkDataInfo {
+    dataset: ScryfallBulkDataset,
     download_url: Url,
     updated_at: DateTime<Utc>,
+    size: Option<u64>,
 }
 
-impl ScryfallBulkDataInfo {
-    const URL_ENDPOINT: &'static str =
-        "https://api.scryfall.com/bulk-data/e2ef41e3-5778-4bc2-af3f-78eca4dd9c23";
+/// An item of the bulk data list
+#[derive(Deserialize, Debug)]
+struct BulkDataItem {
+    #[serde(rename = "type")]
+    dataset: Option<String>,
+    download_uri: String,
+    updated_at: DateTime<Utc>,
+    size: Option<u64>,
+}
 
-    pub fn get() -> Result<Self, reqwest::Error> {
-        let resp = blocking::get(Self::URL_ENDPOINT)?;
-        let bytes = resp.bytes()?;
+/// The response of the bulk data list endpoint, or a single item of it
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum BulkDataResponse {
+    List { data: Vec<BulkDataItem> },
+    Item(BulkDataItem),
+}
 
-        #[derive(Deserialize, Serialize, Debug)]
-        struct TmpBulkDataInfo<'b> {
-            download_uri: &'b str,
-            updated_at: DateTime<Utc>,
-        }
+impl ScryfallBulkDataInfo {
+    /// The bulk data list endpoint, it lists the download URI and update time of every bulk dataset
+    pub const LIST_URL: &'static str = "https://api.scryfall.com/bulk-data";
 
-        let deserialized: TmpBulkDataInfo = serde_json::from_slice(&bytes).unwrap();
-        let download_url = Url::parse(deserialized.download_uri).unwrap();
-        let bulk_data_info: Self = Self {
+    pub fn new(dataset: ScryfallBulkDataset, download_url: Url, updated_at: DateTime<Utc>) -> Self {
+        Self {
+            dataset,
             download_url,
-            updated_at: deserialized.updated_at,
+            updated_at,
+            size: None,
+        }
+    }
+
+    pub fn get(dataset: ScryfallBulkDataset) -> Result<Self, FetchError> {
+        Self::get_from(&get_http::parse_url(Self::LIST_URL)?, dataset)
+    }
+
+    /// Get the info of the given bulk dataset from the given URL, e.g. a mirror of [Self::LIST_URL]
+    ///
+    /// The response can be the bulk data list, or a single item of it. A single item without a `type` is assumed to
+    /// be the requested dataset.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [FetchError] if the download fails, the response isn't the expected JSON, or it doesn't contain the
+    /// dataset
+    pub fn get_from(url: &Url, dataset: ScryfallBulkDataset) -> Result<Self, FetchError> {
+        let decode_err = |msg: String| FetchError::Decode {
+            url: url.to_string(),
+            source: msg.into(),
         };
+        let item = match get_http::get_json::<BulkDataResponse>(url)? {
+            BulkDataResponse::List { data } => data
+                .into_iter()
+                .find(|item| item.dataset.as_deref() == Some(dataset.as_str()))
+                .ok_or_else(|| decode_err(format!("The bulk data list has no {dataset}")))?,
+            BulkDataResponse::Item(item) => match item.dataset.as_deref() {
+                Some(t) if t != dataset.as_str() => {
+                    return Err(decode_err(format!("Expected {dataset} bulk data, got {t}")))
+                }
+                _ => item,
+            },
+        };
+
+        Ok(Self {
+            dataset,
+            download_url: get_http::parse_url(&item.download_uri)?,
+            updated_at: item.updated_at,
+            size: item.size,
+        })
+    }
 
-        Ok(bulk_data_info)
+    pub fn dataset(&self) -> ScryfallBulkDataset {
+        self.dataset
     }
 
+    /// The URI the dataset is advertised to be downloaded from
     pub fn download_url(&self) -> &Url {
         &self.download_url
     }
@@ -39,19 +139,109 @@ impl ScryfallBulkDataInfo {
     pub fn updated_at(&self) -> DateTime<Utc> {
         self.updated_at
     }
+
+    /// The size of the dataset in bytes, if listed
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use pretty_assertions::assert_eq;
     use regex::Regex;
     use testresult::TestResult;
 
     use super::*;
 
+    const BULK_DATA_LIST: &str = r#"{
+  "object": "list",
+  "has_more": false,
+  "data": [
+    {
+      "object": "bulk_data",
+      "type": "oracle_cards",
+      "updated_at": "2024-05-19T09:02:11.000+00:00",
+      "size": 157286400,
+      "download_uri": "https://data.scryfall.io/oracle-cards/oracle-cards-20240519090211.json"
+    },
+    {
+      "object": "bulk_data",
+      "type": "default_cards",
+      "updated_at": "2024-05-19T09:05:48.000+00:00",
+      "size": 451411968,
+      "download_uri": "https://data.scryfall.io/default-cards/default-cards-20240519090548.json"
+    }
+  ]
+}"#;
+
+    fn write_json(dir: &temp_dir::TempDir, json: &str) -> Result<Url, Box<dyn std::error::Error>> {
+        let path = dir.child("bulk-data.json");
+        std::fs::write(&path, json)?;
+        Ok(Url::from_file_path(path).map_err(|()| "invalid path")?)
+    }
+
+    #[test]
+    fn test_select_dataset_from_list() -> TestResult {
+        let dir = temp_dir::TempDir::new()?;
+        let url = write_json(&dir, BULK_DATA_LIST)?;
+
+        let info = ScryfallBulkDataInfo::get_from(&url, ScryfallBulkDataset::OracleCards)?;
+        assert_eq!(
+            info.download_url().as_str(),
+            "https://data.scryfall.io/oracle-cards/oracle-cards-20240519090211.json"
+        );
+        assert_eq!(info.size(), Some(157286400));
+
+        let info = ScryfallBulkDataInfo::get_from(&url, ScryfallBulkDataset::DefaultCards)?;
+        assert_eq!(
+            info.updated_at(),
+            "2024-05-19T09:05:48Z".parse::<DateTime<Utc>>()?
+        );
+
+        let res = ScryfallBulkDataInfo::get_from(&url, ScryfallBulkDataset::AllCards);
+        assert!(matches!(res, Err(FetchError::Decode { .. })), "{res:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_item() -> TestResult {
+        let dir = temp_dir::TempDir::new()?;
+        let url = write_json(
+            &dir,
+            r#"{
+  "download_uri": "https://data.scryfall.io/default-cards/default-cards-20240519090548.json",
+  "updated_at": "2024-05-19T09:05:48.000+00:00"
+}"#,
+        )?;
+        let info = ScryfallBulkDataInfo::get_from(&url, ScryfallBulkDataset::DefaultCards)?;
+        assert_eq!(info.size(), None);
+
+        let url = write_json(
+            &dir,
+            r#"{
+  "type": "all_cards",
+  "download_uri": "https://data.scryfall.io/all-cards/all-cards-20240519091439.json",
+  "updated_at": "2024-05-19T09:14:39.000+00:00"
+}"#,
+        )?;
+        assert!(ScryfallBulkDataInfo::get_from(&url, ScryfallBulkDataset::DefaultCards).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dataset_from_str() -> TestResult {
+        for dataset in ScryfallBulkDataset::ALL {
+            assert_eq!(dataset.to_string().parse::<ScryfallBulkDataset>()?, dataset);
+        }
+        assert!("unique_artwork".parse::<ScryfallBulkDataset>().is_err());
+        Ok(())
+    }
+
     #[ignore = "Will download data from the Scryfall API"]
     #[test]
     fn test_get_scryfall_bulk_info() -> TestResult {
-        let scryfall_bulk_info = ScryfallBulkDataInfo::get()?;
+        let scryfall_bulk_info = ScryfallBulkDataInfo::get(ScryfallBulkDataset::DefaultCards)?;
 
         eprintln!("{scryfall_bulk_info:?}");
         eprintln!("URL: {}", scryfall_bulk_info.download_url().as_str());
@@ -4,5 +4,5 @@ pub mod sets;
 pub mod util;
 
 pub use bulk_cards::ScryfallBulkData;
-pub use bulk_info::ScryfallBulkDataInfo;
+pub use bulk_info::{ScryfallBulkDataInfo, ScryfallBulkDataset};
 pub use sets::{MtgoSet, ScryfallMtgoSets};
@@ -1,7 +1,7 @@
-use std::{error::Error, fmt, io::BufReader};
+use std::{error::Error, fmt};
 
 use chrono::{NaiveDate, Utc};
-use reqwest::blocking;
+use get_http::{FetchError, Url};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug)]
@@ -11,6 +11,8 @@ struct ApiResponseScryfallSet {
 
 #[derive(Deserialize, Debug, Clone)]
 struct Set {
+    #[serde(default)]
+    code: String,
     name: String,
     released_at: String,
     mtgo_code: Option<String>,
@@ -21,38 +23,66 @@ pub struct MtgoSet {
     pub name: String,
     pub released_at: NaiveDate,
     pub mtgo_code: String,
+    /// The Scryfall set code, which is what MTGO and Goatbots use for some sets instead of the MTGO code. Empty in
+    /// files stored before it was recorded.
+    #[serde(default)]
+    pub code: String,
 }
 
 impl MtgoSet {
     fn from_set(set: Set) -> Option<Self> {
         let mtgo_code = set.mtgo_code?;
-        let released_at: NaiveDate =
-            NaiveDate::parse_from_str(&set.released_at, "%Y-%m-%d").unwrap();
+        let released_at: NaiveDate = NaiveDate::parse_from_str(&set.released_at, "%Y-%m-%d")
+            .inspect_err(|e| {
+                log::warn!(
+                    "Skipping set {} with invalid release date {:?}: {e}",
+                    set.name,
+                    set.released_at
+                )
+            })
+            .ok()?;
 
         Some(Self {
             name: set.name,
             released_at,
             mtgo_code,
+            code: set.code,
         })
     }
 }
 
 pub struct ScryfallMtgoSets {
     mtgo_sets: Vec<MtgoSet>,
+    http_status: Option<u16>,
 }
 
 impl ScryfallMtgoSets {
     pub const FILENAME: &'static str = "scryfall-sets.json";
-    const SCRYFALL_SET_LIST_URL: &'static str = "https://api.scryfall.com/sets";
+    pub const SCRYFALL_SET_LIST_URL: &'static str = "https://api.scryfall.com/sets";
 
-    pub fn get() -> Result<Self, reqwest::Error> {
-        let resp = blocking::get(Self::SCRYFALL_SET_LIST_URL)?;
-        let stream = BufReader::new(resp);
-        let ApiResponseScryfallSet { data } = serde_json::from_reader(stream).unwrap();
+    pub fn get() -> Result<Self, FetchError> {
+        Self::get_from(&get_http::parse_url(Self::SCRYFALL_SET_LIST_URL)?)
+    }
+
+    /// Get the sets from the given URL, e.g. a mirror of [Self::SCRYFALL_SET_LIST_URL]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [FetchError] if the download fails or the response isn't the expected JSON
+    pub fn get_from(url: &Url) -> Result<Self, FetchError> {
+        let (ApiResponseScryfallSet { data }, http_status) = get_http::get_json_with_status(url)?;
         let sets: Vec<Set> = data;
         let mtgo_sets: Vec<MtgoSet> = sets.into_iter().filter_map(MtgoSet::from_set).collect();
 
-        Ok(Self { mtgo_sets })
+        Ok(Self {
+            mtgo_sets,
+            http_status,
+        })
+    }
+
+    /// The status of the HTTP response the sets were received in, [None] for `file://` URLs
+    pub fn http_status(&self) -> Option<u16> {
+        self.http_status
     }
 
     pub fn next_released_mtgo_set(&self) -> Result<Option<&MtgoSet>, Box<dyn Error>> {
@@ -0,0 +1,113 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use get_scryfall::ScryfallBulkData;
+
+use crate::{GOATBOTS_CARD_DEFINITIONS_FILENAME, GOATBOTS_PRICE_HISTORY_FILENAME};
+
+/// Paths to the card data files stored by the fetchers, which the parser needs to parse a full trade list
+#[derive(Debug)]
+pub struct CardDataPaths {
+    scryfall: PathBuf,
+    card_definitions: PathBuf,
+    price_history: PathBuf,
+}
+
+impl CardDataPaths {
+    /// Find the card data files in the appdata directory by the names they're stored under
+    ///
+    /// # Arguments
+    ///
+    /// * `appdata_dir` - The directory the data is fetched to
+    ///
+    /// # Errors
+    ///
+    /// If any of the card data files are not found, an error is returned describing which files were not found
+    pub fn find(appdata_dir: &Path) -> io::Result<Self> {
+        let mut find_errs = Vec::new();
+        let mut find = |filename: &str, description: &str| {
+            let path = appdata_dir.join(filename);
+            if !path.is_file() {
+                log::info!("Could not locate {description} in {appdata_dir:?}");
+                find_errs.push(format!("Could not find {description} in {appdata_dir:?}"));
+            }
+            path
+        };
+        let scryfall = find(ScryfallBulkData::FILENAME, "Scryfall data JSON");
+        let card_definitions = find(GOATBOTS_CARD_DEFINITIONS_FILENAME, "card definition JSON");
+        let price_history = find(GOATBOTS_PRICE_HISTORY_FILENAME, "price history JSON");
+
+        if !find_errs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                find_errs.join("\n"),
+            ));
+        }
+
+        Ok(Self {
+            scryfall,
+            card_definitions,
+            price_history,
+        })
+    }
+
+    /// Get the path to the scryfall data JSON-file
+    pub fn scryfall_path(&self) -> &Path {
+        self.scryfall.as_path()
+    }
+
+    /// Get the path to the card definitions JSON-file
+    pub fn card_definitions_path(&self) -> &Path {
+        self.card_definitions.as_path()
+    }
+
+    /// Get the path to the price history JSON-file
+    pub fn price_history_path(&self) -> &Path {
+        self.price_history.as_path()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::releases::PREVIOUS_CARD_DEFINITIONS_FILENAME;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_find_ignores_similar_names() -> TestResult {
+        let dir = TempDir::new()?;
+        for leftover in [
+            "card-definitions.json.tmp",
+            "price-history.json.tmp",
+            PREVIOUS_CARD_DEFINITIONS_FILENAME,
+        ] {
+            fs::write(dir.child(leftover), "{}")?;
+        }
+
+        let err = CardDataPaths::find(dir.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(err.to_string().lines().count(), 3);
+
+        for filename in [
+            ScryfallBulkData::FILENAME,
+            GOATBOTS_CARD_DEFINITIONS_FILENAME,
+            GOATBOTS_PRICE_HISTORY_FILENAME,
+        ] {
+            fs::write(dir.child(filename), "{}")?;
+        }
+        let paths = CardDataPaths::find(dir.path())?;
+        assert_eq!(paths.scryfall_path(), dir.child("scryfall-cards.json"));
+        assert_eq!(
+            paths.card_definitions_path(),
+            dir.child("card-definitions.json")
+        );
+        assert_eq!(paths.price_history_path(), dir.child("price-history.json"));
+        Ok(())
+    }
+}
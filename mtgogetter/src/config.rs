@@ -0,0 +1,408 @@
+use std::{fs, io, path::Path};
+
+use get_goatbots::{GOATBOTS_CARD_DEFINITIONS_URL, GOATBOTS_PRICE_HISTORY_URL};
+use get_http::{ClientSettings, FetchError, Url};
+use get_scryfall::{
+    util::format_datetime_utc_for_url, ScryfallBulkDataInfo, ScryfallBulkDataset, ScryfallMtgoSets,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::refresh::{RefreshPolicies, RefreshPolicy};
+
+/// Configuration of MTGO Getter, also known as `mtgogetter.toml`
+///
+/// Every value can be overridden with an environment variable, which takes precedence over the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The Scryfall bulk dataset the card data is taken from
+    pub scryfall_bulk_dataset: ScryfallBulkDataset,
+    pub endpoints: Endpoints,
+    pub refresh: RefreshPolicies,
+    /// Proxy, TLS and connection settings of all HTTP(S) requests
+    pub http: ClientSettings,
+}
+
+impl Config {
+    pub const FILENAME: &'static str = "mtgogetter.toml";
+
+    /// Load the config from `mtgogetter.toml` in the given directory (if it exists) and apply the environment overrides
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file exists but cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Self, io::Error> {
+        Self::load_with_env(dir, |key| std::env::var(key).ok())
+    }
+
+    /// Like [Self::load] but the overrides are looked up with `lookup` instead of in the environment
+    fn load_with_env(
+        dir: &Path,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, io::Error> {
+        let path = dir.join(Self::FILENAME);
+        let mut config = if path.exists() {
+            Self::from_toml_file(&path)?
+        } else {
+            Self::default()
+        };
+        config.apply_env_overrides(lookup);
+        Ok(config)
+    }
+
+    pub fn from_toml_file(p: &Path) -> Result<Self, io::Error> {
+        let contents = fs::read_to_string(p)?;
+        toml::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Override values with the ones found by `lookup` (the environment variables outside of tests)
+    fn apply_env_overrides(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        let endpoints = &mut self.endpoints;
+        for (key, field) in [
+            ("MCM_MIRROR_URL", &mut endpoints.mirror),
+            ("MCM_SCRYFALL_SETS_URL", &mut endpoints.scryfall_sets),
+            (
+                "MCM_SCRYFALL_BULK_DATA_INFO_URL",
+                &mut endpoints.scryfall_bulk_data_info,
+            ),
+            (
+                "MCM_SCRYFALL_BULK_DATA_URL_PREFIX",
+                &mut endpoints.scryfall_bulk_data_prefix,
+            ),
+            (
+                "MCM_GOATBOTS_PRICE_HISTORY_URL",
+                &mut endpoints.goatbots_price_history,
+            ),
+            (
+                "MCM_GOATBOTS_CARD_DEFINITIONS_URL",
+                &mut endpoints.goatbots_card_definitions,
+            ),
+        ] {
+            if let Some(value) = lookup(key) {
+                log::info!("{key} overrides the configured value with {value}");
+                *field = Some(value);
+            }
+        }
+
+        if let Some(value) = lookup("MCM_SCRYFALL_BULK_DATASET") {
+            match value.parse() {
+                Ok(dataset) => {
+                    log::info!(
+                        "MCM_SCRYFALL_BULK_DATASET overrides the configured dataset with {value}"
+                    );
+                    self.scryfall_bulk_dataset = dataset;
+                }
+                Err(e) => log::warn!("Ignoring MCM_SCRYFALL_BULK_DATASET: {e}"),
+            }
+        }
+
+        let http = &mut self.http;
+        for (key, field) in [
+            ("MCM_HTTP_PROXY", &mut http.proxy),
+            ("MCM_HTTP_NO_PROXY", &mut http.no_proxy),
+        ] {
+            if let Some(value) = lookup(key) {
+                log::info!("{key} overrides the configured value with {value}");
+                *field = Some(value);
+            }
+        }
+        if let Some(value) = lookup("MCM_HTTP_CA_BUNDLE") {
+            log::info!("MCM_HTTP_CA_BUNDLE overrides the configured CA bundle with {value}");
+            http.ca_bundle = Some(value.into());
+        }
+        if let Some(value) = lookup("MCM_HTTP_DISABLE_CONNECTION_REUSE") {
+            match value.parse() {
+                Ok(disable) => {
+                    log::info!("MCM_HTTP_DISABLE_CONNECTION_REUSE overrides the configured value with {value}");
+                    http.disable_connection_reuse = disable;
+                }
+                Err(e) => log::warn!("Ignoring MCM_HTTP_DISABLE_CONNECTION_REUSE: {e}"),
+            }
+        }
+
+        let refresh = &mut self.refresh;
+        for (key, policy) in [
+            ("MCM_REFRESH_GOATBOTS_PRICES", &mut refresh.goatbots_prices),
+            (
+                "MCM_REFRESH_GOATBOTS_CARD_DEFINITIONS",
+                &mut refresh.goatbots_card_definitions,
+            ),
+            (
+                "MCM_REFRESH_SCRYFALL_BULK_DATA",
+                &mut refresh.scryfall_bulk_data,
+            ),
+        ] {
+            if let Some(value) = lookup(key) {
+                match value.parse::<RefreshPolicy>() {
+                    Ok(value) => {
+                        log::info!("{key} overrides the configured refresh policy with {value}");
+                        *policy = value;
+                    }
+                    Err(e) => log::warn!("Ignoring {key}: {e}"),
+                }
+            }
+        }
+    }
+}
+
+/// The URLs the data is fetched from, `http(s)://` and `file://` URLs are supported.
+///
+/// If `mirror` is set, every endpoint that isn't explicitly set is resolved relative to it with the layout:
+///
+/// ```text
+/// <mirror>/scryfall/sets.json
+/// <mirror>/scryfall/bulk-data-info.json
+/// <mirror>/scryfall/<file name of the advertised download URI, e.g. default-cards-<YYYYMMDDHHMMSS>.json>
+/// <mirror>/goatbots/price-history.zip
+/// <mirror>/goatbots/card-definitions.zip
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Endpoints {
+    pub mirror: Option<String>,
+    pub scryfall_sets: Option<String>,
+    pub scryfall_bulk_data_info: Option<String>,
+    /// If set, the bulk data URL is this prefix followed by `<YYYYMMDDHHMMSS>.json` instead of the advertised URI
+    pub scryfall_bulk_data_prefix: Option<String>,
+    pub goatbots_price_history: Option<String>,
+    pub goatbots_card_definitions: Option<String>,
+}
+
+impl Endpoints {
+    pub fn scryfall_sets(&self) -> Result<Url, FetchError> {
+        self.resolve(
+            &self.scryfall_sets,
+            "scryfall/sets.json",
+            ScryfallMtgoSets::SCRYFALL_SET_LIST_URL,
+        )
+    }
+
+    pub fn scryfall_bulk_data_info(&self) -> Result<Url, FetchError> {
+        self.resolve(
+            &self.scryfall_bulk_data_info,
+            "scryfall/bulk-data-info.json",
+            ScryfallBulkDataInfo::LIST_URL,
+        )
+    }
+
+    /// The URL to download the bulk data described by `info` from, the advertised URI unless a prefix or mirror is
+    /// configured
+    pub fn scryfall_bulk_data(&self, info: &ScryfallBulkDataInfo) -> Result<Url, FetchError> {
+        let advertised = info.download_url();
+        match (&self.scryfall_bulk_data_prefix, &self.mirror) {
+            (Some(prefix), _) => get_http::parse_url(&format!(
+                "{prefix}{}.json",
+                format_datetime_utc_for_url(info.updated_at())
+            )),
+            (None, Some(mirror)) => {
+                let filename = advertised
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .unwrap_or_default();
+                mirror_url(mirror, &format!("scryfall/{filename}"))
+            }
+            (None, None) => Ok(advertised.clone()),
+        }
+    }
+
+    pub fn goatbots_price_history(&self) -> Result<Url, FetchError> {
+        self.resolve(
+            &self.goatbots_price_history,
+            "goatbots/price-history.zip",
+            GOATBOTS_PRICE_HISTORY_URL,
+        )
+    }
+
+    pub fn goatbots_card_definitions(&self) -> Result<Url, FetchError> {
+        self.resolve(
+            &self.goatbots_card_definitions,
+            "goatbots/card-definitions.zip",
+            GOATBOTS_CARD_DEFINITIONS_URL,
+        )
+    }
+
+    fn resolve(
+        &self,
+        explicit: &Option<String>,
+        mirror_path: &str,
+        default: &str,
+    ) -> Result<Url, FetchError> {
+        match (explicit, &self.mirror) {
+            (Some(url), _) => get_http::parse_url(url),
+            (None, Some(mirror)) => mirror_url(mirror, mirror_path),
+            (None, None) => get_http::parse_url(default),
+        }
+    }
+}
+
+/// Join a path onto the mirror URL, the mirror is treated as a directory whether or not it ends with a `/`
+fn mirror_url(mirror: &str, path: &str) -> Result<Url, FetchError> {
+    let base = if mirror.ends_with('/') {
+        get_http::parse_url(mirror)?
+    } else {
+        get_http::parse_url(&format!("{mirror}/"))?
+    };
+    base.join(path).map_err(|e| FetchError::InvalidUrl {
+        url: format!("{base}{path}"),
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    fn bulk_data_info() -> Result<ScryfallBulkDataInfo, Box<dyn std::error::Error>> {
+        Ok(ScryfallBulkDataInfo::new(
+            ScryfallBulkDataset::OracleCards,
+            get_http::parse_url(
+                "https://data.scryfall.io/oracle-cards/oracle-cards-20240519090211.json",
+            )?,
+            "2024-05-19T09:02:11Z".parse()?,
+        ))
+    }
+
+    #[test]
+    fn test_defaults() -> TestResult {
+        let endpoints = Endpoints::default();
+        assert_eq!(
+            endpoints.scryfall_sets()?.as_str(),
+            ScryfallMtgoSets::SCRYFALL_SET_LIST_URL
+        );
+        assert_eq!(
+            endpoints.goatbots_price_history()?.as_str(),
+            GOATBOTS_PRICE_HISTORY_URL
+        );
+        assert_eq!(
+            endpoints.scryfall_bulk_data_info()?.as_str(),
+            ScryfallBulkDataInfo::LIST_URL
+        );
+        let info = bulk_data_info()?;
+        assert_eq!(&endpoints.scryfall_bulk_data(&info)?, info.download_url());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirror_and_explicit_endpoints() -> TestResult {
+        let endpoints = Endpoints {
+            mirror: Some("file:///srv/mcm-mirror".into()),
+            goatbots_card_definitions: Some("http://localhost:8080/defs.zip".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            endpoints.scryfall_sets()?.as_str(),
+            "file:///srv/mcm-mirror/scryfall/sets.json"
+        );
+        assert_eq!(
+            endpoints.scryfall_bulk_data(&bulk_data_info()?)?.as_str(),
+            "file:///srv/mcm-mirror/scryfall/oracle-cards-20240519090211.json"
+        );
+        let endpoints = Endpoints {
+            scryfall_bulk_data_prefix: Some("http://localhost:8080/cards-".into()),
+            ..endpoints
+        };
+        assert_eq!(
+            endpoints.scryfall_bulk_data(&bulk_data_info()?)?.as_str(),
+            "http://localhost:8080/cards-20240519090211.json"
+        );
+        assert_eq!(
+            endpoints.goatbots_card_definitions()?.as_str(),
+            "http://localhost:8080/defs.zip"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_from_file_with_env_overrides() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.child(Config::FILENAME),
+            r#"
+[endpoints]
+mirror = "https://mirror.example.com/mcm/"
+scryfall_sets = "https://mirror.example.com/sets.json"
+"#,
+        )?;
+        let mut config = Config::from_toml_file(&temp_dir.child(Config::FILENAME))?;
+        config.apply_env_overrides(|key| {
+            (key == "MCM_SCRYFALL_SETS_URL").then(|| "file:///tmp/sets.json".to_owned())
+        });
+
+        assert_eq!(
+            config.endpoints.mirror.as_deref(),
+            Some("https://mirror.example.com/mcm/")
+        );
+        assert_eq!(
+            config.endpoints.scryfall_sets()?.as_str(),
+            "file:///tmp/sets.json"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_refresh_policies_with_env_overrides() -> TestResult {
+        let mut config: Config = toml::from_str(
+            r#"
+[refresh]
+goatbots_prices = "daily 06:00"
+scryfall_bulk_data = "every 24h"
+"#,
+        )?;
+        config.apply_env_overrides(|key| match key {
+            "MCM_REFRESH_SCRYFALL_BULK_DATA" => Some("manual".to_owned()),
+            "MCM_REFRESH_GOATBOTS_CARD_DEFINITIONS" => Some("sometimes".to_owned()),
+            _ => None,
+        });
+
+        assert_eq!(
+            config.refresh,
+            RefreshPolicies {
+                goatbots_prices: "daily 06:00".parse()?,
+                goatbots_card_definitions: RefreshPolicy::Auto,
+                scryfall_bulk_data: RefreshPolicy::Manual,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_settings_with_env_overrides() -> TestResult {
+        let mut config: Config = toml::from_str(
+            r#"
+[http]
+proxy = "http://proxy.example.com:8080"
+no_proxy = "localhost,.internal.example.com"
+"#,
+        )?;
+        config.apply_env_overrides(|key| match key {
+            "MCM_HTTP_CA_BUNDLE" => Some("/etc/ssl/corporate.pem".to_owned()),
+            "MCM_HTTP_DISABLE_CONNECTION_REUSE" => Some("true".to_owned()),
+            _ => None,
+        });
+
+        assert_eq!(
+            config.http,
+            ClientSettings {
+                proxy: Some("http://proxy.example.com:8080".to_owned()),
+                no_proxy: Some("localhost,.internal.example.com".to_owned()),
+                ca_bundle: Some("/etc/ssl/corporate.pem".into()),
+                disable_connection_reuse: true,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_config_file_is_default() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        // Without the environment of the test runner
+        assert_eq!(
+            Config::load_with_env(temp_dir.path(), |_| None)?,
+            Config::default()
+        );
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
-use std::{fs, io, path::Path};
+use std::{io, path::Path};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use get_http::Validators;
+use get_scryfall::ScryfallBulkDataset;
 use serde::{Deserialize, Serialize};
+use state_file::schema::{self, Migration, Versioned};
 
 mod goatbots_md;
 use goatbots_md::GoatBotsMetaData;
@@ -10,10 +13,18 @@ pub mod scryfall_md;
 use scryfall_md::ScryfallMetaData;
 
 use self::scryfall_md::next_released_mtgo_set::NextReleasedMtgoSet;
+use crate::{releases::ReleaseCountdown, FetchPhase};
 
 /// Also known as `fetch_log.toml`
+///
+/// The `ETag`/`Last-Modified` validators are only kept for the Goatbots files, which are served from the same URLs and
+/// re-downloaded by time-based rules. Every update of the Scryfall bulk data is published under a URL of its own, so a
+/// conditional request for it could never get a `304 Not Modified` (an interrupted download keeps its validators in
+/// `<file>.part.json` to resume it). The Scryfall sets are only fetched once the next set is out, to find the one after
+/// it, which a `304 Not Modified` couldn't answer without the full response.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CardInfoMetaData {
+    schema_version: u32,
     description: Box<str>,
     goatbots: GoatBotsMetaData,
     scryfall: ScryfallMetaData,
@@ -25,25 +36,27 @@ impl CardInfoMetaData {
 
     /// Create a first time [CardInfoMetaData] state log.
     /// From then on it should always be edited/updated on disk instead of creating a new one.
-    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
+            schema_version: Self::SCHEMA_VERSION,
             description: Self::DESCRIPTION.into(),
             goatbots: GoatBotsMetaData::default(),
             scryfall: ScryfallMetaData::default(),
         }
     }
 
+    /// Load the fetch log and migrate it to the current schema, see [schema::load_versioned]
     pub fn from_toml_file(p: &Path) -> Result<Self, io::Error> {
-        let contents = fs::read_to_string(p)?;
-        // TODO: Handle/converge error types here (thiserror?)
-        let toml: CardInfoMetaData = toml::from_str(&contents).unwrap();
-        Ok(toml)
+        schema::load_versioned(p)
+    }
+
+    /// Load the fetch log, or a new one if it's missing or unreadable, see [schema::load_versioned_or_reset]
+    pub fn from_toml_file_or_reset(p: &Path) -> Result<Self, io::Error> {
+        schema::load_versioned_or_reset(p)
     }
 
     pub fn to_toml_on_disk(&self, p: &Path) -> Result<(), io::Error> {
-        let toml = toml::to_string(self).unwrap();
-        fs::write(p, toml)
+        schema::save_versioned(self, p)
     }
 
     pub fn goatbots_prices_updated_at(&self) -> Option<DateTime<Utc>> {
@@ -64,36 +77,69 @@ impl CardInfoMetaData {
     ///
     /// it's updated unless a new set has been released and it's been >20 minutes since last update
     pub fn is_card_definitions_updated(&self) -> bool {
+        self.is_card_definitions_updated_at(Utc::now())
+    }
+
+    /// See [Self::is_card_definitions_updated]
+    ///
+    /// Goatbots adds the cards of a new set during the day of the release, so the card definitions are updated every
+    /// 20 minutes until the day is over, and once more if they weren't updated after it.
+    fn is_card_definitions_updated_at(&self, now: DateTime<Utc>) -> bool {
         let card_definitions_updated_at: DateTime<Utc> =
             match self.goatbots.card_definitions_updated_at() {
                 Some(date) => date,
                 None => return false, // They were never updated
             };
 
-        if self.scryfall.is_next_set_out() {
-            return false; // There's no next set, assume they need to be updated (were never updated in the first place)
+        let today = now.date_naive();
+        if self.scryfall.is_next_set_out(today) {
+            return false; // There's no next set or it's out, the sets are fetched before the card definitions
         }
 
-        let twenty_minutes_ago = Utc::now() - chrono::Duration::minutes(20);
-        let next_release_date = self
-            .scryfall
-            .next_released_mtgo_set()
-            .released_at()
-            .unwrap();
+        let released_at = match self.scryfall.next_released_mtgo_set().released_at() {
+            Some(released_at) if released_at == today => released_at,
+            _ => match self.scryfall.last_released_at() {
+                Some(released_at) => released_at,
+                None => return true, // No set was released since the next released set was first recorded
+            },
+        };
+        let release_day_over = (released_at + chrono::Days::new(1))
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        let twenty_minutes_ago = now - chrono::Duration::minutes(20);
 
-        // If the next release date is after the current date,
-        //  or the card definitions were updated less than 20 min. ago
-        //  then consider card definitions to be updated
-        (next_release_date >= Utc::now().date_naive())
-            || (card_definitions_updated_at < twenty_minutes_ago)
+        card_definitions_updated_at >= release_day_over
+            || (now < release_day_over && card_definitions_updated_at > twenty_minutes_ago)
     }
 
     /// Refresh the timestamp by assigning the current UTC time.
+    pub fn goatbots_card_definitions_updated_at(&self) -> Option<DateTime<Utc>> {
+        self.goatbots.card_definitions_updated_at()
+    }
+
     pub fn refresh_card_definitions_updated_at_timestamp(&mut self) {
         self.goatbots
             .refresh_card_definitions_updated_at_timestamp();
     }
 
+    /// The `ETag`/`Last-Modified` of the last price history download
+    pub fn goatbots_prices_validators(&self) -> &Validators {
+        self.goatbots.prices_validators()
+    }
+
+    pub fn set_goatbots_prices_validators(&mut self, validators: Validators) {
+        self.goatbots.set_prices_validators(validators);
+    }
+
+    /// The `ETag`/`Last-Modified` of the last card definitions download
+    pub fn goatbots_card_definitions_validators(&self) -> &Validators {
+        self.goatbots.card_definitions_validators()
+    }
+
+    pub fn set_goatbots_card_definitions_validators(&mut self, validators: Validators) {
+        self.goatbots.set_card_definitions_validators(validators);
+    }
+
     pub fn scryfall_bulk_data_updated_at(&self) -> Option<DateTime<Utc>> {
         self.scryfall.bulk_data_updated_at()
     }
@@ -106,6 +152,15 @@ impl CardInfoMetaData {
             .is_some_and(|dt| dt > api_timestamp)
     }
 
+    /// The Scryfall bulk dataset of the last bulk data download
+    pub fn scryfall_bulk_dataset(&self) -> ScryfallBulkDataset {
+        self.scryfall.bulk_dataset()
+    }
+
+    pub fn set_scryfall_bulk_dataset(&mut self, dataset: ScryfallBulkDataset) {
+        self.scryfall.set_bulk_dataset(dataset);
+    }
+
     /// Refresh the timestamp by assigning the current UTC time.
     pub fn refresh_bulk_data_updated_at_timestamp(&mut self) {
         self.scryfall.refresh_bulk_data_updated_at_timestamp();
@@ -115,13 +170,52 @@ impl CardInfoMetaData {
     //
     // If it is out, we want to update which set is the next to come out
     pub fn is_next_set_out(&self) -> bool {
-        self.scryfall.is_next_set_out()
+        self.scryfall.is_next_set_out(Utc::now().date_naive())
+    }
+
+    /// The countdown from `today` to the release of the next MTGO set, [None] if the next set isn't known
+    pub fn next_release_countdown(&self, today: NaiveDate) -> Option<ReleaseCountdown> {
+        let next_set = self.scryfall.next_released_mtgo_set();
+        Some(ReleaseCountdown::new(
+            next_set.name()?.to_owned(),
+            next_set.mtgo_code()?.to_owned(),
+            next_set.released_at()?,
+            today,
+        ))
     }
 
     /// Replace the [NextReleasedMtgoSet] with the given set
     pub fn replace_next_released_set(&mut self, next_set: NextReleasedMtgoSet) {
-        self.scryfall.replace_next_released_set(next_set);
+        self.scryfall
+            .replace_next_released_set(next_set, Utc::now().date_naive());
     }
+
+    /// Take the state written by the fetcher of the given [FetchPhase] from `other`, leaving everything else as is
+    ///
+    /// Used to combine the fetch logs of fetchers that ran concurrently on their own copies.
+    pub fn merge_phase(&mut self, phase: FetchPhase, other: &Self) {
+        match phase {
+            FetchPhase::ScryfallSets => self.scryfall.merge_next_released_set(&other.scryfall),
+            FetchPhase::ScryfallBulkInfo => (),
+            FetchPhase::ScryfallBulkCards => self.scryfall.merge_bulk_data(&other.scryfall),
+            FetchPhase::GoatbotsCardDefinitions => {
+                self.goatbots.merge_card_definitions(&other.goatbots)
+            }
+            FetchPhase::GoatbotsPriceHistory => self.goatbots.merge_prices(&other.goatbots),
+        }
+    }
+}
+
+impl Default for CardInfoMetaData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Versioned for CardInfoMetaData {
+    const SCHEMA_VERSION: u32 = 1;
+    /// Version 1 added the `schema_version`
+    const MIGRATIONS: &'static [Migration] = &[|_| Ok(())];
 }
 
 #[cfg(test)]
@@ -149,6 +243,7 @@ mod tests {
         );
 
         let cardinfo_metadata = CardInfoMetaData {
+            schema_version: CardInfoMetaData::SCHEMA_VERSION,
             description: CardInfoMetaData::DESCRIPTION.into(),
             goatbots: goatbots_metadata,
             scryfall: scryfall_metadata,
@@ -161,6 +256,7 @@ mod tests {
         assert_str_eq!(
             serialized,
             "\
+schema_version = 1
 description = \"log for MTGO Getter state, such as updated_at timestamps\"
 
 [goatbots]
@@ -183,10 +279,31 @@ mtgo_code = \"lci\"
         Ok(())
     }
 
+    /// A fetch log written before the `schema_version` was introduced
+    #[test]
+    fn test_migrate_unversioned_fetch_log() -> TestResult {
+        let unversioned = "\
+description = \"log for MTGO Getter state, such as updated_at timestamps\"
+
+[goatbots]
+prices_updated_at = \"2023-10-14T15:24:21Z\"
+
+[scryfall.next_released_mtgo_set]
+";
+        let fetch_log: CardInfoMetaData = schema::from_versioned_toml(unversioned)?;
+        assert_eq!(fetch_log.schema_version, CardInfoMetaData::SCHEMA_VERSION);
+        assert_eq!(
+            fetch_log.goatbots_prices_updated_at(),
+            Some("2023-10-14T15:24:21Z".parse()?)
+        );
+        Ok(())
+    }
+
     /// Should be false cause no data was ever fetched
     #[test]
     fn test_is_scryfall_bulk_updated_false_cause_empty() -> TestResult {
         let cardinfo_metadata = CardInfoMetaData {
+            schema_version: CardInfoMetaData::SCHEMA_VERSION,
             description: CardInfoMetaData::DESCRIPTION.into(),
             goatbots: GoatBotsMetaData::default(),
             scryfall: ScryfallMetaData::default(),
@@ -203,6 +320,7 @@ mtgo_code = \"lci\"
     #[test]
     fn test_is_scryfall_bulk_updated_true() -> TestResult {
         let cardinfo_metadata = CardInfoMetaData {
+            schema_version: CardInfoMetaData::SCHEMA_VERSION,
             description: CardInfoMetaData::DESCRIPTION.into(),
             goatbots: GoatBotsMetaData::default(),
             scryfall: ScryfallMetaData::new(Some(Utc::now()), NextReleasedMtgoSet::default()),
@@ -220,6 +338,7 @@ mtgo_code = \"lci\"
     #[test]
     fn test_is_scryfall_bulk_updated_false() -> TestResult {
         let cardinfo_metadata = CardInfoMetaData {
+            schema_version: CardInfoMetaData::SCHEMA_VERSION,
             description: CardInfoMetaData::DESCRIPTION.into(),
             goatbots: GoatBotsMetaData::default(),
             scryfall: ScryfallMetaData::new(
@@ -235,4 +354,74 @@ mtgo_code = \"lci\"
 
         Ok(())
     }
+
+    #[test]
+    fn test_card_definitions_are_updated_after_a_release() -> TestResult {
+        let set = |name: &str, released_at: NaiveDate| {
+            NextReleasedMtgoSet::new(
+                Some(name.to_string()),
+                Some(released_at),
+                Some(name.to_lowercase()),
+            )
+        };
+        let release_day: NaiveDate = "2024-06-14".parse()?;
+        let mut fetch_log = CardInfoMetaData::new();
+        fetch_log
+            .scryfall
+            .replace_next_released_set(set("MH3", release_day), "2024-06-01".parse()?);
+        fetch_log.goatbots = GoatBotsMetaData::new(Some("2024-06-13T12:00:00Z".parse()?), None);
+        assert!(fetch_log.is_card_definitions_updated_at("2024-06-13T18:00:00Z".parse()?));
+        assert!(!fetch_log.is_card_definitions_updated_at("2024-06-14T00:30:00Z".parse()?));
+
+        // Updated every 20 minutes during the day of the release
+        fetch_log.goatbots = GoatBotsMetaData::new(Some("2024-06-14T00:30:00Z".parse()?), None);
+        assert!(fetch_log.is_card_definitions_updated_at("2024-06-14T00:45:00Z".parse()?));
+        assert!(!fetch_log.is_card_definitions_updated_at("2024-06-14T00:55:00Z".parse()?));
+
+        // The day after, the next set is out so the sets are fetched first
+        assert!(fetch_log.scryfall.is_next_set_out("2024-06-15".parse()?));
+        assert!(!fetch_log.is_card_definitions_updated_at("2024-06-15T08:00:00Z".parse()?));
+        fetch_log
+            .scryfall
+            .replace_next_released_set(set("BLB", "2024-08-02".parse()?), "2024-06-15".parse()?);
+        assert_eq!(fetch_log.scryfall.last_released_at(), Some(release_day));
+        // And the card definitions are updated once more
+        assert!(!fetch_log.is_card_definitions_updated_at("2024-06-15T08:00:00Z".parse()?));
+        fetch_log.goatbots = GoatBotsMetaData::new(Some("2024-06-15T08:00:00Z".parse()?), None);
+        assert!(fetch_log.is_card_definitions_updated_at("2024-06-20T08:00:00Z".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_release_countdown() {
+        let mut fetch_log = CardInfoMetaData::new();
+        let today = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+        assert_eq!(fetch_log.next_release_countdown(today), None);
+
+        fetch_log.replace_next_released_set(NextReleasedMtgoSet::new(
+            Some("The Lost Caverns of Ixalan".to_string()),
+            Some(NaiveDate::from_ymd_opt(2023, 12, 11).unwrap()),
+            Some("lci".to_string()),
+        ));
+        let countdown = fetch_log.next_release_countdown(today).unwrap();
+        assert_eq!(countdown.mtgo_code, "lci");
+        assert_eq!(countdown.days_left, 10);
+    }
+
+    #[test]
+    fn test_merge_phase() {
+        let mut fetch_log = CardInfoMetaData::new();
+        let mut prices_log = fetch_log.clone();
+        prices_log.refresh_prices_updated_at_timestamp();
+        prices_log.refresh_bulk_data_updated_at_timestamp();
+
+        fetch_log.merge_phase(FetchPhase::GoatbotsPriceHistory, &prices_log);
+
+        assert_eq!(
+            fetch_log.goatbots_prices_updated_at(),
+            prices_log.goatbots_prices_updated_at()
+        );
+        // Only the state of the given phase is merged
+        assert_eq!(fetch_log.scryfall_bulk_data_updated_at(), None);
+    }
 }
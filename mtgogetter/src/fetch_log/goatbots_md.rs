@@ -1,11 +1,18 @@
 #![allow(dead_code)]
 use chrono::{DateTime, NaiveDateTime, NaiveTime, Utc};
+use get_http::Validators;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub(super) struct GoatBotsMetaData {
     card_definitions_updated_at: Option<DateTime<Utc>>,
     prices_updated_at: Option<DateTime<Utc>>,
+    /// The `ETag`/`Last-Modified` of the last card definitions download
+    #[serde(default, skip_serializing_if = "Validators::is_empty")]
+    card_definitions_validators: Validators,
+    /// The `ETag`/`Last-Modified` of the last price history download
+    #[serde(default, skip_serializing_if = "Validators::is_empty")]
+    prices_validators: Validators,
 }
 
 impl GoatBotsMetaData {
@@ -16,9 +23,38 @@ impl GoatBotsMetaData {
         Self {
             card_definitions_updated_at,
             prices_updated_at,
+            ..Default::default()
         }
     }
 
+    pub(super) fn card_definitions_validators(&self) -> &Validators {
+        &self.card_definitions_validators
+    }
+
+    pub(super) fn set_card_definitions_validators(&mut self, validators: Validators) {
+        self.card_definitions_validators = validators;
+    }
+
+    pub(super) fn prices_validators(&self) -> &Validators {
+        &self.prices_validators
+    }
+
+    pub(super) fn set_prices_validators(&mut self, validators: Validators) {
+        self.prices_validators = validators;
+    }
+
+    /// Take the card definitions state from `other`
+    pub(super) fn merge_card_definitions(&mut self, other: &Self) {
+        self.card_definitions_updated_at = other.card_definitions_updated_at;
+        self.card_definitions_validators = other.card_definitions_validators.clone();
+    }
+
+    /// Take the price history state from `other`
+    pub(super) fn merge_prices(&mut self, other: &Self) {
+        self.prices_updated_at = other.prices_updated_at;
+        self.prices_validators = other.prices_validators.clone();
+    }
+
     pub fn card_definitions_updated_at(&self) -> Option<DateTime<Utc>> {
         self.card_definitions_updated_at
     }
@@ -86,6 +122,7 @@ mod tests {
         let goatbots_metadata = GoatBotsMetaData {
             card_definitions_updated_at: Some(card_defs_updated_at_datetime),
             prices_updated_at: Some(prices_updated_at_datetime),
+            ..Default::default()
         };
 
         let serialized = toml::to_string(&goatbots_metadata)?;
@@ -103,11 +140,34 @@ prices_updated_at = \"2023-10-14T15:24:21Z\"
         Ok(())
     }
 
+    #[test]
+    fn test_goatbots_metadata_validators_roundtrip() -> TestResult {
+        let mut goatbots_metadata = GoatBotsMetaData::default();
+        goatbots_metadata.set_prices_validators(Validators {
+            etag: Some("\"5f2b\"".into()),
+            last_modified: Some("Sun, 02 Jun 2024 04:00:00 GMT".into()),
+        });
+
+        let serialized = toml::to_string(&goatbots_metadata)?;
+        assert_str_eq!(
+            serialized,
+            "\
+[prices_validators]
+etag = \"\\\"5f2b\\\"\"
+last_modified = \"Sun, 02 Jun 2024 04:00:00 GMT\"
+"
+        );
+        let deserialized: GoatBotsMetaData = toml::from_str(&serialized)?;
+        assert_eq!(deserialized, goatbots_metadata);
+        Ok(())
+    }
+
     #[test]
     fn test_is_prices_updated_true() -> TestResult {
         let goatbots_metadata = GoatBotsMetaData {
             card_definitions_updated_at: Some("2023-10-21T22:29:53Z".parse()?),
             prices_updated_at: Some(Utc::now()),
+            ..Default::default()
         };
 
         assert!(goatbots_metadata.is_price_updated());
@@ -119,6 +179,7 @@ prices_updated_at = \"2023-10-14T15:24:21Z\"
         let goatbots_metadata = GoatBotsMetaData {
             card_definitions_updated_at: Some("2023-10-21T22:29:53Z".parse()?),
             prices_updated_at: Some(Utc.with_ymd_and_hms(2023, 10, 1, 0, 1, 1).unwrap()),
+            ..Default::default()
         };
 
         assert_eq!(goatbots_metadata.is_price_updated(), false);
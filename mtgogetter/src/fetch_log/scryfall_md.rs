@@ -1,5 +1,6 @@
 #![allow(dead_code)]
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use get_scryfall::ScryfallBulkDataset;
 use serde::{Deserialize, Serialize};
 
 pub mod next_released_mtgo_set;
@@ -9,6 +10,12 @@ use self::next_released_mtgo_set::NextReleasedMtgoSet;
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ScryfallMetaData {
     bulk_data_updated_at: Option<DateTime<Utc>>,
+    /// The dataset of the last bulk data download, [None] if it was downloaded before the dataset was selectable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bulk_dataset: Option<ScryfallBulkDataset>,
+    /// The release date of the last set that was recorded as the next released set and has been released since
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_released_at: Option<NaiveDate>,
     next_released_mtgo_set: NextReleasedMtgoSet,
 }
 
@@ -19,6 +26,8 @@ impl ScryfallMetaData {
     ) -> Self {
         Self {
             bulk_data_updated_at,
+            bulk_dataset: None,
+            last_released_at: None,
             next_released_mtgo_set,
         }
     }
@@ -33,22 +42,62 @@ impl ScryfallMetaData {
         self.bulk_data_updated_at = Some(Utc::now())
     }
 
+    /// The dataset of the last bulk data download, which was always the default cards before it was selectable
+    pub fn bulk_dataset(&self) -> ScryfallBulkDataset {
+        self.bulk_dataset.unwrap_or_default()
+    }
+
+    pub(super) fn set_bulk_dataset(&mut self, dataset: ScryfallBulkDataset) {
+        self.bulk_dataset = Some(dataset);
+    }
+
     pub fn next_released_mtgo_set(&self) -> &NextReleasedMtgoSet {
         &self.next_released_mtgo_set
     }
 
+    /// The release date of the last set that was recorded as the next released set and has been released since
+    pub fn last_released_at(&self) -> Option<NaiveDate> {
+        self.last_released_at
+    }
+
     // Returns if the next set to come out is now out on MTGO.
     //
     // If it is out, we want to update which set is the next to come out
-    pub(super) fn is_next_set_out(&self) -> bool {
-        // If the name is empty we never set the next released set or it is out
-        // either way that means it needs to be updated
+    pub(super) fn is_next_set_out(&self, today: NaiveDate) -> bool {
+        // If the name is empty we never set the next released set, either way that means it needs to be updated.
+        // A set released today is still the next released set until the day is over, same as on Scryfall
         self.next_released_mtgo_set.is_any_none()
+            || self
+                .next_released_mtgo_set
+                .released_at()
+                .is_some_and(|released_at| released_at < today)
     }
 
-    pub(super) fn replace_next_released_set(&mut self, next_set: NextReleasedMtgoSet) {
+    /// Replace the next released set, and record the release date of the current one if it was released before `today`
+    pub(super) fn replace_next_released_set(
+        &mut self,
+        next_set: NextReleasedMtgoSet,
+        today: NaiveDate,
+    ) {
+        if let Some(released_at) = self.next_released_mtgo_set.released_at() {
+            if released_at < today {
+                self.last_released_at = Some(released_at);
+            }
+        }
         self.next_released_mtgo_set = next_set;
     }
+
+    /// Take the bulk data state from `other`
+    pub(super) fn merge_bulk_data(&mut self, other: &Self) {
+        self.bulk_data_updated_at = other.bulk_data_updated_at;
+        self.bulk_dataset = other.bulk_dataset;
+    }
+
+    /// Take the next released set from `other`
+    pub(super) fn merge_next_released_set(&mut self, other: &Self) {
+        self.last_released_at = other.last_released_at;
+        self.next_released_mtgo_set = other.next_released_mtgo_set.clone();
+    }
 }
 
 #[cfg(test)]
@@ -69,6 +118,8 @@ mod tests {
         let updated_at_datetime: DateTime<Utc> = "1970-01-01T00:00:00Z".parse()?;
         let scryfall_md = ScryfallMetaData {
             bulk_data_updated_at: Some(updated_at_datetime),
+            bulk_dataset: None,
+            last_released_at: None,
             next_released_mtgo_set,
         };
 
@@ -1,86 +1,781 @@
+pub mod card_data;
+pub mod config;
 pub mod fetch_log;
+pub mod progress;
+pub mod provenance;
+pub mod refresh;
+pub mod releases;
+pub mod store;
 
-use std::{fs, io, path::PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+    thread,
+    time::Instant,
+};
 
-use get_scryfall::{MtgoSet, ScryfallBulkData, ScryfallBulkDataInfo, ScryfallMtgoSets};
+use chrono::{DateTime, Utc};
+pub use get_http::FetchError;
+use get_http::{Conditional, Validators};
+use get_scryfall::{ScryfallBulkData, ScryfallBulkDataInfo, ScryfallMtgoSets};
+use parse_scryfall::write_sorted_json_lines;
+use serde::Serialize;
 
-use crate::fetch_log::CardInfoMetaData;
+use crate::{
+    config::Config,
+    fetch_log::scryfall_md::next_released_mtgo_set::NextReleasedMtgoSet,
+    fetch_log::CardInfoMetaData,
+    provenance::{Provenance, ProvenanceManifest},
+    refresh::RefreshPolicy,
+    store::{store_validated, store_validated_replacing, DataFile},
+};
+pub use progress::{FetchPhase, FetchProgress, ProgressTracker};
 
-const FETCH_LOG_FILENAME: &str = "fetch_log.toml";
+/// Name of the file the Goatbots card definitions are saved to
+pub const GOATBOTS_CARD_DEFINITIONS_FILENAME: &str = "card-definitions.json";
+/// Name of the file the Goatbots price history is saved to
+pub const GOATBOTS_PRICE_HISTORY_FILENAME: &str = "price-history.json";
+
+/// Options that control how data is fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchOptions {
+    /// Download the data even if the fetch log says it's up to date
+    pub force: bool,
+    /// Maximum number of downloads to run concurrently, 0 is treated as 1
+    pub jobs: usize,
+}
+
+impl FetchOptions {
+    /// Enough to run all the independent downloads of [fetch_all] at once
+    pub const DEFAULT_JOBS: usize = FETCH_CHAINS.len();
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            force: false,
+            jobs: Self::DEFAULT_JOBS,
+        }
+    }
+}
+
+/// Signature of the individual fetchers
+pub type Fetcher = fn(
+    &Path,
+    &mut CardInfoMetaData,
+    &Config,
+    FetchOptions,
+    &dyn Fn(FetchProgress),
+) -> Result<FetchStatus, FetchError>;
+
+/// The fetchers of [fetch_all], grouped in chains that run concurrently.
+///
+/// The fetchers of a chain run in order, as the card definitions are considered up to date based on the next
+/// released set recorded by the sets fetcher. Each fetcher is listed with the [FetchPhase] of the state it records in
+/// the fetch log.
+const FETCH_CHAINS: [&[(FetchPhase, Fetcher)]; 3] = [
+    &[
+        (FetchPhase::ScryfallSets, fetch_scryfall_sets as Fetcher),
+        (
+            FetchPhase::GoatbotsCardDefinitions,
+            fetch_goatbots_card_definitions as Fetcher,
+        ),
+    ],
+    &[(
+        FetchPhase::ScryfallBulkCards,
+        fetch_scryfall_bulk_cards as Fetcher,
+    )],
+    &[(
+        FetchPhase::GoatbotsPriceHistory,
+        fetch_goatbots_price_history as Fetcher,
+    )],
+];
+
+/// The outcome of fetching one kind of data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FetchStatus {
+    /// The kind of data, e.g. `scryfall-sets`
+    pub data: &'static str,
+    /// True if the data was downloaded, false if it was up to date
+    pub downloaded: bool,
+    /// Where the data is stored
+    pub path: PathBuf,
+    /// Where and how the data was downloaded, [None] if it was up to date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+impl FetchStatus {
+    /// [Self::data] of the Scryfall sets
+    pub const SCRYFALL_SETS: &'static str = "scryfall-sets";
+    /// [Self::data] of the Scryfall bulk data cards
+    pub const SCRYFALL_BULK_CARDS: &'static str = "scryfall-bulk-cards";
+    /// [Self::data] of the Goatbots card definitions
+    pub const GOATBOTS_CARD_DEFINITIONS: &'static str = "goatbots-card-definitions";
+    /// [Self::data] of the Goatbots price history
+    pub const GOATBOTS_PRICE_HISTORY: &'static str = "goatbots-price-history";
+
+    fn downloaded(data: &'static str, path: PathBuf, provenance: Provenance) -> Self {
+        Self {
+            data,
+            downloaded: true,
+            path,
+            provenance: Some(provenance),
+        }
+    }
+
+    fn up_to_date(data: &'static str, path: PathBuf) -> Self {
+        Self {
+            data,
+            downloaded: false,
+            path,
+            provenance: None,
+        }
+    }
+}
 
 /// Fetches all data if any needs updating and stores it in `save_to_dir`
 ///
-pub fn fetch_all(save_to_dir: PathBuf) -> Result<(), io::Error> {
-    let fetch_log_dst = save_to_dir.join(FETCH_LOG_FILENAME);
-    let mut fetch_log: CardInfoMetaData = match fetch_log_dst.exists() {
-        true => CardInfoMetaData::from_toml_file(&fetch_log_dst)?,
-        false => CardInfoMetaData::new(),
-    };
+/// # Errors
+///
+/// Returns a [FetchError] if any of the downloads fail or the data cannot be stored
+pub fn fetch_all(save_to_dir: PathBuf) -> Result<(), FetchError> {
+    fetch_all_with_options(&save_to_dir, FetchOptions::default()).map(|_| ())
+}
+
+/// Fetches all data that needs updating (or all of it if [FetchOptions::force] is set) and stores it in `save_to_dir`
+///
+/// # Returns
+///
+/// The [FetchStatus] of each kind of data
+///
+/// # Errors
+///
+/// Returns a [FetchError] if any of the downloads fail or the data cannot be stored
+pub fn fetch_all_with_options(
+    save_to_dir: &Path,
+    opts: FetchOptions,
+) -> Result<Vec<FetchStatus>, FetchError> {
+    fetch_all_with_progress(save_to_dir, opts, &|_| {})
+}
+
+/// Like [fetch_all_with_options] but reports the [FetchProgress] to `progress` as the data is fetched
+///
+/// The independent downloads run concurrently on at most [FetchOptions::jobs] threads, each on its own copy of the
+/// fetch log. The copies are merged and the fetch log is saved once all downloads are done, including the state of
+/// the downloads that succeeded if another one failed.
+///
+/// # Errors
+///
+/// Returns the first [FetchError] (in the order of the [FetchStatus]es) if any of the downloads fail or the data
+/// cannot be stored
+pub fn fetch_all_with_progress(
+    save_to_dir: &Path,
+    opts: FetchOptions,
+    progress: &(dyn Fn(FetchProgress) + Sync),
+) -> Result<Vec<FetchStatus>, FetchError> {
+    let config = load_config(save_to_dir)?;
+    log::debug!("config: {config:?}");
+    let mut fetch_log = load_fetch_log(save_to_dir)?;
     log::debug!("fetch log contents: {fetch_log:?}");
 
-    if fetch_log.is_next_set_out() {
-        log::info!("Fetching sets from Scryfall");
-        let sets = ScryfallMtgoSets::get().unwrap();
-        let next_set: &MtgoSet = sets.next_released_mtgo_set().unwrap().unwrap();
-        log::info!("Next released set is {next_set:?}");
-        fetch_log.replace_next_released_set(next_set.into());
-        let dst = save_to_dir.join(ScryfallMtgoSets::FILENAME);
-        log::info!("Writing scryfall sets to {dst:?}");
-        let sets_vec = sets.take_sets();
-        fs::write(dst, serde_json::to_string(&sets_vec).unwrap())?;
-    } else {
+    let next_chain = AtomicUsize::new(0);
+    let outcomes: Mutex<Vec<Option<ChainOutcome>>> =
+        Mutex::new((0..FETCH_CHAINS.len()).map(|_| None).collect());
+    let workers = opts.jobs.clamp(1, FETCH_CHAINS.len());
+    log::debug!("Fetching with {workers} worker(s)");
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let idx = next_chain.fetch_add(1, Ordering::Relaxed);
+                let Some(chain) = FETCH_CHAINS.get(idx) else {
+                    break;
+                };
+                let outcome = run_chain(
+                    chain,
+                    save_to_dir,
+                    fetch_log.clone(),
+                    &config,
+                    opts,
+                    progress,
+                );
+                outcomes.lock().unwrap_or_else(PoisonError::into_inner)[idx] = Some(outcome);
+            });
+        }
+    });
+
+    let mut statuses = Vec::new();
+    let mut first_err = None;
+    for outcome in outcomes
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+        .into_iter()
+        .flatten()
+    {
+        for (phase, status) in outcome.statuses {
+            fetch_log.merge_phase(phase, &outcome.fetch_log);
+            statuses.push((phase, status));
+        }
+        if let Some(e) = outcome.error {
+            log::error!("{e}");
+            first_err.get_or_insert(e);
+        }
+    }
+
+    // Save the log to disk
+    save_fetch_log(save_to_dir, &fetch_log)?;
+    record_provenance(save_to_dir, statuses.iter().map(|(_, status)| status))?;
+    // The prices of new cards are recorded once all downloads are done, as the card definitions and the price history
+    // are fetched concurrently
+    match releases::record_first_prices(save_to_dir) {
+        Ok(0) => (),
+        Ok(observed) => log::info!("Recorded the first price of {observed} new card(s)"),
+        Err(e) => log::warn!("Failed to record the first prices of new cards: {e}"),
+    }
+    if let Some(e) = first_err {
+        return Err(e);
+    }
+    statuses.sort_by_key(|(phase, _)| phase.index());
+    Ok(statuses.into_iter().map(|(_, status)| status).collect())
+}
+
+/// The result of running the fetchers of a chain
+struct ChainOutcome {
+    /// The chain's copy of the fetch log
+    fetch_log: CardInfoMetaData,
+    /// The status of each fetcher that succeeded
+    statuses: Vec<(FetchPhase, FetchStatus)>,
+    /// The error of the fetcher that failed, the rest of the chain is skipped
+    error: Option<FetchError>,
+}
+
+fn run_chain(
+    chain: &[(FetchPhase, Fetcher)],
+    save_to_dir: &Path,
+    mut fetch_log: CardInfoMetaData,
+    config: &Config,
+    opts: FetchOptions,
+    progress: &dyn Fn(FetchProgress),
+) -> ChainOutcome {
+    let mut statuses = Vec::with_capacity(chain.len());
+    for (phase, fetcher) in chain {
+        match fetcher(save_to_dir, &mut fetch_log, config, opts, progress) {
+            Ok(status) => {
+                progress(FetchProgress::Finished(*phase));
+                statuses.push((*phase, status));
+            }
+            Err(e) => {
+                return ChainOutcome {
+                    fetch_log,
+                    statuses,
+                    error: Some(e),
+                }
+            }
+        }
+    }
+    ChainOutcome {
+        fetch_log,
+        statuses,
+        error: None,
+    }
+}
+
+/// Load the [Config] from `save_to_dir`, see [Config::load], and apply its [http](Config::http) settings to the
+/// shared HTTP client
+///
+/// # Errors
+///
+/// Returns a [FetchError] if the config cannot be loaded or the HTTP client cannot be built with its settings
+pub fn load_config(save_to_dir: &Path) -> Result<Config, FetchError> {
+    let config =
+        Config::load(save_to_dir).map_err(FetchError::local(save_to_dir.join(Config::FILENAME)))?;
+    get_http::client::configure(config.http.clone())?;
+    Ok(config)
+}
+
+/// Load the fetch log from `save_to_dir`, or create a new one if it doesn't exist or is unreadable
+pub fn load_fetch_log(save_to_dir: &Path) -> Result<CardInfoMetaData, FetchError> {
+    let fetch_log_dst = save_to_dir.join(CardInfoMetaData::FILENAME);
+    CardInfoMetaData::from_toml_file_or_reset(&fetch_log_dst)
+        .map_err(FetchError::local(fetch_log_dst))
+}
+
+/// Save the fetch log to `save_to_dir`
+pub fn save_fetch_log(save_to_dir: &Path, fetch_log: &CardInfoMetaData) -> Result<(), FetchError> {
+    let fetch_log_dst = save_to_dir.join(CardInfoMetaData::FILENAME);
+    fetch_log
+        .to_toml_on_disk(&fetch_log_dst)
+        .map_err(FetchError::local(fetch_log_dst))
+}
+
+/// Add the [Provenance] of the downloaded data in `statuses` to the [ProvenanceManifest] in `save_to_dir`
+///
+/// # Errors
+///
+/// Returns a [FetchError::Local] if the manifest cannot be loaded or saved
+pub fn record_provenance<'a>(
+    save_to_dir: &Path,
+    statuses: impl IntoIterator<Item = &'a FetchStatus>,
+) -> Result<(), FetchError> {
+    let mut downloads = statuses
+        .into_iter()
+        .filter_map(|s| Some((s.path.file_name()?, s.provenance.as_ref()?)))
+        .peekable();
+    if downloads.peek().is_none() {
+        return Ok(());
+    }
+    let manifest_dst = save_to_dir.join(provenance::PROVENANCE_FILENAME);
+    let mut manifest =
+        ProvenanceManifest::load(save_to_dir).map_err(FetchError::local(&manifest_dst))?;
+    for (file_name, provenance) in downloads {
+        manifest.insert(file_name.to_string_lossy().into_owned(), provenance.clone());
+    }
+    manifest
+        .save(save_to_dir)
+        .map_err(FetchError::local(manifest_dst))
+}
+
+/// Serialize `value` as JSON and store it at `dst` if it's valid, see [store_validated]
+///
+/// # Returns
+///
+/// The stored JSON and its number of records
+fn write_json<T: Serialize + ?Sized>(
+    dst: &Path,
+    value: &T,
+    kind: DataFile,
+) -> Result<(Vec<u8>, usize), FetchError> {
+    let json = serde_json::to_vec(value).map_err(|e| FetchError::local(dst)(e.into()))?;
+    let records = store_validated(dst, &json, kind)?;
+    Ok((json, records))
+}
+
+/// Fetch the MTGO sets from Scryfall if the next set has been released, and record the next set to be released
+pub fn fetch_scryfall_sets(
+    save_to_dir: &Path,
+    fetch_log: &mut CardInfoMetaData,
+    config: &Config,
+    opts: FetchOptions,
+    progress: &dyn Fn(FetchProgress),
+) -> Result<FetchStatus, FetchError> {
+    progress(FetchProgress::Phase(FetchPhase::ScryfallSets));
+    let dst = save_to_dir.join(ScryfallMtgoSets::FILENAME);
+    if !opts.force && !fetch_log.is_next_set_out() {
         log::info!("Scryfall sets data is up to date - skipping download");
+        return Ok(FetchStatus::up_to_date(FetchStatus::SCRYFALL_SETS, dst));
     }
 
-    let scryfall_bulk_info: ScryfallBulkDataInfo = ScryfallBulkDataInfo::get().unwrap();
+    log::info!("Fetching sets from Scryfall");
+    let url = config.endpoints.scryfall_sets()?;
+    let started = Instant::now();
+    let sets = ScryfallMtgoSets::get_from(&url)?;
+    let http_status = sets.http_status();
+    match sets
+        .next_released_mtgo_set()
+        .map_err(|e| FetchError::Decode {
+            url: url.to_string(),
+            source: format!("no MTGO set is released after today ({e})").into(),
+        })? {
+        Some(next_set) => {
+            log::info!("Next released set is {next_set:?}");
+            fetch_log.replace_next_released_set(next_set.into());
+        }
+        None => {
+            log::warn!("Scryfall doesn't list any upcoming MTGO sets");
+            // Forget the released set, it's no longer the next one
+            fetch_log.replace_next_released_set(NextReleasedMtgoSet::default());
+        }
+    }
+    log::info!("Writing scryfall sets to {dst:?}");
+    let sets_vec = sets.take_sets();
+    let (json, records) = write_json(&dst, &sets_vec, DataFile::ScryfallSets)?;
+    let provenance = Provenance::new(&url, http_status, &json, records, started.elapsed());
+    Ok(FetchStatus::downloaded(
+        FetchStatus::SCRYFALL_SETS,
+        dst,
+        provenance,
+    ))
+}
+
+/// Fetch the Scryfall bulk data info
+pub fn fetch_scryfall_bulk_info(config: &Config) -> Result<ScryfallBulkDataInfo, FetchError> {
+    ScryfallBulkDataInfo::get_from(
+        &config.endpoints.scryfall_bulk_data_info()?,
+        config.scryfall_bulk_dataset,
+    )
+}
+
+/// Fetch the Scryfall bulk data (default cards) if it has been updated since the last download, or as configured by
+/// [RefreshPolicies::scryfall_bulk_data](refresh::RefreshPolicies::scryfall_bulk_data)
+pub fn fetch_scryfall_bulk_cards(
+    save_to_dir: &Path,
+    fetch_log: &mut CardInfoMetaData,
+    config: &Config,
+    opts: FetchOptions,
+    progress: &dyn Fn(FetchProgress),
+) -> Result<FetchStatus, FetchError> {
+    progress(FetchProgress::Phase(FetchPhase::ScryfallBulkInfo));
+    let dst = save_to_dir.join(ScryfallBulkData::FILENAME);
+    // Decided before getting the bulk data info, so the policy can prevent any request to Scryfall
+    let policy_fresh = match opts.force {
+        true => Some(false),
+        false => config
+            .refresh
+            .scryfall_bulk_data
+            .is_fresh(fetch_log.scryfall_bulk_data_updated_at(), Utc::now()),
+    };
+    if policy_fresh == Some(true) {
+        log::info!(
+            "Scryfall bulk data is up to date according to the refresh policy - skipping download"
+        );
+        progress(FetchProgress::Finished(FetchPhase::ScryfallBulkInfo));
+        progress(FetchProgress::Phase(FetchPhase::ScryfallBulkCards));
+        return Ok(FetchStatus::up_to_date(
+            FetchStatus::SCRYFALL_BULK_CARDS,
+            dst,
+        ));
+    }
+    let scryfall_bulk_info = fetch_scryfall_bulk_info(config)?;
     log::info!("scryfall bulk info: {scryfall_bulk_info:?}");
-    if fetch_log.is_scryfall_bulk_updated(scryfall_bulk_info.updated_at()) {
-        log::info!("Scryfall bulk data is up to date - skipping download")
-    } else {
-        log::info!("Fetching scryfall bulk data");
-        let scryfall_bulk_data = ScryfallBulkData::get(scryfall_bulk_info.updated_at()).unwrap();
-        fetch_log.refresh_bulk_data_updated_at_timestamp();
-        let cards = scryfall_bulk_data.take_cards();
-        let dst = save_to_dir.join(ScryfallBulkData::FILENAME);
-        log::info!("Writing scryfall bulk data (default cards) to {dst:?}");
-        fs::write(dst, serde_json::to_string(&cards).unwrap())?;
+    progress(FetchProgress::Finished(FetchPhase::ScryfallBulkInfo));
+    progress(FetchProgress::Phase(FetchPhase::ScryfallBulkCards));
+    let dataset = scryfall_bulk_info.dataset();
+    let dataset_changed = fetch_log.scryfall_bulk_dataset() != dataset;
+    if policy_fresh.is_none()
+        && !dataset_changed
+        && fetch_log.is_scryfall_bulk_updated(scryfall_bulk_info.updated_at())
+    {
+        log::info!("Scryfall bulk data is up to date - skipping download");
+        return Ok(FetchStatus::up_to_date(
+            FetchStatus::SCRYFALL_BULK_CARDS,
+            dst,
+        ));
     }
 
-    if fetch_log.is_card_definitions_updated() {
-        log::info!("Card definitions are up to date - skipping download");
+    log::info!("Fetching scryfall bulk data ({dataset})");
+    let url = config.endpoints.scryfall_bulk_data(&scryfall_bulk_info)?;
+    let started = Instant::now();
+    let scryfall_bulk_data = ScryfallBulkData::download_from(
+        &url,
+        scryfall_bulk_info.updated_at(),
+        save_to_dir,
+        &on_bytes(FetchPhase::ScryfallBulkCards, progress),
+    )?;
+    let http_status = scryfall_bulk_data.http_status();
+    let mut cards = scryfall_bulk_data.take_cards();
+    log::info!("Writing scryfall bulk data ({dataset}) to {dst:?}");
+    // Stored sorted by MTGO ID so the parser can merge join it with the collection without sorting it first
+    let mut json_lines = Vec::new();
+    write_sorted_json_lines(&mut cards, &mut json_lines).map_err(FetchError::local(&dst))?;
+    let records = if dataset_changed {
+        // The number of cards of another dataset says nothing about the plausibility of this one
+        store_validated_replacing(&dst, &json_lines, DataFile::ScryfallCards)?
     } else {
-        log::info!("Fetching Card definitions");
-        let gb_card_defs = get_goatbots::get_goatbots_card_definitions();
-        let dst = save_to_dir.join("card-definitions.json");
-        log::info!("Writing Card definitions to {dst:?}");
-        fs::write(dst, gb_card_defs)?;
-        log::info!("Refreshing timestamp for fetching Goatbots card definitions");
-        fetch_log.refresh_card_definitions_updated_at_timestamp();
+        store_validated(&dst, &json_lines, DataFile::ScryfallCards)?
+    };
+    fetch_log.set_scryfall_bulk_dataset(dataset);
+    fetch_log.refresh_bulk_data_updated_at_timestamp();
+    let provenance = Provenance::new(&url, http_status, &json_lines, records, started.elapsed());
+    Ok(FetchStatus::downloaded(
+        FetchStatus::SCRYFALL_BULK_CARDS,
+        dst,
+        provenance,
+    ))
+}
+
+/// Fetch the Goatbots card definitions if they haven't been updated today, or as configured by
+/// [RefreshPolicies::goatbots_card_definitions](refresh::RefreshPolicies::goatbots_card_definitions)
+pub fn fetch_goatbots_card_definitions(
+    save_to_dir: &Path,
+    fetch_log: &mut CardInfoMetaData,
+    config: &Config,
+    opts: FetchOptions,
+    progress: &dyn Fn(FetchProgress),
+) -> Result<FetchStatus, FetchError> {
+    progress(FetchProgress::Phase(FetchPhase::GoatbotsCardDefinitions));
+    let dst = save_to_dir.join(GOATBOTS_CARD_DEFINITIONS_FILENAME);
+    if is_up_to_date(
+        opts,
+        config.refresh.goatbots_card_definitions,
+        fetch_log.goatbots_card_definitions_updated_at(),
+        || fetch_log.is_card_definitions_updated(),
+    ) {
+        log::info!("Card definitions are up to date - skipping download");
+        return Ok(FetchStatus::up_to_date(
+            FetchStatus::GOATBOTS_CARD_DEFINITIONS,
+            dst,
+        ));
+    }
+
+    log::info!("Fetching Card definitions");
+    let validators = cached_validators(&dst, fetch_log.goatbots_card_definitions_validators());
+    let url = config.endpoints.goatbots_card_definitions()?;
+    let started = Instant::now();
+    let gb_card_defs = get_goatbots::get_goatbots_card_definitions_if_modified(
+        &url,
+        &validators,
+        &on_bytes(FetchPhase::GoatbotsCardDefinitions, progress),
+    )?;
+    log::info!("Refreshing timestamp for fetching Goatbots card definitions");
+    fetch_log.refresh_card_definitions_updated_at_timestamp();
+    match gb_card_defs {
+        Conditional::NotModified => {
+            log::info!("Card definitions haven't changed since the last download");
+            Ok(FetchStatus::up_to_date(
+                FetchStatus::GOATBOTS_CARD_DEFINITIONS,
+                dst,
+            ))
+        }
+        Conditional::Modified {
+            body,
+            validators,
+            status: http_status,
+        } => {
+            releases::keep_previous_card_definitions(save_to_dir).map_err(FetchError::local(
+                save_to_dir.join(releases::PREVIOUS_CARD_DEFINITIONS_FILENAME),
+            ))?;
+            log::info!("Writing Card definitions to {dst:?}");
+            let records =
+                store_validated(&dst, body.as_bytes(), DataFile::GoatbotsCardDefinitions)?;
+            let provenance = Provenance::new(
+                &url,
+                http_status,
+                body.as_bytes(),
+                records,
+                started.elapsed(),
+            );
+            fetch_log.set_goatbots_card_definitions_validators(validators);
+            match releases::detect_new_cards(save_to_dir, Utc::now()) {
+                Ok(0) => (),
+                Ok(recorded) => {
+                    log::info!("Detected {recorded} new card(s) in the card definitions")
+                }
+                Err(e) => log::warn!("Failed to detect new cards in the card definitions: {e}"),
+            }
+            Ok(FetchStatus::downloaded(
+                FetchStatus::GOATBOTS_CARD_DEFINITIONS,
+                dst,
+                provenance,
+            ))
+        }
     }
+}
 
-    // Get price history
-    if fetch_log.is_goatbots_prices_updated() {
+/// Fetch the Goatbots price history if it hasn't been updated since the last price update, or as configured by
+/// [RefreshPolicies::goatbots_prices](refresh::RefreshPolicies::goatbots_prices)
+pub fn fetch_goatbots_price_history(
+    save_to_dir: &Path,
+    fetch_log: &mut CardInfoMetaData,
+    config: &Config,
+    opts: FetchOptions,
+    progress: &dyn Fn(FetchProgress),
+) -> Result<FetchStatus, FetchError> {
+    progress(FetchProgress::Phase(FetchPhase::GoatbotsPriceHistory));
+    let dst = save_to_dir.join(GOATBOTS_PRICE_HISTORY_FILENAME);
+    if is_up_to_date(
+        opts,
+        config.refresh.goatbots_prices,
+        fetch_log.goatbots_prices_updated_at(),
+        || fetch_log.is_goatbots_prices_updated(),
+    ) {
         log::info!("Prices are up to date - skipping download");
-    } else {
-        log::info!("Fetching Goatbots price history");
-        let gb_price_hist = get_goatbots::get_goatbots_price_history();
-        let dst = save_to_dir.join("price-history.json");
-        log::info!("Writing Goatbots price history {dst:?}");
-        fs::write(dst, gb_price_hist)?;
-        log::info!("Refreshing timestamp for fetching Goatbots price history");
-        fetch_log.refresh_prices_updated_at_timestamp();
+        return Ok(FetchStatus::up_to_date(
+            FetchStatus::GOATBOTS_PRICE_HISTORY,
+            dst,
+        ));
     }
 
-    // Save the log to disk
-    fetch_log.to_toml_on_disk(&save_to_dir.join(CardInfoMetaData::FILENAME))
+    log::info!("Fetching Goatbots price history");
+    let validators = cached_validators(&dst, fetch_log.goatbots_prices_validators());
+    let url = config.endpoints.goatbots_price_history()?;
+    let started = Instant::now();
+    let gb_price_hist = get_goatbots::get_goatbots_price_history_if_modified(
+        &url,
+        &validators,
+        &on_bytes(FetchPhase::GoatbotsPriceHistory, progress),
+    )?;
+    log::info!("Refreshing timestamp for fetching Goatbots price history");
+    fetch_log.refresh_prices_updated_at_timestamp();
+    match gb_price_hist {
+        Conditional::NotModified => {
+            log::info!("Goatbots price history hasn't changed since the last download");
+            Ok(FetchStatus::up_to_date(
+                FetchStatus::GOATBOTS_PRICE_HISTORY,
+                dst,
+            ))
+        }
+        Conditional::Modified {
+            body,
+            validators,
+            status: http_status,
+        } => {
+            log::info!("Writing Goatbots price history {dst:?}");
+            let records = store_validated(&dst, body.as_bytes(), DataFile::GoatbotsPriceHistory)?;
+            fetch_log.set_goatbots_prices_validators(validators);
+            let provenance = Provenance::new(
+                &url,
+                http_status,
+                body.as_bytes(),
+                records,
+                started.elapsed(),
+            );
+            Ok(FetchStatus::downloaded(
+                FetchStatus::GOATBOTS_PRICE_HISTORY,
+                dst,
+                provenance,
+            ))
+        }
+    }
+}
+
+/// Evaluate the freshness checks of the fetch log and the refresh policies at `now`, without making any requests
+///
+/// Used to decide whether it's worth running [fetch_all_with_progress], which makes the final decision per source.
+/// The built-in rule of the Scryfall bulk data needs the Scryfall API, so with [RefreshPolicy::Auto] it's only stale
+/// if it was never downloaded. It's still checked whenever another source is refreshed.
+///
+/// # Returns
+///
+/// The phases of the sources whose data is stale, in the order of [FetchPhase::ALL]
+pub fn stale_phases(
+    fetch_log: &CardInfoMetaData,
+    config: &Config,
+    now: DateTime<Utc>,
+) -> Vec<FetchPhase> {
+    let refresh = &config.refresh;
+    let mut stale = Vec::new();
+    // Without a known next set a fetch cannot make the sets or the card definitions up to date by the built-in rules,
+    // e.g. while Scryfall doesn't list an upcoming MTGO set, so they're only stale once a known set is released
+    let next_set_known = fetch_log.next_release_countdown(now.date_naive()).is_some();
+    if next_set_known && fetch_log.is_next_set_out() {
+        stale.push(FetchPhase::ScryfallSets);
+    }
+    let bulk_data_updated_at = fetch_log.scryfall_bulk_data_updated_at();
+    let bulk_data_fresh = refresh
+        .scryfall_bulk_data
+        .is_fresh(bulk_data_updated_at, now)
+        .unwrap_or(bulk_data_updated_at.is_some());
+    if !bulk_data_fresh {
+        stale.push(FetchPhase::ScryfallBulkCards);
+    }
+    if !refresh
+        .goatbots_card_definitions
+        .is_fresh(fetch_log.goatbots_card_definitions_updated_at(), now)
+        .unwrap_or_else(|| match fetch_log.goatbots_card_definitions_updated_at() {
+            Some(_) => !next_set_known || fetch_log.is_card_definitions_updated(),
+            None => false,
+        })
+    {
+        stale.push(FetchPhase::GoatbotsCardDefinitions);
+    }
+    if !refresh
+        .goatbots_prices
+        .is_fresh(fetch_log.goatbots_prices_updated_at(), now)
+        .unwrap_or_else(|| fetch_log.is_goatbots_prices_updated())
+    {
+        stale.push(FetchPhase::GoatbotsPriceHistory);
+    }
+    stale
+}
+
+/// Check if data downloaded at `updated_at` is up to date according to its [RefreshPolicy], `auto` is the built-in
+/// rule of the source used by [RefreshPolicy::Auto]. Data is never up to date if the fetch is forced.
+fn is_up_to_date(
+    opts: FetchOptions,
+    policy: RefreshPolicy,
+    updated_at: Option<DateTime<Utc>>,
+    auto: impl FnOnce() -> bool,
+) -> bool {
+    !opts.force && policy.is_fresh(updated_at, Utc::now()).unwrap_or_else(auto)
+}
+
+/// Adapt the `progress` observer to the byte progress callbacks of the getters
+fn on_bytes<'a>(
+    phase: FetchPhase,
+    progress: &'a dyn Fn(FetchProgress),
+) -> impl Fn(u64, Option<u64>) + 'a {
+    move |downloaded, total| {
+        progress(FetchProgress::Bytes {
+            phase,
+            downloaded,
+            total,
+        })
+    }
+}
+
+/// The validators to send with a conditional request for the data stored at `dst`
+///
+/// If the data is missing, it has to be downloaded regardless of whether it changed on the server.
+fn cached_validators(dst: &Path, validators: &Validators) -> Validators {
+    if dst.exists() {
+        validators.clone()
+    } else {
+        Validators::default()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use pretty_assertions::assert_eq;
     use testresult::TestResult;
 
     use super::*;
 
+    #[test]
+    fn test_stale_phases() -> TestResult {
+        let mut config = Config::default();
+        let mut fetch_log = CardInfoMetaData::new();
+        let now = Utc::now();
+        // Nothing was ever downloaded, the sets are fetched along with the others
+        assert_eq!(
+            stale_phases(&fetch_log, &config, now),
+            [
+                FetchPhase::ScryfallBulkCards,
+                FetchPhase::GoatbotsCardDefinitions,
+                FetchPhase::GoatbotsPriceHistory,
+            ]
+        );
+
+        // Without a known next set, e.g. as Scryfall doesn't list one, a fetch cannot clear the sets and card
+        // definitions by the built-in rules so they aren't stale
+        fetch_log.refresh_bulk_data_updated_at_timestamp();
+        fetch_log.refresh_prices_updated_at_timestamp();
+        fetch_log.refresh_card_definitions_updated_at_timestamp();
+        assert_eq!(stale_phases(&fetch_log, &config, Utc::now()), []);
+
+        // A known next set is stale once it's released
+        let today = Utc::now().date_naive();
+        fetch_log.replace_next_released_set(NextReleasedMtgoSet::new(
+            Some("Bloomburrow".to_owned()),
+            Some(today + chrono::Days::new(7)),
+            Some("blb".to_owned()),
+        ));
+        assert_eq!(stale_phases(&fetch_log, &config, Utc::now()), []);
+        fetch_log.replace_next_released_set(NextReleasedMtgoSet::new(
+            Some("Bloomburrow".to_owned()),
+            Some(today - chrono::Days::new(1)),
+            Some("blb".to_owned()),
+        ));
+        config.refresh.goatbots_card_definitions = RefreshPolicy::Manual;
+        assert_eq!(
+            stale_phases(&fetch_log, &config, Utc::now()),
+            [FetchPhase::ScryfallSets]
+        );
+
+        // The bulk data only ages out with a configured policy
+        let in_two_days = Utc::now() + chrono::Duration::days(2);
+        assert!(!stale_phases(&fetch_log, &config, in_two_days)
+            .contains(&FetchPhase::ScryfallBulkCards));
+        config.refresh.scryfall_bulk_data = "every 24h".parse()?;
+        assert!(
+            stale_phases(&fetch_log, &config, in_two_days).contains(&FetchPhase::ScryfallBulkCards)
+        );
+        Ok(())
+    }
+
     #[ignore = "Will download A LOT of data and save it to disk"]
     #[test]
     fn test_fetch_all() -> TestResult {
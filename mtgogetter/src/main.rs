@@ -0,0 +1,196 @@
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use clap::{ArgAction, Parser, Subcommand};
+use get_scryfall::ScryfallBulkDataInfo;
+use mtgogetter::{FetchError, FetchOptions, FetchProgress, FetchStatus, Fetcher};
+use serde_json::json;
+
+/// Download card data from Scryfall and Goatbots for MTGO Collection Manager
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Directory to store the data and the fetch log in
+    #[arg(short, long, global = true, default_value = ".")]
+    out_dir: PathBuf,
+    /// Download the data even if the fetch log says it's up to date
+    #[arg(long, global = true)]
+    force: bool,
+    /// Maximum number of concurrent downloads when fetching all data
+    #[arg(short, long, global = true, default_value_t = FetchOptions::DEFAULT_JOBS)]
+    jobs: usize,
+    /// Print the status as JSON to stdout
+    #[arg(long, global = true)]
+    json: bool,
+    /// Increase log verbosity (-v: info, -vv: debug, -vvv: trace)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Fetch all data that needs updating
+    All,
+    /// Fetch the MTGO sets from Scryfall
+    Sets,
+    /// Show the Scryfall bulk data info
+    BulkInfo,
+    /// Fetch the Scryfall bulk data (the configured dataset, default cards by default)
+    BulkCards,
+    /// Fetch the Goatbots card definitions
+    GoatbotsCardDefinitions,
+    /// Fetch the Goatbots price history
+    GoatbotsPriceHistory,
+}
+
+/// The result of a command
+enum Report {
+    Fetched(Vec<FetchStatus>),
+    BulkInfo(ScryfallBulkDataInfo),
+}
+
+impl Report {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Report::Fetched(statuses) => json!(statuses),
+            Report::BulkInfo(info) => json!({
+                "dataset": info.dataset(),
+                "download_url": info.download_url().as_str(),
+                "updated_at": info.updated_at(),
+                "size": info.size(),
+            }),
+        }
+    }
+
+    fn print(&self) {
+        match self {
+            Report::Fetched(statuses) => {
+                for s in statuses {
+                    let state = if s.downloaded {
+                        "downloaded"
+                    } else {
+                        "up to date"
+                    };
+                    println!("{}: {state} ({})", s.data, s.path.display());
+                }
+            }
+            Report::BulkInfo(info) => {
+                println!("dataset: {}", info.dataset());
+                println!("download_url: {}", info.download_url());
+                println!("updated_at: {}", info.updated_at());
+                if let Some(size) = info.size() {
+                    println!("size: {size} bytes");
+                }
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Warnings and errors are always logged to stderr, stdout is reserved for the status output
+    if let Err(e) = stderrlog::new()
+        .verbosity(usize::from(cli.verbose) + 1)
+        .init()
+    {
+        eprintln!("Failed to initialize logging: {e}");
+    }
+
+    match run(&cli) {
+        Ok(report) => {
+            if cli.json {
+                println!("{}", json!({ "status": "ok", "result": report.to_json() }));
+            } else {
+                report.print();
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if cli.json {
+                println!("{}", json!({ "status": "error", "error": e.to_string() }));
+            } else {
+                eprintln!("Error: {e}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<Report, FetchError> {
+    fs::create_dir_all(&cli.out_dir).map_err(FetchError::local(&cli.out_dir))?;
+    let opts = FetchOptions {
+        force: cli.force,
+        jobs: cli.jobs,
+    };
+
+    let fetcher: Fetcher = match cli.command {
+        Command::All => {
+            return Ok(Report::Fetched(mtgogetter::fetch_all_with_progress(
+                &cli.out_dir,
+                opts,
+                &log_progress,
+            )?))
+        }
+        Command::BulkInfo => {
+            let config = mtgogetter::load_config(&cli.out_dir)?;
+            return Ok(Report::BulkInfo(mtgogetter::fetch_scryfall_bulk_info(
+                &config,
+            )?));
+        }
+        Command::Sets => mtgogetter::fetch_scryfall_sets,
+        Command::BulkCards => mtgogetter::fetch_scryfall_bulk_cards,
+        Command::GoatbotsCardDefinitions => mtgogetter::fetch_goatbots_card_definitions,
+        Command::GoatbotsPriceHistory => mtgogetter::fetch_goatbots_price_history,
+    };
+
+    let config = mtgogetter::load_config(&cli.out_dir)?;
+    let mut fetch_log = mtgogetter::load_fetch_log(&cli.out_dir)?;
+    let status = fetcher(&cli.out_dir, &mut fetch_log, &config, opts, &log_progress)?;
+    mtgogetter::save_fetch_log(&cli.out_dir, &fetch_log)?;
+    mtgogetter::record_provenance(&cli.out_dir, [&status])?;
+    Ok(Report::Fetched(vec![status]))
+}
+
+/// Log the phase changes at info level, the byte progress is too noisy for anything but trace
+fn log_progress(progress: FetchProgress) {
+    match progress {
+        FetchProgress::Phase(phase) => log::info!("Fetching {}", phase.description()),
+        FetchProgress::Finished(phase) => log::info!("Done fetching {}", phase.description()),
+        FetchProgress::Bytes {
+            phase,
+            downloaded,
+            total,
+        } => log::trace!("{}: {downloaded}/{total:?} bytes", phase.description()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_global_flags_after_subcommand() {
+        let cli = Cli::parse_from(["mtgogetter", "bulk-cards", "--force", "-o", "data", "-vv"]);
+        assert!(cli.force);
+        assert!(!cli.json);
+        assert_eq!(cli.out_dir, PathBuf::from("data"));
+        assert_eq!(cli.verbose, 2);
+        assert!(matches!(cli.command, Command::BulkCards));
+    }
+
+    #[test]
+    fn test_json_status() {
+        let report = Report::Fetched(vec![FetchStatus {
+            data: "goatbots-price-history",
+            downloaded: false,
+            path: PathBuf::from("price-history.json"),
+            provenance: None,
+        }]);
+        assert_eq!(
+            report.to_json(),
+            json!([{"data": "goatbots-price-history", "downloaded": false, "path": "price-history.json"}])
+        );
+    }
+}
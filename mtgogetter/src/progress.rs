@@ -0,0 +1,147 @@
+use serde::Serialize;
+
+/// The phases of [fetch_all](crate::fetch_all) in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FetchPhase {
+    ScryfallSets,
+    ScryfallBulkInfo,
+    ScryfallBulkCards,
+    GoatbotsCardDefinitions,
+    GoatbotsPriceHistory,
+}
+
+impl FetchPhase {
+    pub const ALL: [Self; 5] = [
+        Self::ScryfallSets,
+        Self::ScryfallBulkInfo,
+        Self::ScryfallBulkCards,
+        Self::GoatbotsCardDefinitions,
+        Self::GoatbotsPriceHistory,
+    ];
+
+    /// The position of the phase in [Self::ALL]
+    pub fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|p| *p == self)
+            .expect("every phase is in ALL")
+    }
+
+    /// Short human readable description, e.g. for a progress bar label
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ScryfallSets => "Scryfall sets",
+            Self::ScryfallBulkInfo => "Scryfall bulk data info",
+            Self::ScryfallBulkCards => "Scryfall bulk data",
+            Self::GoatbotsCardDefinitions => "Goatbots card definitions",
+            Self::GoatbotsPriceHistory => "Goatbots prices",
+        }
+    }
+}
+
+/// Progress reported to the observer passed to [fetch_all_with_progress](crate::fetch_all_with_progress)
+///
+/// Independent phases run concurrently, so the events of different phases can be interleaved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchProgress {
+    /// A phase started, phases that are skipped because the data is up to date are still reported
+    Phase(FetchPhase),
+    /// Bytes of a phase's download
+    Bytes {
+        phase: FetchPhase,
+        downloaded: u64,
+        /// The size of the download if the server reported it
+        total: Option<u64>,
+    },
+    /// A phase finished successfully
+    Finished(FetchPhase),
+}
+
+impl FetchProgress {
+    pub fn phase(&self) -> FetchPhase {
+        match *self {
+            FetchProgress::Phase(phase)
+            | FetchProgress::Bytes { phase, .. }
+            | FetchProgress::Finished(phase) => phase,
+        }
+    }
+}
+
+/// Combines the interleaved [FetchProgress] of all phases into the overall progress of [fetch_all](crate::fetch_all)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgressTracker {
+    /// The fraction of each phase that is done, indexed by [FetchPhase::index]
+    done: [f32; FetchPhase::ALL.len()],
+}
+
+impl ProgressTracker {
+    /// Record the progress and return the overall fraction that is done
+    ///
+    /// Each phase counts equally, and a download of unknown size counts as half done until it finishes.
+    /// The fraction never decreases, e.g. if a download is restarted.
+    pub fn update(&mut self, progress: FetchProgress) -> f32 {
+        let done = &mut self.done[progress.phase().index()];
+        let fraction = match progress {
+            FetchProgress::Phase(_) => 0.,
+            FetchProgress::Bytes {
+                downloaded,
+                total: Some(total),
+                ..
+            } if total > 0 => (downloaded as f32 / total as f32).min(1.),
+            FetchProgress::Bytes { .. } => 0.5,
+            FetchProgress::Finished(_) => 1.,
+        };
+        *done = done.max(fraction);
+        self.fraction()
+    }
+
+    /// The overall fraction that is done
+    pub fn fraction(&self) -> f32 {
+        self.done.iter().sum::<f32>() / self.done.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_progress_tracker() {
+        let mut tracker = ProgressTracker::default();
+        assert_eq!(
+            tracker.update(FetchProgress::Phase(FetchPhase::ScryfallSets)),
+            0.
+        );
+        assert_eq!(
+            tracker.update(FetchProgress::Bytes {
+                phase: FetchPhase::GoatbotsPriceHistory,
+                downloaded: 50,
+                total: Some(100),
+            }),
+            0.1
+        );
+        assert_eq!(
+            tracker.update(FetchProgress::Bytes {
+                phase: FetchPhase::ScryfallBulkCards,
+                downloaded: 50,
+                total: None,
+            }),
+            0.2
+        );
+        // A restarted download doesn't move the progress backwards
+        assert_eq!(
+            tracker.update(FetchProgress::Bytes {
+                phase: FetchPhase::GoatbotsPriceHistory,
+                downloaded: 10,
+                total: Some(100),
+            }),
+            0.2
+        );
+        for phase in FetchPhase::ALL {
+            tracker.update(FetchProgress::Finished(phase));
+        }
+        assert_eq!(tracker.fraction(), 1.);
+    }
+}
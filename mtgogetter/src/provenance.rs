@@ -0,0 +1,174 @@
+use std::{collections::BTreeMap, fs, io, path::Path, time::Duration};
+
+use chrono::{DateTime, Utc};
+use get_http::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::store::write_atomic;
+
+/// Name of the file the provenance of the downloaded data is recorded in
+pub const PROVENANCE_FILENAME: &str = "provenance.json";
+
+/// Where and how a stored data file was downloaded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// The URL the data was downloaded from
+    pub source_url: String,
+    /// The status of the HTTP response, [None] for `file://` URLs
+    pub http_status: Option<u16>,
+    /// When the download finished
+    pub fetched_at: DateTime<Utc>,
+    /// The size of the stored file in bytes
+    pub bytes: u64,
+    /// The SHA-256 of the stored file as lowercase hex
+    pub sha256: String,
+    /// The number of records in the stored file
+    pub records: usize,
+    /// How long it took to download and store the data, in milliseconds
+    pub duration_ms: u64,
+}
+
+impl Provenance {
+    /// Describe the `contents` stored after downloading them from `source_url`
+    ///
+    /// # Arguments
+    ///
+    /// * `source_url` - The URL the data was downloaded from
+    /// * `http_status` - The status of the HTTP response, [None] for `file://` URLs
+    /// * `contents` - The stored contents, which may be converted from the downloaded data e.g. by unzipping it
+    /// * `records` - The number of records in `contents`
+    /// * `duration` - The time it took to download and store the data
+    pub fn new(
+        source_url: &Url,
+        http_status: Option<u16>,
+        contents: &[u8],
+        records: usize,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            source_url: source_url.to_string(),
+            http_status,
+            fetched_at: Utc::now(),
+            bytes: contents.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(contents)),
+            records,
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Check that `contents` are the data described by this provenance, e.g. the stored file wasn't replaced since
+    pub fn matches(&self, contents: &[u8]) -> bool {
+        self.bytes == contents.len() as u64
+            && self.sha256 == format!("{:x}", Sha256::digest(contents))
+    }
+}
+
+/// The [Provenance] of the last download of each data file, also known as `provenance.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProvenanceManifest {
+    /// Keyed by the file name of the stored data, e.g. `price-history.json`
+    files: BTreeMap<String, Provenance>,
+}
+
+impl ProvenanceManifest {
+    /// Load the manifest from `dir`, or an empty one if nothing was downloaded since provenance was recorded
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Self, io::Error> {
+        match fs::read(dir.join(PROVENANCE_FILENAME)) {
+            Ok(json) => Ok(serde_json::from_slice(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Save the manifest to `dir`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, dir: &Path) -> Result<(), io::Error> {
+        let json = serde_json::to_vec_pretty(self)?;
+        write_atomic(&dir.join(PROVENANCE_FILENAME), &json)
+    }
+
+    /// Record the provenance of the data file `file_name`, replacing that of its previous download
+    pub fn insert(&mut self, file_name: String, provenance: Provenance) {
+        self.files.insert(file_name, provenance);
+    }
+
+    /// The provenance of the data file `file_name`
+    pub fn get(&self, file_name: &str) -> Option<&Provenance> {
+        self.files.get(file_name)
+    }
+
+    /// The data files and their provenance, sorted by file name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Provenance)> {
+        self.files.iter().map(|(name, p)| (name.as_str(), p))
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_provenance() -> TestResult {
+        let url = Url::parse("https://www.goatbots.com/download/price-history.zip")?;
+        let provenance = Provenance::new(
+            &url,
+            Some(200),
+            b"{\"347\": 244.99}",
+            1,
+            Duration::from_millis(1500),
+        );
+        assert_eq!(provenance.source_url, url.as_str());
+        assert_eq!(provenance.bytes, 15);
+        assert_eq!(
+            provenance.sha256,
+            "2de8c8a9fed9d956fec93522034dce7505de0dc2ff29f4fbb40116ec1fe68dc6"
+        );
+        assert_eq!(provenance.duration_ms, 1500);
+        assert!(provenance.matches(b"{\"347\": 244.99}"));
+        assert!(!provenance.matches(b"{\"347\": 244.98}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_roundtrip() -> TestResult {
+        let dir = TempDir::new()?;
+        assert!(ProvenanceManifest::load(dir.path())?.is_empty());
+
+        let url = Url::parse("file:///srv/mcm-mirror/scryfall/sets.json")?;
+        let mut manifest = ProvenanceManifest::default();
+        manifest.insert(
+            "scryfall-sets.json".into(),
+            Provenance::new(&url, None, b"[]", 0, Duration::ZERO),
+        );
+        manifest.save(dir.path())?;
+
+        let loaded = ProvenanceManifest::load(dir.path())?;
+        assert_eq!(loaded, manifest);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(
+            loaded.get("scryfall-sets.json").map(|p| p.http_status),
+            Some(None)
+        );
+        Ok(())
+    }
+}
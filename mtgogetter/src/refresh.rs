@@ -0,0 +1,235 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// When the data of a source is considered stale and downloaded again
+///
+/// Written as a string in `mtgogetter.toml`: `"auto"`, `"always"`, `"manual"`, `"every 12h"`, `"every 30m"` or
+/// `"daily 04:00"` (UTC).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum RefreshPolicy {
+    /// The built-in rule of the source
+    #[default]
+    Auto,
+    /// Download on every fetch
+    Always,
+    /// Only download if the data was never downloaded, or the fetch is forced
+    Manual,
+    /// Download once the given number of minutes has passed since the last download
+    Interval { minutes: u32 },
+    /// Download once the given UTC time of day has passed since the last download
+    Daily { cutoff: NaiveTime },
+}
+
+impl RefreshPolicy {
+    /// Check whether data downloaded at `updated_at` is still fresh at `now`
+    ///
+    /// # Returns
+    ///
+    /// [None] for [RefreshPolicy::Auto], the built-in rule of the source decides in that case
+    pub fn is_fresh(&self, updated_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<bool> {
+        let fresh = match (*self, updated_at) {
+            (Self::Auto, _) => return None,
+            (Self::Always, _) | (_, None) => false,
+            (Self::Manual, Some(_)) => true,
+            (Self::Interval { minutes }, Some(updated_at)) => {
+                now - updated_at < Duration::minutes(minutes.into())
+            }
+            (Self::Daily { cutoff }, Some(updated_at)) => {
+                updated_at > last_daily_cutoff(cutoff, now)
+            }
+        };
+        Some(fresh)
+    }
+}
+
+/// The most recent time the UTC time of day was `cutoff`, at or before `now`
+pub fn last_daily_cutoff(cutoff: NaiveTime, now: DateTime<Utc>) -> DateTime<Utc> {
+    let today = now.date_naive().and_time(cutoff).and_utc();
+    if today <= now {
+        today
+    } else {
+        today - Duration::days(1)
+    }
+}
+
+const DAILY_FORMAT: &str = "%H:%M";
+
+impl FromStr for RefreshPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "auto" => return Ok(Self::Auto),
+            "always" => return Ok(Self::Always),
+            "manual" => return Ok(Self::Manual),
+            _ => (),
+        }
+        if let Some(interval) = s.strip_prefix("every ") {
+            let interval = interval.trim();
+            let invalid = || format!("Invalid interval {interval:?}, expected e.g. 12h or 30m");
+            let (amount, minutes_per_unit) = if let Some(hours) = interval.strip_suffix('h') {
+                (hours, 60)
+            } else if let Some(minutes) = interval.strip_suffix('m') {
+                (minutes, 1)
+            } else {
+                return Err(invalid());
+            };
+            let minutes = amount
+                .parse::<u32>()
+                .ok()
+                .and_then(|a| a.checked_mul(minutes_per_unit))
+                .filter(|m| *m > 0)
+                .ok_or_else(invalid)?;
+            return Ok(Self::Interval { minutes });
+        }
+        if let Some(cutoff) = s.strip_prefix("daily ") {
+            let cutoff = NaiveTime::parse_from_str(cutoff.trim(), DAILY_FORMAT).map_err(|e| {
+                format!("Invalid daily cutoff {cutoff:?}, expected e.g. 04:00: {e}")
+            })?;
+            return Ok(Self::Daily { cutoff });
+        }
+        Err(format!(
+            "Invalid refresh policy {s:?}, expected auto, always, manual, every <N>h, every <N>m or daily <HH:MM>"
+        ))
+    }
+}
+
+impl TryFrom<String> for RefreshPolicy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for RefreshPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => f.write_str("auto"),
+            Self::Always => f.write_str("always"),
+            Self::Manual => f.write_str("manual"),
+            Self::Interval { minutes } if minutes % 60 == 0 => write!(f, "every {}h", minutes / 60),
+            Self::Interval { minutes } => write!(f, "every {minutes}m"),
+            Self::Daily { cutoff } => write!(f, "daily {}", cutoff.format(DAILY_FORMAT)),
+        }
+    }
+}
+
+impl From<RefreshPolicy> for String {
+    fn from(value: RefreshPolicy) -> Self {
+        value.to_string()
+    }
+}
+
+/// The [RefreshPolicy] of each data source, the `[refresh]` table of `mtgogetter.toml`
+///
+/// With [RefreshPolicy::Auto] the Goatbots prices are stale after 4 AM UTC, the card definitions once the next set
+/// is released, and the Scryfall bulk data when Scryfall reports a newer version.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshPolicies {
+    pub goatbots_prices: RefreshPolicy,
+    pub goatbots_card_definitions: RefreshPolicy,
+    pub scryfall_bulk_data: RefreshPolicy,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_parse_and_display() -> TestResult {
+        for (s, policy) in [
+            ("auto", RefreshPolicy::Auto),
+            ("always", RefreshPolicy::Always),
+            ("manual", RefreshPolicy::Manual),
+            ("every 12h", RefreshPolicy::Interval { minutes: 720 }),
+            ("every 90m", RefreshPolicy::Interval { minutes: 90 }),
+            (
+                "daily 04:00",
+                RefreshPolicy::Daily {
+                    cutoff: NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+                },
+            ),
+        ] {
+            assert_eq!(s.parse::<RefreshPolicy>()?, policy);
+            assert_eq!(policy.to_string(), s);
+        }
+        for invalid in [
+            "",
+            "never",
+            "every",
+            "every 0h",
+            "every 5d",
+            "daily 25:00",
+            "every h",
+        ] {
+            assert!(invalid.parse::<RefreshPolicy>().is_err(), "{invalid:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_fresh() -> TestResult {
+        let now: DateTime<Utc> = "2024-05-19T03:00:00Z".parse()?;
+        let updated_at = Some("2024-05-18T20:00:00Z".parse()?);
+
+        assert_eq!(RefreshPolicy::Auto.is_fresh(updated_at, now), None);
+        assert_eq!(RefreshPolicy::Always.is_fresh(updated_at, now), Some(false));
+        assert_eq!(RefreshPolicy::Manual.is_fresh(updated_at, now), Some(true));
+        assert_eq!(RefreshPolicy::Manual.is_fresh(None, now), Some(false));
+        assert_eq!(
+            "every 6h"
+                .parse::<RefreshPolicy>()?
+                .is_fresh(updated_at, now),
+            Some(false)
+        );
+        assert_eq!(
+            "every 8h"
+                .parse::<RefreshPolicy>()?
+                .is_fresh(updated_at, now),
+            Some(true)
+        );
+        // The last 04:00 cutoff was yesterday morning, before the download
+        assert_eq!(
+            "daily 04:00"
+                .parse::<RefreshPolicy>()?
+                .is_fresh(updated_at, now),
+            Some(true)
+        );
+        // The last 02:00 cutoff was this morning, after the download
+        assert_eq!(
+            "daily 02:00"
+                .parse::<RefreshPolicy>()?
+                .is_fresh(updated_at, now),
+            Some(false)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_policies_toml() -> TestResult {
+        let policies: RefreshPolicies = toml::from_str(
+            r#"
+goatbots_prices = "daily 06:30"
+scryfall_bulk_data = "manual"
+"#,
+        )?;
+        assert_eq!(
+            policies,
+            RefreshPolicies {
+                goatbots_prices: "daily 06:30".parse()?,
+                goatbots_card_definitions: RefreshPolicy::Auto,
+                scryfall_bulk_data: RefreshPolicy::Manual,
+            }
+        );
+        assert!(toml::from_str::<RefreshPolicies>(r#"goatbots_prices = "sometimes""#).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+pub use parse_goatbots::card_definitions::GoatBotsCard;
+use parse_goatbots::{
+    card_definitions::{parse_card_def_json, Rarity},
+    price_history::parse_price_history_json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    store::write_atomic, GOATBOTS_CARD_DEFINITIONS_FILENAME, GOATBOTS_PRICE_HISTORY_FILENAME,
+};
+
+/// Name of the file the card definitions are kept in as they were before the last download, to detect new cards
+pub const PREVIOUS_CARD_DEFINITIONS_FILENAME: &str = "goatbots-definitions-previous.json";
+/// Name of the file the cards that were added to the card definitions are recorded in
+pub const NEW_CARDS_FILENAME: &str = "new-cards.json";
+
+/// The countdown to the release of the next MTGO set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseCountdown {
+    pub name: String,
+    pub mtgo_code: String,
+    pub released_at: NaiveDate,
+    /// Days until the release, 0 on the day of the release and negative if the sets weren't fetched since
+    pub days_left: i64,
+}
+
+impl ReleaseCountdown {
+    pub fn new(name: String, mtgo_code: String, released_at: NaiveDate, today: NaiveDate) -> Self {
+        Self {
+            name,
+            mtgo_code,
+            released_at,
+            days_left: (released_at - today).num_days(),
+        }
+    }
+
+    /// Returns true if the set is out on MTGO
+    pub fn is_released(&self) -> bool {
+        self.days_left <= 0
+    }
+}
+
+/// A card that was added to the Goatbots card definitions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewCard {
+    pub name: String,
+    pub cardset: String,
+    pub rarity: Rarity,
+    pub foil: bool,
+    /// When the card was first seen in the card definitions
+    pub detected_at: DateTime<Utc>,
+    /// The first Goatbots price of the card, [None] until the price history has one
+    #[serde(default)]
+    pub first_price: Option<f32>,
+}
+
+/// The cards that were added to the Goatbots card definitions, also known as `new-cards.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NewCards {
+    /// Keyed by MTGO ID, like the card definitions
+    cards: BTreeMap<u32, NewCard>,
+}
+
+impl NewCards {
+    /// Load the new cards from `dir`, or none if they were never recorded
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Self, io::Error> {
+        match fs::read(dir.join(NEW_CARDS_FILENAME)) {
+            Ok(json) => Ok(serde_json::from_slice(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Save the new cards to `dir`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, dir: &Path) -> Result<(), io::Error> {
+        let json = serde_json::to_vec(self)?;
+        write_atomic(&dir.join(NEW_CARDS_FILENAME), &json)
+    }
+
+    /// Record the cards in `current` that aren't in `previous`, cards that are already recorded are kept as they are
+    ///
+    /// # Returns
+    ///
+    /// The number of cards that were recorded
+    pub fn record_added(
+        &mut self,
+        previous: &HashMap<u32, GoatBotsCard>,
+        current: &HashMap<u32, GoatBotsCard>,
+        detected_at: DateTime<Utc>,
+    ) -> usize {
+        let mut recorded = 0;
+        for (id, card) in current {
+            if previous.contains_key(id) || self.cards.contains_key(id) {
+                continue;
+            }
+            self.cards.insert(
+                *id,
+                NewCard {
+                    name: card.name.clone(),
+                    cardset: card.cardset.clone(),
+                    rarity: card.rarity.clone(),
+                    foil: card.foil,
+                    detected_at,
+                    first_price: None,
+                },
+            );
+            recorded += 1;
+        }
+        recorded
+    }
+
+    /// Assign the price in `prices` to the cards that don't have a first price yet
+    ///
+    /// # Returns
+    ///
+    /// The number of cards that got their first price
+    pub fn observe_prices(&mut self, prices: &HashMap<u32, f32>) -> usize {
+        let mut observed = 0;
+        for (id, card) in self
+            .cards
+            .iter_mut()
+            .filter(|(_, c)| c.first_price.is_none())
+        {
+            if let Some(&price) = prices.get(id) {
+                card.first_price = Some(price);
+                observed += 1;
+            }
+        }
+        observed
+    }
+
+    /// Returns true if any of the cards don't have a first price yet
+    pub fn is_missing_prices(&self) -> bool {
+        self.cards.values().any(|c| c.first_price.is_none())
+    }
+
+    /// The new cards and their MTGO IDs in ascending order of ID
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &NewCard)> {
+        self.cards.iter().map(|(id, card)| (*id, card))
+    }
+
+    /// The new cards of the set with the given Goatbots set code, e.g. `MH3`, ignoring case
+    pub fn in_set<'a>(&'a self, cardset: &'a str) -> impl Iterator<Item = (u32, &'a NewCard)> {
+        self.iter()
+            .filter(move |(_, card)| card.cardset.eq_ignore_ascii_case(cardset))
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
+
+/// Copy the stored card definitions in `dir` to [PREVIOUS_CARD_DEFINITIONS_FILENAME] before they're replaced
+///
+/// # Errors
+///
+/// Returns an error if the stored card definitions exist but cannot be copied
+pub fn keep_previous_card_definitions(dir: &Path) -> Result<(), io::Error> {
+    let current = dir.join(GOATBOTS_CARD_DEFINITIONS_FILENAME);
+    if !current.exists() {
+        return Ok(());
+    }
+    fs::copy(current, dir.join(PREVIOUS_CARD_DEFINITIONS_FILENAME)).map(|_| ())
+}
+
+/// Diff the card definitions in `dir` against the previous copy and record the cards that were added in
+/// [NEW_CARDS_FILENAME]. Nothing is recorded if there's no previous copy, as every card would be new.
+///
+/// # Returns
+///
+/// The number of cards that were recorded
+///
+/// # Errors
+///
+/// Returns an error if the card definitions cannot be parsed or the new cards cannot be saved
+pub fn detect_new_cards(dir: &Path, detected_at: DateTime<Utc>) -> Result<usize, io::Error> {
+    let previous_path = dir.join(PREVIOUS_CARD_DEFINITIONS_FILENAME);
+    if !previous_path.exists() {
+        return Ok(0);
+    }
+    let previous = parse_card_def_json(&previous_path).map_err(boxed_to_io_err)?;
+    let current = parse_card_def_json(&dir.join(GOATBOTS_CARD_DEFINITIONS_FILENAME))
+        .map_err(boxed_to_io_err)?;
+
+    let mut new_cards = NewCards::load(dir)?;
+    let recorded = new_cards.record_added(&previous, &current, detected_at);
+    if recorded > 0 {
+        // Cards that already have a price in the stored price history got it before they were detected
+        let price_history = dir.join(GOATBOTS_PRICE_HISTORY_FILENAME);
+        if price_history.exists() {
+            new_cards.observe_prices(
+                &parse_price_history_json(&price_history).map_err(boxed_to_io_err)?,
+            );
+        }
+        new_cards.save(dir)?;
+    }
+    Ok(recorded)
+}
+
+/// Assign the first price to the new cards in `dir` that don't have one, from the stored price history
+///
+/// # Returns
+///
+/// The number of cards that got their first price
+///
+/// # Errors
+///
+/// Returns an error if the new cards or price history cannot be parsed, or the new cards cannot be saved
+pub fn record_first_prices(dir: &Path) -> Result<usize, io::Error> {
+    let mut new_cards = NewCards::load(dir)?;
+    let price_history = dir.join(GOATBOTS_PRICE_HISTORY_FILENAME);
+    if !new_cards.is_missing_prices() || !price_history.exists() {
+        return Ok(0);
+    }
+    let observed = new_cards
+        .observe_prices(&parse_price_history_json(&price_history).map_err(boxed_to_io_err)?);
+    if observed > 0 {
+        new_cards.save(dir)?;
+    }
+    Ok(observed)
+}
+
+fn boxed_to_io_err(e: Box<dyn std::error::Error>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    fn goatbots_card(name: &str, cardset: &str) -> GoatBotsCard {
+        GoatBotsCard::new(name.to_string(), cardset.to_string(), Rarity::Rare, false)
+    }
+
+    #[test]
+    fn test_release_countdown() {
+        let released_at = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap();
+        let countdown = ReleaseCountdown::new(
+            "Modern Horizons 3".to_string(),
+            "mh3".to_string(),
+            released_at,
+            NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+        );
+        assert_eq!(countdown.days_left, 13);
+        assert!(!countdown.is_released());
+
+        let countdown = ReleaseCountdown::new(
+            "Modern Horizons 3".to_string(),
+            "mh3".to_string(),
+            released_at,
+            released_at,
+        );
+        assert!(countdown.is_released());
+    }
+
+    #[test]
+    fn test_record_added_and_first_prices() {
+        let previous = HashMap::from([(1, goatbots_card("Event Ticket", "EVENT"))]);
+        let mut current = previous.clone();
+        current.insert(125000, goatbots_card("Ocelot Pride", "MH3"));
+        current.insert(125001, goatbots_card("Flare of Duplication", "MH3"));
+        let detected_at = Utc::now();
+
+        let mut new_cards = NewCards::default();
+        assert_eq!(new_cards.record_added(&previous, &current, detected_at), 2);
+        // Recording the same diff again doesn't change when the cards were detected
+        assert_eq!(new_cards.record_added(&previous, &current, Utc::now()), 0);
+        assert_eq!(
+            new_cards.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            [125000, 125001]
+        );
+        assert_eq!(new_cards.in_set("mh3").count(), 2);
+        assert_eq!(new_cards.in_set("EVENT").count(), 0);
+
+        assert!(new_cards.is_missing_prices());
+        assert_eq!(
+            new_cards.observe_prices(&HashMap::from([(125000, 12.5)])),
+            1
+        );
+        // The first price is kept when the price changes
+        assert_eq!(
+            new_cards.observe_prices(&HashMap::from([(125000, 9.0), (125001, 0.1)])),
+            1
+        );
+        assert!(!new_cards.is_missing_prices());
+        assert_eq!(
+            new_cards
+                .iter()
+                .map(|(_, c)| c.first_price)
+                .collect::<Vec<_>>(),
+            [Some(12.5), Some(0.1)]
+        );
+        assert!(new_cards.iter().all(|(_, c)| c.detected_at == detected_at));
+    }
+
+    #[test]
+    fn test_detect_new_cards_in_dir() -> TestResult {
+        let dir = TempDir::new()?;
+        let write_defs = |cards: &HashMap<u32, GoatBotsCard>| -> TestResult {
+            fs::write(
+                dir.child(GOATBOTS_CARD_DEFINITIONS_FILENAME),
+                serde_json::to_vec(cards)?,
+            )?;
+            Ok(())
+        };
+        let mut defs = HashMap::from([(1, goatbots_card("Event Ticket", "EVENT"))]);
+
+        // The first download has nothing to diff against
+        keep_previous_card_definitions(dir.path())?;
+        write_defs(&defs)?;
+        assert_eq!(detect_new_cards(dir.path(), Utc::now())?, 0);
+        assert!(!dir.child(NEW_CARDS_FILENAME).exists());
+
+        keep_previous_card_definitions(dir.path())?;
+        defs.insert(125000, goatbots_card("Ocelot Pride", "MH3"));
+        write_defs(&defs)?;
+        fs::write(
+            dir.child(GOATBOTS_PRICE_HISTORY_FILENAME),
+            r#"{"1": 1.0, "125000": 12.5}"#,
+        )?;
+        assert_eq!(detect_new_cards(dir.path(), Utc::now())?, 1);
+
+        let new_cards = NewCards::load(dir.path())?;
+        let (id, card) = new_cards.iter().next().unwrap();
+        assert_eq!(id, 125000);
+        assert_eq!(card.name, "Ocelot Pride");
+        assert_eq!(card.first_price, Some(12.5));
+        assert_eq!(record_first_prices(dir.path())?, 0);
+        Ok(())
+    }
+}
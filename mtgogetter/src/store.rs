@@ -0,0 +1,228 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use parse_goatbots::card_definitions::GoatBotsCard;
+use parse_scryfall::SortedCards;
+use serde::de::IgnoredAny;
+
+pub use state_file::write_atomic;
+
+use crate::FetchError;
+
+/// A new download must have at least this fraction of the records of the stored copy to replace it
+pub const MIN_RECORD_RATIO: f64 = 0.5;
+
+/// The data files written by the fetchers, the variant determines how the contents are validated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFile {
+    /// A JSON array of sets
+    ScryfallSets,
+    /// JSON lines of cards sorted by MTGO ID, or a JSON array of cards as stored before
+    ScryfallCards,
+    /// A JSON object of Goatbots card definitions keyed by MTGO ID, validated with the schema of the parser
+    GoatbotsCardDefinitions,
+    /// A JSON object of prices keyed by MTGO ID
+    GoatbotsPriceHistory,
+}
+
+impl DataFile {
+    /// Parse `contents` according to the schema of the file
+    ///
+    /// # Returns
+    ///
+    /// The number of records
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contents don't match the schema, e.g. because the download was truncated
+    pub fn count_records(self, contents: &[u8]) -> Result<usize, io::Error> {
+        let count = match self {
+            Self::ScryfallSets => {
+                serde_json::from_slice::<Vec<IgnoredAny>>(contents).map(|v| v.len())?
+            }
+            Self::ScryfallCards => {
+                SortedCards::from_reader(contents)?.try_fold(0, |n, card| card.map(|_| n + 1))?
+            }
+            Self::GoatbotsCardDefinitions => {
+                serde_json::from_slice::<HashMap<u32, GoatBotsCard>>(contents).map(|m| m.len())?
+            }
+            Self::GoatbotsPriceHistory => {
+                serde_json::from_slice::<HashMap<u32, f32>>(contents).map(|m| m.len())?
+            }
+        };
+        Ok(count)
+    }
+
+    /// Check that `contents` are a plausible replacement for the copy stored at `previous`
+    ///
+    /// The contents must match the schema and contain at least one record, and at least [MIN_RECORD_RATIO] of the
+    /// records of the stored copy. A stored copy that is missing or invalid itself, or no `previous` at all, doesn't
+    /// restrict the record count.
+    ///
+    /// # Returns
+    ///
+    /// The number of records
+    ///
+    /// # Errors
+    ///
+    /// Returns an [io::ErrorKind::InvalidData] error describing why the contents were rejected
+    pub fn validate(self, contents: &[u8], previous: Option<&Path>) -> Result<usize, io::Error> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let records = self
+            .count_records(contents)
+            .map_err(|e| invalid(format!("Invalid {self:?} data: {e}")))?;
+        if records == 0 {
+            return Err(invalid(format!("{self:?} data contains no records")));
+        }
+        if let Some(previous) = previous
+            .and_then(|p| fs::read(p).ok())
+            .and_then(|prev| self.count_records(&prev).ok())
+        {
+            if (records as f64) < previous as f64 * MIN_RECORD_RATIO {
+                return Err(invalid(format!(
+                    "{self:?} data contains {records} records, the stored copy has {previous}"
+                )));
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// Validate `contents` and atomically replace the file at `dst` with them
+///
+/// If the validation fails, the stored copy is kept as it is.
+///
+/// # Arguments
+///
+/// * `dst` - The file to replace
+/// * `contents` - The new contents
+/// * `kind` - Determines how the contents are validated, see [DataFile::validate]
+///
+/// # Returns
+///
+/// The number of records stored
+///
+/// # Errors
+///
+/// Returns a [FetchError::Local] if the contents are rejected or cannot be written
+pub fn store_validated(dst: &Path, contents: &[u8], kind: DataFile) -> Result<usize, FetchError> {
+    store(dst, contents, kind, Some(dst))
+}
+
+/// Like [store_validated] but the record count isn't compared to the stored copy, for when the stored copy is of a
+/// different selection of the data, e.g. another Scryfall bulk dataset
+///
+/// # Errors
+///
+/// Returns a [FetchError::Local] if the contents are rejected or cannot be written
+pub fn store_validated_replacing(
+    dst: &Path,
+    contents: &[u8],
+    kind: DataFile,
+) -> Result<usize, FetchError> {
+    store(dst, contents, kind, None)
+}
+
+fn store(
+    dst: &Path,
+    contents: &[u8],
+    kind: DataFile,
+    previous: Option<&Path>,
+) -> Result<usize, FetchError> {
+    let records = kind.validate(contents, previous).map_err(|e| {
+        log::warn!("Keeping the stored copy of {dst:?}: {e}");
+        FetchError::local(dst)(e)
+    })?;
+    write_atomic(dst, contents).map_err(FetchError::local(dst))?;
+    log::info!("Stored {records} records in {dst:?}");
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    const CARD_DEFS: &str = r#"{
+        "347": {"name": "Black Lotus", "cardset": "VMA", "rarity": "Bonus", "foil": 0},
+        "348": {"name": "Black Lotus", "cardset": "VMA", "rarity": "Bonus", "foil": 1}
+    }"#;
+
+    #[test]
+    fn test_count_records() -> TestResult {
+        assert_eq!(
+            DataFile::GoatbotsCardDefinitions.count_records(CARD_DEFS.as_bytes())?,
+            2
+        );
+        assert_eq!(
+            DataFile::GoatbotsPriceHistory.count_records(br#"{"347": 244.99}"#)?,
+            1
+        );
+        assert_eq!(DataFile::ScryfallSets.count_records(b"[{}, {}, {}]")?, 3);
+        let card = r#"{"mtgo_id": 235, "name": "Swamp", "released_at": "2003-12-31", "rarity": "common", "prices": {}}"#;
+        assert_eq!(
+            DataFile::ScryfallCards.count_records(format!("{card}\n{card}\n").as_bytes())?,
+            2
+        );
+        assert_eq!(
+            DataFile::ScryfallCards.count_records(format!("[{card}]").as_bytes())?,
+            1
+        );
+        assert!(DataFile::ScryfallCards.count_records(b"{}\n").is_err());
+        // Truncated download
+        assert!(DataFile::GoatbotsPriceHistory
+            .count_records(br#"{"347": 244.99, "34"#)
+            .is_err());
+        // A foil flag the parser rejects
+        assert!(DataFile::GoatbotsCardDefinitions
+            .count_records(CARD_DEFS.replace(r#""foil": 1"#, r#""foil": 2"#).as_bytes())
+            .is_err());
+        // Card definitions in the place of prices
+        assert!(DataFile::GoatbotsPriceHistory
+            .count_records(CARD_DEFS.as_bytes())
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_validated_keeps_previous_copy() -> TestResult {
+        let dir = TempDir::new()?;
+        let dst = dir.child("price-history.json");
+        let prices = br#"{"347": 244.99, "348": 419.99, "31745": 0.37}"#;
+
+        assert_eq!(
+            store_validated(&dst, prices, DataFile::GoatbotsPriceHistory)?,
+            3
+        );
+        assert_eq!(fs::read(&dst)?, prices);
+
+        for rejected in [&b"{}"[..], b"", br#"{"347": 244.99}"#, b"<html>"] {
+            let err = store_validated(&dst, rejected, DataFile::GoatbotsPriceHistory).unwrap_err();
+            assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidData);
+            assert_eq!(fs::read(&dst)?, prices);
+        }
+        assert!(!dir.child("price-history.json.tmp").exists());
+
+        // A smaller update that's still plausible replaces the stored copy
+        let update = br#"{"347": 250.0, "348": 420.0}"#;
+        store_validated(&dst, update, DataFile::GoatbotsPriceHistory)?;
+        assert_eq!(fs::read(&dst)?, update);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_stored_copy_is_replaced() -> TestResult {
+        let dir = TempDir::new()?;
+        let dst = dir.child("scryfall-sets.json");
+        fs::write(&dst, "[{}, {}, {")?;
+        store_validated(&dst, b"[{}]", DataFile::ScryfallSets)?;
+        assert_eq!(fs::read(&dst)?, b"[{}]");
+
+        fs::write(&dst, "[{}, {}, {}]")?;
+        assert!(store_validated(&dst, b"[{}]", DataFile::ScryfallSets).is_err());
+        store_validated_replacing(&dst, b"[{}]", DataFile::ScryfallSets)?;
+        assert_eq!(fs::read(&dst)?, b"[{}]");
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// TOML state files with a schema version, migrated to the current layout when they're loaded
+pub mod schema;
+
+/// Write `contents` to a temporary file next to `dst` and rename it to `dst` once it's flushed to disk, so `dst` is
+/// never left partially written
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be written or renamed, the temporary file is removed in that case
+pub fn write_atomic(dst: &Path, contents: &[u8]) -> Result<(), io::Error> {
+    let tmp = tmp_path(dst);
+    let res = fs::File::create(&tmp)
+        .and_then(|mut f| {
+            f.write_all(contents)?;
+            f.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, dst));
+    if res.is_err() {
+        _ = fs::remove_file(&tmp);
+    }
+    res
+}
+
+/// The temporary file that is written before it's renamed to `dst`
+fn tmp_path(dst: &Path) -> PathBuf {
+    let mut tmp = dst.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
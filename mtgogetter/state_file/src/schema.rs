@@ -0,0 +1,205 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Serialize};
+use toml::Table;
+
+use crate::write_atomic;
+
+/// Name of the version field in a [Versioned] TOML file
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Upgrades the table of a [Versioned] file by one schema version
+pub type Migration = fn(&mut Table) -> Result<(), String>;
+
+/// A state file stored as TOML with a `schema_version` field, that is migrated to the current layout when it's loaded
+///
+/// Files written before the field was introduced are version 0.
+pub trait Versioned: Serialize + DeserializeOwned + Default {
+    /// The version of the layout of the type
+    const SCHEMA_VERSION: u32;
+    /// `MIGRATIONS[v]` upgrades a table from version `v` to `v + 1`, so there's one migration per version
+    const MIGRATIONS: &'static [Migration];
+}
+
+/// Parse the TOML `contents` of a [Versioned] file, running the migrations from the version of the contents
+///
+/// # Errors
+///
+/// Returns an [io::ErrorKind::Unsupported] error if the contents were written by a newer version, and an
+/// [io::ErrorKind::InvalidData] error if they aren't valid TOML, a migration fails, or the migrated table doesn't match
+/// the layout of `T`
+pub fn from_versioned_toml<T: Versioned>(contents: &str) -> Result<T, io::Error> {
+    debug_assert_eq!(T::MIGRATIONS.len(), T::SCHEMA_VERSION as usize);
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let mut table: Table = toml::from_str(contents).map_err(|e| invalid(e.to_string()))?;
+    let version = match table.get(SCHEMA_VERSION_KEY) {
+        None => 0,
+        Some(toml::Value::Integer(v)) => {
+            u32::try_from(*v).map_err(|_| invalid(format!("Invalid {SCHEMA_VERSION_KEY}: {v}")))?
+        }
+        Some(v) => return Err(invalid(format!("Invalid {SCHEMA_VERSION_KEY}: {v}"))),
+    };
+    if version > T::SCHEMA_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Schema version {version} is newer than the supported version {}",
+                T::SCHEMA_VERSION
+            ),
+        ));
+    }
+    for (from, migrate) in T::MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("Migrating schema version {from} to {}", from + 1);
+        migrate(&mut table)
+            .map_err(|e| invalid(format!("Failed to migrate from version {from}: {e}")))?;
+    }
+    table.insert(
+        SCHEMA_VERSION_KEY.to_owned(),
+        toml::Value::Integer(T::SCHEMA_VERSION.into()),
+    );
+    T::deserialize(table).map_err(|e| invalid(e.to_string()))
+}
+
+/// Load the [Versioned] file at `path`, see [from_versioned_toml]
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed
+pub fn load_versioned<T: Versioned>(path: &Path) -> Result<T, io::Error> {
+    from_versioned_toml(&fs::read_to_string(path)?)
+}
+
+/// Load the [Versioned] file at `path`, or the default if the file doesn't exist
+///
+/// A file that cannot be parsed is renamed to a timestamped backup (see [backup_path]) and the default is returned,
+/// so the state is reset instead of the application failing to start. A file written by a newer version is left as
+/// it is, the state it holds is only unknown to this version.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or backed up, or an [io::ErrorKind::Unsupported] error if
+/// it was written by a newer version
+pub fn load_versioned_or_reset<T: Versioned>(path: &Path) -> Result<T, io::Error> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(e),
+    };
+    match from_versioned_toml(&contents) {
+        Ok(value) => Ok(value),
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Err(e),
+        Err(e) => {
+            let backup = backup_path(path);
+            log::warn!(
+                "Failed to load {path:?}: {e}. Backing it up to {backup:?} and resetting it"
+            );
+            fs::rename(path, &backup)?;
+            Ok(T::default())
+        }
+    }
+}
+
+/// Serialize the [Versioned] value and atomically write it to `path`
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialized or written
+pub fn save_versioned<T: Versioned>(value: &T, path: &Path) -> Result<(), io::Error> {
+    let toml = toml::to_string(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write_atomic(path, toml.as_bytes())
+}
+
+/// The path an unreadable file is backed up to, e.g. `fetch_log.toml.2024-05-19T090548Z.bak`
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".{}.bak", Utc::now().format("%Y-%m-%dT%H%M%SZ")));
+    PathBuf::from(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    /// Version 1 added `schema_version`, version 2 renamed `count` to `total`
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct State {
+        schema_version: u32,
+        total: u32,
+    }
+
+    impl Versioned for State {
+        const SCHEMA_VERSION: u32 = 2;
+        const MIGRATIONS: &'static [Migration] = &[
+            |_| Ok(()),
+            |table| {
+                let count = table.remove("count").ok_or("missing count")?;
+                table.insert("total".into(), count);
+                Ok(())
+            },
+        ];
+    }
+
+    #[test]
+    fn test_migrations() -> TestResult {
+        let expected = State {
+            schema_version: 2,
+            total: 3,
+        };
+        assert_eq!(from_versioned_toml::<State>("count = 3")?, expected);
+        assert_eq!(
+            from_versioned_toml::<State>("schema_version = 1\ncount = 3")?,
+            expected
+        );
+        assert_eq!(
+            from_versioned_toml::<State>("schema_version = 2\ntotal = 3")?,
+            expected
+        );
+        let err = from_versioned_toml::<State>("schema_version = 3\ntotal = 3").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(from_versioned_toml::<State>("schema_version = 1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_or_reset_backs_up_unreadable_file() -> TestResult {
+        let dir = TempDir::new()?;
+        let path = dir.child("state.toml");
+        assert_eq!(load_versioned_or_reset::<State>(&path)?, State::default());
+
+        fs::write(&path, "total = [")?;
+        assert_eq!(load_versioned_or_reset::<State>(&path)?, State::default());
+        assert!(!path.exists());
+        let backups: Vec<_> = fs::read_dir(dir.path())?.collect::<Result<_, _>>()?;
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0].path())?, "total = [");
+
+        let state = State {
+            schema_version: 2,
+            total: 7,
+        };
+        save_versioned(&state, &path)?;
+        assert_eq!(load_versioned_or_reset::<State>(&path)?, state);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_or_reset_keeps_newer_file() -> TestResult {
+        let dir = TempDir::new()?;
+        let path = dir.child("state.toml");
+        fs::write(&path, "schema_version = 3\nsum = 7")?;
+
+        let err = load_versioned_or_reset::<State>(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(fs::read_to_string(&path)?, "schema_version = 3\nsum = 7");
+        assert_eq!(fs::read_dir(dir.path())?.count(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use std::{fs, io::Write, path::Path};
+
+use testresult::TestResult;
+
+/// An upcoming MTGO set that is added to the sets of the test data, which were all released before it was recorded
+const UPCOMING_SET_JSON: &str = r#"{"object": "set", "code": "ffs", "name": "Far Future Set", "released_at": "2999-01-01", "mtgo_code": "ffs"}"#;
+
+pub fn write_zip(path: &Path, name: &str, contents: &[u8]) -> TestResult {
+    let mut zip = zip::ZipWriter::new(fs::File::create(path)?);
+    zip.start_file(name, zip::write::SimpleFileOptions::default())?;
+    zip.write_all(contents)?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Create a mirror with the layout described in [mtgogetter::config::Endpoints]
+pub fn create_mirror(dir: &Path) -> TestResult {
+    let scryfall = dir.join("scryfall");
+    let goatbots = dir.join("goatbots");
+    fs::create_dir_all(&scryfall)?;
+    fs::create_dir_all(&goatbots)?;
+
+    let mut sets: serde_json::Value = serde_json::from_slice(&fs::read(
+        "../test/test-data/scryfall/sets-small-16sets.json",
+    )?)?;
+    sets["data"]
+        .as_array_mut()
+        .ok_or("sets test data has no data array")?
+        .insert(0, serde_json::from_str(UPCOMING_SET_JSON)?);
+    fs::write(
+        scryfall.join("sets.json"),
+        serde_json::to_vec_pretty(&sets)?,
+    )?;
+    fs::copy(
+        "../test/test-data/scryfall/bulk-data-info-default-cards.json",
+        scryfall.join("bulk-data-info.json"),
+    )?;
+    fs::copy(
+        "../test/test-data/scryfall/default-cards-small-5cards.json",
+        scryfall.join("default-cards-20240519090548.json"),
+    )?;
+    write_zip(
+        &goatbots.join("price-history.zip"),
+        "price-history.json",
+        &fs::read("../test/test-data/goatbots/price-hist-small-5cards.json")?,
+    )?;
+    write_zip(
+        &goatbots.join("card-definitions.zip"),
+        "card-definitions.json",
+        &fs::read("../test/test-data/goatbots/card-defs-small-5cards.json")?,
+    )?;
+    Ok(())
+}
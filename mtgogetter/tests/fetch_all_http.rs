@@ -0,0 +1,292 @@
+mod common;
+
+use std::fs;
+
+use chrono::{Days, Utc};
+use get_http::test_server::MirrorServer;
+use mtgogetter::{
+    config::Config, fetch_all_with_options, load_fetch_log, provenance::ProvenanceManifest,
+    releases::NewCards, FetchError, FetchOptions, FetchStatus,
+};
+use parse_scryfall::SortedCards;
+use pretty_assertions::{assert_eq, assert_ne};
+use temp_dir::TempDir;
+use testresult::TestResult;
+
+use common::{create_mirror, write_zip};
+
+/// A mirror of the test data served over HTTP, and an appdata directory configured to fetch from it
+struct Harness {
+    mirror: TempDir,
+    server: MirrorServer,
+    appdata: TempDir,
+}
+
+impl Harness {
+    fn new() -> TestResult<Self> {
+        let mirror = TempDir::new()?;
+        create_mirror(mirror.path())?;
+        let server = MirrorServer::serve_dir(mirror.path())?;
+        let appdata = TempDir::new()?;
+        fs::write(
+            appdata.child(Config::FILENAME),
+            format!("[endpoints]\nmirror = \"{}\"\n", server.url()),
+        )?;
+        Ok(Self {
+            mirror,
+            server,
+            appdata,
+        })
+    }
+
+    /// Run [fetch_all_with_options] and return the names of the downloaded data
+    fn fetch_all(&self, force: bool) -> Result<Vec<&'static str>, FetchError> {
+        let opts = FetchOptions {
+            force,
+            ..Default::default()
+        };
+        let statuses = fetch_all_with_options(self.appdata.path(), opts)?;
+        Ok(downloaded(&statuses))
+    }
+}
+
+fn downloaded(statuses: &[FetchStatus]) -> Vec<&'static str> {
+    statuses
+        .iter()
+        .filter(|s| s.downloaded)
+        .map(|s| s.data)
+        .collect()
+}
+
+#[test]
+fn test_first_run_downloads_everything() -> TestResult {
+    let harness = Harness::new()?;
+
+    assert_eq!(
+        harness.fetch_all(false)?,
+        [
+            "scryfall-sets",
+            "scryfall-bulk-cards",
+            "goatbots-card-definitions",
+            "goatbots-price-history"
+        ]
+    );
+    let mut requests = harness.server.requests();
+    requests.sort();
+    assert_eq!(
+        requests,
+        [
+            "/goatbots/card-definitions.zip",
+            "/goatbots/price-history.zip",
+            "/scryfall/bulk-data-info.json",
+            "/scryfall/default-cards-20240519090548.json",
+            "/scryfall/sets.json",
+        ]
+    );
+
+    let appdata = &harness.appdata;
+    let cards =
+        SortedCards::open(&appdata.child("scryfall-cards.json"))?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(cards.len(), 5);
+    assert_eq!(
+        fs::read_to_string(appdata.child("price-history.json"))?,
+        fs::read_to_string("../test/test-data/goatbots/price-hist-small-5cards.json")?
+    );
+    assert!(appdata.child("card-definitions.json").exists());
+    let fetch_log = load_fetch_log(appdata.path())?;
+    let countdown = fetch_log
+        .next_release_countdown(Utc::now().date_naive())
+        .unwrap();
+    assert_eq!(countdown.mtgo_code, "ffs");
+    assert!(fetch_log.goatbots_card_definitions_updated_at().is_some());
+    assert!(fetch_log.goatbots_prices_updated_at().is_some());
+    // Nothing was released since the first run
+    assert!(NewCards::load(appdata.path())?.is_empty());
+
+    let manifest = ProvenanceManifest::load(appdata.path())?;
+    let files: Vec<&str> = manifest.iter().map(|(file, _)| file).collect();
+    assert_eq!(
+        files,
+        [
+            "card-definitions.json",
+            "price-history.json",
+            "scryfall-cards.json",
+            "scryfall-sets.json"
+        ]
+    );
+    let prices = manifest.get("price-history.json").unwrap();
+    assert_eq!(
+        prices.source_url,
+        harness
+            .server
+            .url()
+            .join("goatbots/price-history.zip")?
+            .as_str()
+    );
+    assert_eq!(prices.http_status, Some(200));
+    assert_eq!(prices.records, 5);
+    assert!(prices.matches(&fs::read(appdata.child("price-history.json"))?));
+    let cards = manifest.get("scryfall-cards.json").unwrap();
+    assert_eq!(cards.records, 5);
+    assert!(cards.matches(&fs::read(appdata.child("scryfall-cards.json"))?));
+    Ok(())
+}
+
+#[test]
+fn test_up_to_date_data_is_not_downloaded() -> TestResult {
+    let harness = Harness::new()?;
+    harness.fetch_all(false)?;
+    harness.server.clear_requests();
+
+    // Only the bulk data info is checked, as the bulk data is updated whenever Scryfall updates it
+    assert_eq!(harness.fetch_all(false)?, Vec::<&str>::new());
+    assert_eq!(harness.server.requests(), ["/scryfall/bulk-data-info.json"]);
+
+    // The provenance of the data that wasn't downloaded is kept
+    let manifest = ProvenanceManifest::load(harness.appdata.path())?;
+
+    // Forcing a fetch still sends conditional requests for the unchanged Goatbots data
+    assert_eq!(
+        harness.fetch_all(true)?,
+        ["scryfall-sets", "scryfall-bulk-cards"]
+    );
+    let after = ProvenanceManifest::load(harness.appdata.path())?;
+    assert_eq!(
+        after.get("price-history.json"),
+        manifest.get("price-history.json")
+    );
+    assert_ne!(
+        after.get("scryfall-sets.json"),
+        manifest.get("scryfall-sets.json")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_new_set_released() -> TestResult {
+    let harness = Harness::new()?;
+    harness.fetch_all(false)?;
+    let appdata = &harness.appdata;
+
+    // Time passes, the next set was released yesterday and the Goatbots data was last updated the day before
+    let today = Utc::now().date_naive();
+    let yesterday = today - Days::new(1);
+    let two_days_ago = (Utc::now() - Days::new(2)).to_rfc3339();
+    let fetch_log_path = appdata.child("fetch_log.toml");
+    let mut fetch_log: toml::Table = fs::read_to_string(&fetch_log_path)?.parse()?;
+    fetch_log["goatbots"]["card_definitions_updated_at"] = two_days_ago.clone().into();
+    fetch_log["goatbots"]["prices_updated_at"] = two_days_ago.into();
+    fetch_log["scryfall"]["next_released_mtgo_set"] =
+        format!("name = \"Modern Horizons 3\"\nreleased_at = \"{yesterday}\"\nmtgo_code = \"mh3\"")
+            .parse::<toml::Table>()?
+            .into();
+    fs::write(&fetch_log_path, toml::to_string(&fetch_log)?)?;
+
+    // And the mirror has the released set, the one after it, and its cards with prices
+    let next_release = today + Days::new(30);
+    let sets_path = harness.mirror.path().join("scryfall/sets.json");
+    let mut sets: serde_json::Value = serde_json::from_slice(&fs::read(&sets_path)?)?;
+    let data = sets["data"].as_array_mut().unwrap();
+    data.insert(
+        1,
+        serde_json::json!({"name": "Bloomburrow", "released_at": next_release, "mtgo_code": "blb"}),
+    );
+    data.insert(
+        2,
+        serde_json::json!({"name": "Modern Horizons 3", "released_at": yesterday, "mtgo_code": "mh3"}),
+    );
+    let goatbots = harness.mirror.path().join("goatbots");
+    fs::write(&sets_path, serde_json::to_vec(&sets)?)?;
+    let mut card_defs: serde_json::Value = serde_json::from_slice(&fs::read(
+        "../test/test-data/goatbots/card-defs-small-5cards.json",
+    )?)?;
+    card_defs["130000"] = serde_json::json!({
+        "name": "Ajani, Nacatl Pariah",
+        "cardset": "MH3",
+        "rarity": "Mythic",
+        "foil": 0
+    });
+    write_zip(
+        &goatbots.join("card-definitions.zip"),
+        "card-definitions.json",
+        &serde_json::to_vec(&card_defs)?,
+    )?;
+    let mut prices: serde_json::Value = serde_json::from_slice(&fs::read(
+        "../test/test-data/goatbots/price-hist-small-5cards.json",
+    )?)?;
+    prices["130000"] = 12.5.into();
+    write_zip(
+        &goatbots.join("price-history.zip"),
+        "price-history.json",
+        &serde_json::to_vec(&prices)?,
+    )?;
+
+    assert_eq!(
+        harness.fetch_all(false)?,
+        [
+            "scryfall-sets",
+            "goatbots-card-definitions",
+            "goatbots-price-history"
+        ]
+    );
+    let countdown = load_fetch_log(appdata.path())?
+        .next_release_countdown(today)
+        .unwrap();
+    assert_eq!(countdown.name, "Bloomburrow");
+    assert_eq!(countdown.days_left, 30);
+
+    let new_cards = NewCards::load(appdata.path())?;
+    let new_cards: Vec<_> = new_cards.iter().collect();
+    assert_eq!(new_cards.len(), 1);
+    let (id, card) = new_cards[0];
+    assert_eq!(id, 130000);
+    assert_eq!(card.name, "Ajani, Nacatl Pariah");
+    assert_eq!(card.cardset, "MH3");
+    assert_eq!(card.first_price, Some(12.5));
+
+    // The card definitions were updated after the day of the release, so they are up to date now
+    assert_eq!(harness.fetch_all(false)?, Vec::<&str>::new());
+    Ok(())
+}
+
+#[test]
+fn test_corrupted_download_keeps_previous_data() -> TestResult {
+    let harness = Harness::new()?;
+    harness.fetch_all(false)?;
+    let appdata = &harness.appdata;
+    let card_defs = fs::read(appdata.child("card-definitions.json"))?;
+    let prices = fs::read(appdata.child("price-history.json"))?;
+    let card_defs_updated_at =
+        load_fetch_log(appdata.path())?.goatbots_card_definitions_updated_at();
+
+    // The card definitions are cut off in the middle of the archive
+    let card_defs_zip = harness.mirror.path().join("goatbots/card-definitions.zip");
+    let zip = fs::read(&card_defs_zip)?;
+    fs::write(&card_defs_zip, &zip[..zip.len() / 2])?;
+    let res = harness.fetch_all(true);
+    assert!(matches!(res, Err(FetchError::Archive { .. })), "{res:?}");
+    // The failed download is not recorded as an update
+    assert_eq!(
+        load_fetch_log(appdata.path())?.goatbots_card_definitions_updated_at(),
+        card_defs_updated_at
+    );
+
+    // The price history is a valid archive of a truncated file
+    fs::write(&card_defs_zip, zip)?;
+    let prices_updated_at = load_fetch_log(appdata.path())?.goatbots_prices_updated_at();
+    write_zip(
+        &harness.mirror.path().join("goatbots/price-history.zip"),
+        "price-history.json",
+        &prices[..prices.len() / 2],
+    )?;
+    let res = harness.fetch_all(true);
+    assert!(matches!(res, Err(FetchError::Local { .. })), "{res:?}");
+    assert_eq!(fs::read(appdata.child("card-definitions.json"))?, card_defs);
+    assert_eq!(fs::read(appdata.child("price-history.json"))?, prices);
+    assert!(!appdata.child("price-history.json.tmp").exists());
+    assert_eq!(
+        load_fetch_log(appdata.path())?.goatbots_prices_updated_at(),
+        prices_updated_at
+    );
+    Ok(())
+}
@@ -0,0 +1,282 @@
+mod common;
+
+use std::{fs, sync::Mutex};
+
+use mtgogetter::{
+    config::Config, fetch_all_with_options, fetch_all_with_progress, FetchError, FetchOptions,
+    FetchPhase, FetchProgress, ProgressTracker,
+};
+use parse_scryfall::SortedCards;
+use pretty_assertions::assert_eq;
+use temp_dir::TempDir;
+use testresult::TestResult;
+
+use common::{create_mirror, write_zip};
+
+#[test]
+fn test_fetch_all_from_file_mirror() -> TestResult {
+    let mirror = TempDir::new()?;
+    create_mirror(mirror.path())?;
+    let appdata = TempDir::new()?;
+    let mirror_url = get_http::Url::from_directory_path(mirror.path()).unwrap();
+    fs::write(
+        appdata.child(Config::FILENAME),
+        format!("[endpoints]\nmirror = \"{mirror_url}\"\n"),
+    )?;
+
+    let statuses = fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+    assert!(statuses.iter().all(|s| s.downloaded), "{statuses:?}");
+
+    let sets: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(appdata.child("scryfall-sets.json"))?)?;
+    assert_eq!(sets.as_array().map(Vec::len), Some(5));
+    let cards =
+        SortedCards::open(&appdata.child("scryfall-cards.json"))?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(cards.len(), 5);
+    assert!(cards.is_sorted_by_key(|c| c.mtgo_id));
+    assert_eq!(
+        fs::read_to_string(appdata.child("price-history.json"))?,
+        fs::read_to_string("../test/test-data/goatbots/price-hist-small-5cards.json")?
+    );
+    assert!(appdata.child("card-definitions.json").exists());
+    assert!(appdata.child("fetch_log.toml").exists());
+    let manifest = mtgogetter::provenance::ProvenanceManifest::load(appdata.path())?;
+    assert!(manifest.iter().all(|(_, p)| p.http_status.is_none()));
+    assert_eq!(
+        manifest.get("scryfall-sets.json").map(|p| p.records),
+        Some(5)
+    );
+
+    // Everything is up to date now, except the Scryfall bulk data which is checked against the bulk data info
+    let statuses = fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+    let downloaded: Vec<&str> = statuses
+        .iter()
+        .filter(|s| s.downloaded)
+        .map(|s| s.data)
+        .collect();
+    assert_eq!(downloaded, Vec::<&str>::new());
+
+    // Unless forced
+    let statuses = fetch_all_with_options(
+        appdata.path(),
+        FetchOptions {
+            force: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(statuses.iter().all(|s| s.downloaded));
+    Ok(())
+}
+
+#[test]
+fn test_fetch_all_reports_progress() -> TestResult {
+    let mirror = TempDir::new()?;
+    create_mirror(mirror.path())?;
+    let appdata = TempDir::new()?;
+    let mirror_url = get_http::Url::from_directory_path(mirror.path()).unwrap();
+    fs::write(
+        appdata.child(Config::FILENAME),
+        format!("[endpoints]\nmirror = \"{mirror_url}\"\n"),
+    )?;
+
+    let events = Mutex::new(Vec::new());
+    fetch_all_with_progress(appdata.path(), FetchOptions::default(), &|p| {
+        events.lock().unwrap().push(p)
+    })?;
+    let events = events.into_inner()?;
+
+    // The phases run concurrently, but every phase is started and finished once
+    for phase in FetchPhase::ALL {
+        let started = events
+            .iter()
+            .position(|e| *e == FetchProgress::Phase(phase));
+        let finished = events
+            .iter()
+            .position(|e| *e == FetchProgress::Finished(phase));
+        assert!(
+            started.is_some() && started < finished,
+            "{phase:?}: {events:?}"
+        );
+    }
+    // The sets are fetched before the card definitions, which depend on the next released set
+    assert!(
+        events
+            .iter()
+            .position(|e| *e == FetchProgress::Finished(FetchPhase::ScryfallSets))
+            < events
+                .iter()
+                .position(|e| *e == FetchProgress::Phase(FetchPhase::GoatbotsCardDefinitions))
+    );
+
+    let price_history_zip_len =
+        fs::metadata(mirror.path().join("goatbots/price-history.zip"))?.len();
+    assert!(events.contains(&FetchProgress::Bytes {
+        phase: FetchPhase::GoatbotsPriceHistory,
+        downloaded: price_history_zip_len,
+        total: Some(price_history_zip_len),
+    }));
+
+    let mut tracker = ProgressTracker::default();
+    let fractions: Vec<f32> = events.iter().map(|e| tracker.update(*e)).collect();
+    assert!(fractions.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(fractions.last(), Some(&1.));
+    Ok(())
+}
+
+#[test]
+fn test_fetch_all_single_worker() -> TestResult {
+    let mirror = TempDir::new()?;
+    create_mirror(mirror.path())?;
+    let appdata = TempDir::new()?;
+    let mirror_url = get_http::Url::from_directory_path(mirror.path()).unwrap();
+    fs::write(
+        appdata.child(Config::FILENAME),
+        format!("[endpoints]\nmirror = \"{mirror_url}\"\n"),
+    )?;
+
+    let opts = FetchOptions {
+        jobs: 1,
+        ..Default::default()
+    };
+    let statuses = fetch_all_with_options(appdata.path(), opts)?;
+    let data: Vec<&str> = statuses.iter().map(|s| s.data).collect();
+    assert_eq!(
+        data,
+        [
+            "scryfall-sets",
+            "scryfall-bulk-cards",
+            "goatbots-card-definitions",
+            "goatbots-price-history"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_fetch_all_keeps_previous_data_if_download_is_invalid() -> TestResult {
+    let mirror = TempDir::new()?;
+    create_mirror(mirror.path())?;
+    let appdata = TempDir::new()?;
+    let mirror_url = get_http::Url::from_directory_path(mirror.path()).unwrap();
+    fs::write(
+        appdata.child(Config::FILENAME),
+        format!("[endpoints]\nmirror = \"{mirror_url}\"\n"),
+    )?;
+    fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+    let prices = fs::read(appdata.child("price-history.json"))?;
+
+    // The mirror now serves a truncated price history
+    write_zip(
+        &mirror.path().join("goatbots/price-history.zip"),
+        "price-history.json",
+        &prices[..prices.len() / 2],
+    )?;
+    let res = fetch_all_with_options(
+        appdata.path(),
+        FetchOptions {
+            force: true,
+            ..Default::default()
+        },
+    );
+    assert!(matches!(res, Err(FetchError::Local { .. })), "{res:?}");
+    assert_eq!(fs::read(appdata.child("price-history.json"))?, prices);
+    assert!(!appdata.child("price-history.json.tmp").exists());
+    Ok(())
+}
+
+#[test]
+fn test_fetch_all_refresh_policies() -> TestResult {
+    let mirror = TempDir::new()?;
+    create_mirror(mirror.path())?;
+    let appdata = TempDir::new()?;
+    let mirror_url = get_http::Url::from_directory_path(mirror.path()).unwrap();
+    fs::write(
+        appdata.child(Config::FILENAME),
+        format!(
+            "[endpoints]\nmirror = \"{mirror_url}\"\n\n[refresh]\ngoatbots_prices = \"always\"\nscryfall_bulk_data = \"manual\"\n"
+        ),
+    )?;
+    fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+
+    // The bulk data info can't be fetched, but it isn't needed as the bulk data is only refreshed manually
+    fs::remove_file(mirror.path().join("scryfall/bulk-data-info.json"))?;
+    let statuses = fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+    let downloaded: Vec<&str> = statuses
+        .iter()
+        .filter(|s| s.downloaded)
+        .map(|s| s.data)
+        .collect();
+    assert_eq!(downloaded, ["goatbots-price-history"]);
+    Ok(())
+}
+
+#[test]
+fn test_fetch_all_switching_bulk_dataset() -> TestResult {
+    let mirror = TempDir::new()?;
+    create_mirror(mirror.path())?;
+    let scryfall = mirror.path().join("scryfall");
+    fs::write(
+        scryfall.join("bulk-data-info.json"),
+        r#"{
+  "object": "list",
+  "data": [
+    {
+      "type": "oracle_cards",
+      "download_uri": "https://data.scryfall.io/oracle-cards/oracle-cards-20240519090211.json",
+      "updated_at": "2024-05-19T09:02:11.000+00:00"
+    },
+    {
+      "type": "default_cards",
+      "download_uri": "https://data.scryfall.io/default-cards/default-cards-20240519090548.json",
+      "updated_at": "2024-05-19T09:05:48.000+00:00"
+    }
+  ]
+}"#,
+    )?;
+    let default_cards: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(
+        scryfall.join("default-cards-20240519090548.json"),
+    )?)?;
+    fs::write(
+        scryfall.join("oracle-cards-20240519090211.json"),
+        serde_json::to_vec(&default_cards[..1])?,
+    )?;
+
+    let appdata = TempDir::new()?;
+    let mirror_url = get_http::Url::from_directory_path(mirror.path()).unwrap();
+    let config = format!("[endpoints]\nmirror = \"{mirror_url}\"\n");
+    fs::write(appdata.child(Config::FILENAME), &config)?;
+    fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+
+    // The oracle cards are older than the stored default cards and a fraction of them, but they are another dataset
+    fs::write(
+        appdata.child(Config::FILENAME),
+        format!("scryfall_bulk_dataset = \"oracle_cards\"\n{config}"),
+    )?;
+    let statuses = fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+    assert!(statuses
+        .iter()
+        .any(|s| s.data == "scryfall-bulk-cards" && s.downloaded));
+    let cards = SortedCards::open(&appdata.child("scryfall-cards.json"))?;
+    assert_eq!(cards.count(), 1);
+
+    let statuses = fetch_all_with_options(appdata.path(), FetchOptions::default())?;
+    assert!(statuses
+        .iter()
+        .any(|s| s.data == "scryfall-bulk-cards" && !s.downloaded));
+    Ok(())
+}
+
+#[test]
+fn test_fetch_all_missing_mirror_file_is_error() -> TestResult {
+    let mirror = TempDir::new()?;
+    let appdata = TempDir::new()?;
+    let mirror_url = get_http::Url::from_directory_path(mirror.path()).unwrap();
+    fs::write(
+        appdata.child(Config::FILENAME),
+        format!("[endpoints]\nmirror = \"{mirror_url}\"\n"),
+    )?;
+
+    let res = fetch_all_with_options(appdata.path(), FetchOptions::default());
+    assert!(matches!(res, Err(FetchError::Io { .. })), "{res:?}");
+    Ok(())
+}
@@ -1,4 +1,4 @@
-pub mod paths;
+pub mod scheduler;
 pub mod state;
 pub mod update;
 pub mod util;
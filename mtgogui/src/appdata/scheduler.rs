@@ -0,0 +1,71 @@
+use std::{io, path::Path, thread, time::Duration};
+
+use chrono::Utc;
+use fltk::app::Sender;
+use mtgoupdater::new_update_all::FetchPhase;
+
+use crate::Message;
+
+/// How often the freshness of the card data is checked while the GUI is open
+pub const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// The interval is doubled for every refresh that didn't make the card data fresh, e.g. while offline, up to
+/// `2^MAX_BACKOFF_DOUBLINGS` times the interval
+const MAX_BACKOFF_DOUBLINGS: u32 = 4;
+
+/// Spawn a thread that checks the freshness of the card data every `interval` and sends a
+/// [Message::RefreshCardData] if any of it is stale
+///
+/// The thread runs until the application exits. If the card data is still stale at the check after a refresh, the
+/// refresh failed, and the checks back off until the card data is fresh again.
+///
+/// # Arguments
+///
+/// * `ev_sender` - [Sender] to send the [Message] to
+/// * `interval` - Time between the checks, the first check is after one interval as the data is updated at startup
+pub fn spawn_refresh_scheduler(ev_sender: Sender<Message>, interval: Duration) {
+    log::info!("Spawning Refresh Scheduler thread, checking every {interval:?}");
+    thread::Builder::new()
+        .name("Refresh Scheduler".to_string())
+        .spawn(move || {
+            let mut refresh_requested = false;
+            let mut failed_refreshes = 0;
+            loop {
+                thread::sleep(interval * 2u32.pow(failed_refreshes));
+                let appdata_dir = match super::util::appdata_path() {
+                    Ok(appdata_dir) => appdata_dir,
+                    Err(e) => {
+                        log::warn!("Refresh Scheduler failed to get appdata path: {e}");
+                        continue;
+                    }
+                };
+                match stale_phases(&appdata_dir) {
+                    Ok(stale) if stale.is_empty() => {
+                        log::debug!("Card data is up to date");
+                        refresh_requested = false;
+                        failed_refreshes = 0;
+                    }
+                    Ok(stale) => {
+                        if refresh_requested {
+                            failed_refreshes = (failed_refreshes + 1).min(MAX_BACKOFF_DOUBLINGS);
+                            log::warn!(
+                                "Card data is still stale after a refresh, checking again in {:?}",
+                                interval * 2u32.pow(failed_refreshes)
+                            );
+                        }
+                        log::info!("Stale card data: {stale:?} - refreshing in the background");
+                        ev_sender.send(Message::RefreshCardData);
+                        refresh_requested = true;
+                    }
+                    Err(e) => log::warn!("Refresh Scheduler failed to check the card data: {e}"),
+                }
+            }
+        })
+        .expect("Failed spawning Refresh Scheduler thread");
+}
+
+/// Evaluate the freshness checks of the fetch log in the appdata directory with the configured refresh policies
+fn stale_phases(appdata_dir: &Path) -> io::Result<Vec<FetchPhase>> {
+    let config = mtgogetter::load_config(appdata_dir)?;
+    let fetch_log = mtgogetter::load_fetch_log(appdata_dir)?;
+    Ok(mtgogetter::stale_phases(&fetch_log, &config, Utc::now()))
+}
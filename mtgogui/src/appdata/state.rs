@@ -2,15 +2,32 @@ use std::{ffi::OsString, io, path::PathBuf};
 
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
+use state_file::schema::{self, Migration, Versioned};
 use toml::Table;
 
 use super::GUI_STATE;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct GuiState {
+    schema_version: u32,
     tradelist_added_date: Option<DateTime<Utc>>,
 }
 
+impl Default for GuiState {
+    fn default() -> Self {
+        Self {
+            schema_version: Self::SCHEMA_VERSION,
+            tradelist_added_date: None,
+        }
+    }
+}
+
+impl Versioned for GuiState {
+    const SCHEMA_VERSION: u32 = 1;
+    /// Version 1 added the `schema_version`
+    const MIGRATIONS: &'static [Migration] = &[|_| Ok(())];
+}
+
 impl GuiState {
     /// Create a new [GuiState] instance
     pub fn new() -> Self {
@@ -29,40 +46,24 @@ impl GuiState {
     pub fn save(&self, mut dst_dir: PathBuf) -> io::Result<()> {
         dst_dir.push(GUI_STATE);
         log::debug!("Saving GUI state to {dst_dir:?}");
-        let toml = toml::to_string(&self).expect("Failed to serialize GUI state");
-        log::debug!("GUI state contents: {toml}");
-        std::fs::write(dst_dir, toml)?;
-        Ok(())
+        schema::save_versioned(self, &dst_dir)
     }
 
     /// Load the [GuiState] from the given directory containing a TOML file named [GUI_STATE] or create a default [GuiState] if there's no matching file in the directory.
     ///
+    /// A state written by an older version is migrated to the current schema. A state that cannot be parsed is backed
+    /// up next to the original and replaced by the default [GuiState].
+    ///
     /// # Arguments
     ///
     /// * `src_dir` - The directory to load the [GuiState] from.
     ///
     /// # Errors
     ///
-    /// Returns an [io::Error] if the [GuiState] fails to be loaded.
+    /// Returns an [io::Error] if the [GuiState] file cannot be read or backed up, or was written by a newer version.
     pub fn load(mut src_dir: PathBuf) -> io::Result<Self> {
         src_dir.push(GUI_STATE);
-        let gui_state = if src_dir.try_exists()? {
-            let toml = std::fs::read_to_string(src_dir)?;
-            match toml::from_str(&toml) {
-                Ok(gui_state) => gui_state,
-                Err(e) => {
-                    log::warn!("Failed to deserialize GUI state: {e}");
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!("Failed to deserialize GUI state: {e}"),
-                    ));
-                }
-            }
-        } else {
-            log::info!("No GUI state found, creating default GUI state");
-            Self::new()
-        };
-        Ok(gui_state)
+        schema::load_versioned_or_reset(&src_dir)
     }
 
     /// Save the current [`DateTime<Utc>`] as the last time a tradelist was added.
@@ -97,6 +98,35 @@ mod tests {
         assert_eq!(gui_state, gui_state_loaded);
     }
 
+    #[test]
+    fn test_gui_state_migrate_unversioned() {
+        let tmpdir = TempDir::new().unwrap();
+        std::fs::write(
+            tmpdir.child(GUI_STATE),
+            "tradelist_added_date = \"1970-01-01T00:00:00Z\"\n",
+        )
+        .unwrap();
+
+        let gui_state = GuiState::load(tmpdir.path().to_path_buf()).unwrap();
+
+        assert_eq!(gui_state.schema_version, GuiState::SCHEMA_VERSION);
+        assert_eq!(
+            gui_state.tradelist_added_date,
+            Some(DateTime::<Utc>::UNIX_EPOCH)
+        );
+    }
+
+    #[test]
+    fn test_gui_state_unreadable_is_reset() {
+        let tmpdir = TempDir::new().unwrap();
+        std::fs::write(tmpdir.child(GUI_STATE), "tradelist_added_date = [").unwrap();
+
+        let gui_state = GuiState::load(tmpdir.path().to_path_buf()).unwrap();
+
+        assert_eq!(gui_state, GuiState::default());
+        assert!(!tmpdir.child(GUI_STATE).exists());
+    }
+
     #[test]
     fn test_gui_state_tradelist_data() {
         let mut gui_state = GuiState::new();
@@ -4,11 +4,12 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use mtgoupdater::new_update_all::new_update_all;
+use mtgogetter::card_data::CardDataPaths;
+use mtgoupdater::new_update_all::{new_update_all_with_progress, FetchProgress};
 
 use crate::util::{self, first_file_match_from_dir};
 
-use super::{paths::CardDataPaths, APP_DATA_DIR};
+use super::APP_DATA_DIR;
 
 /// [AppData] contains the paths to the appdata directory and the card data files
 #[derive(Debug)]
@@ -18,15 +19,19 @@ pub struct AppData {
 }
 
 impl AppData {
-    /// Instantiate [AppData] from the path to the appdata directory
+    /// Instantiate [AppData] from the path to the appdata directory, updating the card data first
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - Receives the phase changes and download progress of the update
     ///
     /// # Errors
     ///
     /// Fails if not all the expected files can be located or the MTGO Getter fails to update the data
-    pub fn update() -> Result<Self, Error> {
+    pub fn update(progress: &(dyn Fn(FetchProgress) + Sync)) -> Result<Self, Error> {
         let appdata_dir = super::util::appdata_path()?;
 
-        new_update_all(appdata_dir.as_os_str())?;
+        new_update_all_with_progress(appdata_dir.as_os_str(), progress)?;
 
         let card_data_paths = CardDataPaths::find(&appdata_dir)?;
 
@@ -2,10 +2,19 @@ use std::{
     ffi::OsStr,
     io::Error,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
     thread,
 };
 
 use fltk::{app::Sender, enums::Color};
+use mtgoupdater::{
+    new_update_all::{FetchProgress, ProgressTracker},
+    price_report::{PriceReport, ReportThresholds},
+    Collection, MtgoCard,
+};
 
 use crate::{
     appdata::{update::AppData, APP_DATA_DIR},
@@ -20,6 +29,8 @@ use crate::{
 #[derive(Debug)]
 pub struct TradelistProcessor {
     event_sender: Sender<Message>,
+    /// Set while a trade list is being processed
+    busy: Arc<AtomicBool>,
 }
 
 impl TradelistProcessor {
@@ -27,23 +38,51 @@ impl TradelistProcessor {
     pub fn new(ev_send: Sender<Message>) -> Self {
         Self {
             event_sender: ev_send,
+            busy: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns true if a trade list is being processed
+    pub fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::Acquire)
+    }
+
+    /// Process the tradelist at the given path again to pick up card data that was refreshed, unless a trade list is
+    /// already being processed, which refreshes the card data as well
+    ///
+    /// # Arguments
+    ///
+    /// * `full_trade_list_path` - [Path] to the full trade list
+    pub fn refresh(&mut self, full_trade_list_path: Box<Path>) {
+        if self.is_busy() {
+            log::info!("Trade list is already being processed - skipping refresh");
+            return;
         }
+        self.process(full_trade_list_path);
     }
 
     /// Process the tradelist at the given path
     ///
+    /// Nothing is processed if the file isn't a valid full trade list, e.g. if it was changed since it was selected.
+    ///
     /// # Arguments
     ///
     /// * `full_trade_list_path` - [Path] to the full trade list
     pub fn process(&mut self, full_trade_list_path: Box<Path>) {
-        // TODO: Some basic verification that we actually got a trade list and not some random non-sense.
+        if let Err(e) = mtgoupdater::validate_full_trade_list(&full_trade_list_path) {
+            log::error!("Not processing {full_trade_list_path:?}, it's not a full trade list: {e}");
+            return;
+        }
         let trade_list_processor_thread =
             std::thread::Builder::new().name("Trade List Processor".to_string());
         log::info!("Spawning Trade List Processor thread");
+        self.busy.store(true, Ordering::Release);
         let _handle = trade_list_processor_thread
             .spawn({
                 let sender = self.event_sender.clone();
+                let busy = BusyGuard(Arc::clone(&self.busy));
                 move || {
+                    let _busy = busy;
                     sender.send(Message::MenuBar(MenubarMessage::ProgressBar(
                         ProgressUpdate {
                             show: true,
@@ -66,13 +105,15 @@ impl TradelistProcessor {
                     // Give the full trade list to the parser
                     // Find all the most recent files in the appdata directory, download and update them if necessary
                     log::debug!("Instantiating appdata directory");
-                    let appdata_paths = match AppData::update() {
-                        Ok(paths) => paths,
-                        Err(err) => {
-                            log::error!("{err}");
-                            return;
-                        }
-                    };
+                    let appdata_paths =
+                        match AppData::update(&update_progress_reporter(sender.clone())) {
+                            Ok(paths) => paths,
+                            Err(err) => {
+                                log::error!("{err}");
+                                hide_progress_bar(&sender);
+                                return;
+                            }
+                        };
 
                     sender.send(Message::MenuBar(MenubarMessage::ProgressBar(
                         ProgressUpdate {
@@ -104,6 +145,9 @@ impl TradelistProcessor {
                         },
                     )));
 
+                    // The most recent snapshot is the previous one until the parser saves the new one
+                    let previous_cards = previous_collection_snapshot(&appdata_paths.appdata_dir);
+
                     match mtgoupdater::parse_full(
                         full_trade_list_path.as_ref(),
                         appdata_paths.card_data.scryfall_path(),
@@ -125,13 +169,19 @@ impl TradelistProcessor {
                             )));
 
                             fadeout_progress_bar(sender.clone());
-                            sender.send(Message::SetCollectionStats(CollectionStats::from_cards(
+                            let price_report = PriceReport::new(
                                 &cards,
-                            )));
+                                previous_cards.as_deref(),
+                                ReportThresholds::default(),
+                            );
+                            let mut stats = CollectionStats::from_cards(&cards);
+                            stats.calc_price_report(&price_report);
+                            sender.send(Message::SetCollectionStats(stats));
                             sender.send(Message::SetCards(cards));
                         }
                         Err(e) => {
                             log::info!("MTGO Parser error: {e}");
+                            hide_progress_bar(&sender);
                         }
                     }
                 }
@@ -140,6 +190,104 @@ impl TradelistProcessor {
     }
 }
 
+/// Clears the busy flag of the [TradelistProcessor] when the processing thread is done, however it ends
+struct BusyGuard(Arc<AtomicBool>);
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// The progress bar range covered by updating the card data
+const UPDATE_PROGRESS_START: f64 = 10.;
+const UPDATE_PROGRESS_END: f64 = 70.;
+
+/// Create a progress observer for [AppData::update] that maps the [FetchProgress] onto progress bar
+/// updates between [UPDATE_PROGRESS_START] and [UPDATE_PROGRESS_END]
+///
+/// The downloads run concurrently and report progress for every chunk, so the overall progress is tracked with a
+/// [ProgressTracker] and only changes of at least half a percent or of the label are sent.
+fn update_progress_reporter(sender: Sender<Message>) -> impl Fn(FetchProgress) + Sync {
+    let state = Mutex::new((ProgressTracker::default(), f64::NAN, Box::<str>::default()));
+    move |fetch_progress| {
+        let label: Box<str> = match fetch_progress {
+            FetchProgress::Phase(phase) | FetchProgress::Finished(phase) => {
+                format!("Updating {}...", phase.description()).into()
+            }
+            FetchProgress::Bytes {
+                phase,
+                downloaded,
+                total: Some(total),
+            } => format!(
+                "Downloading {} ({} / {} MB)",
+                phase.description(),
+                downloaded / 1_000_000,
+                total / 1_000_000
+            )
+            .into(),
+            FetchProgress::Bytes {
+                phase, downloaded, ..
+            } => format!(
+                "Downloading {} ({} MB)",
+                phase.description(),
+                downloaded / 1_000_000
+            )
+            .into(),
+        };
+
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (tracker, last_progress, last_label) = &mut *state;
+        let progress = UPDATE_PROGRESS_START
+            + f64::from(tracker.update(fetch_progress))
+                * (UPDATE_PROGRESS_END - UPDATE_PROGRESS_START);
+        if (progress - *last_progress).abs() < 0.5 && label == *last_label {
+            return;
+        }
+        sender.send(Message::MenuBar(MenubarMessage::ProgressBar(
+            ProgressUpdate {
+                show: true,
+                progress,
+                label: label.clone(),
+                ..Default::default()
+            },
+        )));
+        *last_progress = progress;
+        *last_label = label;
+    }
+}
+
+/// Load the cards from the most recent collection snapshot in the appdata directory
+///
+/// Returns [None] if there's no snapshot or it cannot be loaded.
+fn previous_collection_snapshot(appdata_dir: &Path) -> Option<Vec<MtgoCard>> {
+    let snapshot = match mtgoupdater::most_recent_collection_snapshot(appdata_dir) {
+        Ok(Some(snapshot)) => snapshot,
+        Ok(None) => return None,
+        Err(e) => {
+            log::warn!("Failed to locate previous collection snapshot: {e}");
+            return None;
+        }
+    };
+    match Collection::from_json_file(&snapshot) {
+        Ok(mut collection) => Some(collection.take_cards()),
+        Err(e) => {
+            log::warn!("Failed to load previous collection snapshot {snapshot:?}: {e}");
+            None
+        }
+    }
+}
+
+/// Hide the progress bar, e.g. when the processing failed part way
+fn hide_progress_bar(ev_sender: &Sender<Message>) {
+    ev_sender.send(Message::MenuBar(MenubarMessage::ProgressBar(
+        ProgressUpdate {
+            show: false,
+            ..Default::default()
+        },
+    )));
+}
+
 /// Spawn a thread to set the progress bar to 100% and then fade it out.
 ///
 /// # Arguments
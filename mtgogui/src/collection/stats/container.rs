@@ -1,4 +1,4 @@
-use mtgoupdater::{MtgoCard, Rarity};
+use mtgoupdater::{price_report::PriceReport, MtgoCard, Rarity};
 
 use super::util::{MultiValueStat, UniqueTotal};
 
@@ -11,6 +11,8 @@ pub struct CollectionStats {
     cards_under_a_tenth_tix: UniqueTotal,
     cards_over_5_tix: UniqueTotal,
     rarity_distribution: Option<MultiValueStat>,
+    largest_price_spreads: Option<MultiValueStat>,
+    suspicious_prices: Option<usize>,
 }
 
 impl CollectionStats {
@@ -23,6 +25,8 @@ impl CollectionStats {
             cards_under_a_tenth_tix: UniqueTotal::default(),
             cards_over_5_tix: UniqueTotal::default(),
             rarity_distribution: None,
+            largest_price_spreads: None,
+            suspicious_prices: None,
         }
     }
 
@@ -196,6 +200,37 @@ impl CollectionStats {
         )
     }
 
+    /// Add the largest price spreads and the count of suspicious prices from a [PriceReport]
+    ///
+    /// # Arguments
+    ///
+    /// * `report` - The [PriceReport] for the same cards the stats were created from
+    pub fn calc_price_report(&mut self, report: &PriceReport) {
+        const SHOW_SPREADS: usize = 3;
+        let spreads: Vec<String> = report
+            .spreads()
+            .iter()
+            .take(SHOW_SPREADS)
+            .map(|s| {
+                format!(
+                    "@.{name}: {abs:.2} tix ({rel:.0}%) @{source}",
+                    name = s.name,
+                    abs = s.abs_spread,
+                    rel = s.rel_spread * 100.,
+                    source = s.most_expensive_source()
+                )
+            })
+            .collect();
+
+        if !spreads.is_empty() {
+            self.largest_price_spreads = Some(MultiValueStat::new(
+                "Largest price spreads".to_string(),
+                spreads,
+            ));
+        }
+        self.suspicious_prices = Some(report.flagged().len());
+    }
+
     pub fn set_total_cards(&mut self, total_unique_cards: usize, total_card_quantity: usize) {
         self.total_cards = UniqueTotal::new(total_unique_cards, total_card_quantity);
     }
@@ -241,6 +276,14 @@ impl CollectionStats {
     pub fn take_most_expensive_item(&mut self) -> Option<MultiValueStat> {
         self.most_expensive_item.take()
     }
+
+    pub fn take_largest_price_spreads(&mut self) -> Option<MultiValueStat> {
+        self.largest_price_spreads.take()
+    }
+
+    pub fn suspicious_prices(&self) -> Option<usize> {
+        self.suspicious_prices
+    }
 }
 
 impl Default for CollectionStats {
@@ -1,6 +1,6 @@
 use std::vec::Drain;
 
-use mtgogetter::fetch_log::CardInfoMetaData;
+use mtgogetter::{fetch_log::CardInfoMetaData, provenance::ProvenanceManifest};
 
 use super::{
     container::CollectionStats,
@@ -185,6 +185,13 @@ impl TryFrom<CollectionStats> for BrowserItems {
         } else {
             return Err("No rarity distribution stat set".into());
         }
+        // Price report stats are optional as they are only available when prices could be compared
+        if let Some(price_spreads_stat_val) = stats.take_largest_price_spreads() {
+            browser_items.add_multi_value_item(price_spreads_stat_val);
+        }
+        if let Some(suspicious_prices) = stats.suspicious_prices() {
+            browser_items.add_item("Suspicious prices", &suspicious_prices.to_string());
+        }
         Ok(browser_items)
     }
 }
@@ -219,3 +226,29 @@ impl TryFrom<CardInfoMetaData> for BrowserItems {
         Ok(items)
     }
 }
+
+impl From<&ProvenanceManifest> for BrowserItems {
+    fn from(manifest: &ProvenanceManifest) -> Self {
+        log::info!("Converting provenance to browser items");
+        let mut items = BrowserItems::new();
+        for (file, provenance) in manifest.iter() {
+            let http_status = provenance
+                .http_status
+                .map_or_else(|| "N/A".to_string(), |status| status.to_string());
+            let download = CategoryStat::new(
+                file.into(),
+                vec![
+                    ("Source".into(), provenance.source_url.clone()),
+                    ("HTTP status".into(), http_status),
+                    ("Fetched".into(), provenance.fetched_at.to_string()),
+                    ("Size".into(), format!("{} bytes", provenance.bytes)),
+                    ("Records".into(), provenance.records.to_string()),
+                    ("SHA-256".into(), provenance.sha256.clone()),
+                    ("Duration".into(), format!("{} ms", provenance.duration_ms)),
+                ],
+            );
+            items.add_category_item(download);
+        }
+        items
+    }
+}
@@ -18,7 +18,7 @@ use table::column::Column::*;
 
 pub mod table;
 
-const TABLE_WIDTH: i32 = 790;
+const TABLE_WIDTH: i32 = 930;
 
 /// Set the layout for the main collection table to the main window
 ///
@@ -74,7 +74,8 @@ impl CollectionTable {
     pub const COL_FOIL: CollectionColumn = CollectionColumn::new(2, "FOIL", 60);
     pub const COL_GOATBOTS: CollectionColumn = CollectionColumn::new(3, "GOATBOTS", 120);
     pub const COL_CARDHOARDER: CollectionColumn = CollectionColumn::new(4, "CARDHOARDER", 120);
-    pub const COL_SET: CollectionColumn = CollectionColumn::new(5, "SET", 60);
+    /// Wide enough for the set names, e.g. "Limited Edition Alpha (1E)"
+    pub const COL_SET: CollectionColumn = CollectionColumn::new(5, "SET", 200);
     pub const COL_RARITY: CollectionColumn = CollectionColumn::new(6, "RARITY", 100);
 
     /// Create a new [CollectionTable] with the given width, height, and event sender
@@ -6,7 +6,7 @@ use fltk::{
     prelude::{TableExt, WidgetBase},
 };
 use fltk_table::SmartTable;
-use mtgoupdater::MtgoCard;
+use mtgoupdater::{set_catalog::cmp_release_order, MtgoCard};
 
 use crate::{
     collection::view::table::{SortStates, SortedBy},
@@ -106,10 +106,10 @@ pub fn sort_cards(cards: &mut [MtgoCard], sort_states: &mut SortStates, category
         }
         Column::Set => {
             if sort_states.set_ord().is_descending() {
-                cards.sort_by(|a, b| b.set.cmp(&a.set));
+                cards.sort_by(|a, b| cmp_release_order(b, a));
                 sort_states.set_set_ord(SortedBy::Set(Ordering::Ascending));
             } else {
-                cards.sort_by(|a, b| a.set.cmp(&b.set));
+                cards.sort_by(|a, b| cmp_release_order(a, b));
                 sort_states.set_set_ord(SortedBy::Set(Ordering::Descending));
             }
         }
@@ -244,6 +244,14 @@ pub fn draw_cards(table: &mut SmartTable, cards: &[MtgoCard]) {
     }
 }
 
+/// The name of the set of the card followed by its code, or only the code if the set wasn't found in the set catalog
+fn set_label(card: &MtgoCard) -> String {
+    match &card.set_name {
+        Some(name) => format!("{name} ({})", card.set),
+        None => card.set.to_string(),
+    }
+}
+
 /// Helper to fill a single row with [MtgoCard] data
 ///
 /// # Arguments
@@ -263,6 +271,6 @@ pub fn fill_card_row(table: &mut SmartTable, row_idx: i32, card: &MtgoCard) {
             "N/A".into()
         }
     });
-    CollectionTable::COL_SET.fill(table, row_idx, &card.set);
+    CollectionTable::COL_SET.fill(table, row_idx, &set_label(card));
     CollectionTable::COL_RARITY.fill(table, row_idx, &card.rarity.to_string());
 }
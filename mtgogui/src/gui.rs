@@ -2,6 +2,7 @@ use std::ffi::OsStr;
 use std::path::PathBuf;
 use std::sync::OnceLock;
 
+use crate::appdata::scheduler::{spawn_refresh_scheduler, REFRESH_CHECK_INTERVAL};
 use crate::appdata::state::GuiState;
 use crate::assets::{self, get_asc_svg, get_icon_search, get_logo};
 use crate::collection::processor::TradelistProcessor;
@@ -29,7 +30,7 @@ use fltk_flex::{Flex, FlexType};
 use fltk_grid::Grid;
 use fltk_table::{SmartTable, TableOpts};
 use fltk_theme::{widget_themes, ThemeType, WidgetTheme};
-use mtgogetter::fetch_log::CardInfoMetaData;
+use mtgogetter::{fetch_log::CardInfoMetaData, provenance::ProvenanceManifest};
 
 use self::setup::setup_main_window;
 
@@ -39,6 +40,8 @@ mod setup;
 pub struct MtgoGui {
     app: app::App,
     state: GuiState,
+    /// Cleared if the state couldn't be loaded, e.g. because it was written by a newer version, so it isn't replaced
+    save_state: bool,
     rcv: app::Receiver<Message>,
     main_win: window::Window,
     menu: McmMenuBar,
@@ -84,6 +87,7 @@ impl MtgoGui {
         main_win.show();
 
         let tradelist_processor = TradelistProcessor::new(ev_send.clone());
+        spawn_refresh_scheduler(ev_send.clone(), REFRESH_CHECK_INTERVAL);
 
         main_win.set_callback(move |_| {
             if app::event() == Event::Close {
@@ -93,6 +97,7 @@ impl MtgoGui {
         Self {
             app,
             state: GuiState::default(), // Placeholder, is overwritten at startup
+            save_state: true,
             rcv: ev_rcv,
             main_win,
             menu,
@@ -115,7 +120,8 @@ impl MtgoGui {
         self.state = match GuiState::load(appdata_dir.clone()) {
             Ok(state) => state,
             Err(e) => {
-                log::warn!("Failed to load GUI state: {e}");
+                log::warn!("Failed to load GUI state: {e}. It won't be saved on exit");
+                self.save_state = false;
                 GuiState::default()
             }
         };
@@ -160,10 +166,12 @@ impl MtgoGui {
                 match msg {
                     Message::Quit => {
                         log::info!("Quit");
-                        if let Err(e) = self.state.save(
-                            appdata::util::appdata_path().expect("Failed to get appdata path"),
-                        ) {
-                            log::error!("Failed to save GUI state: {e}");
+                        if self.save_state {
+                            if let Err(e) = self.state.save(
+                                appdata::util::appdata_path().expect("Failed to get appdata path"),
+                            ) {
+                                log::error!("Failed to save GUI state: {e}");
+                            }
                         }
                         self.app.quit();
                     }
@@ -174,6 +182,20 @@ impl MtgoGui {
                         self.app.redraw();
                     }
                     Message::GotFullTradeList(full_trade_list_path) => {
+                        match mtgoupdater::validate_full_trade_list(&full_trade_list_path) {
+                            Ok(summary) => log::info!(
+                                "Full trade list validated: {} unique cards, {} total",
+                                summary.unique_cards,
+                                summary.total_quantity
+                            ),
+                            Err(e) => {
+                                log::warn!("Rejected {full_trade_list_path:?}: {e}");
+                                dialog::alert_default(&format!(
+                                    "The file is not an MTGO full trade list:\n{e}"
+                                ));
+                                continue;
+                            }
+                        }
                         // TODO: Error pop-up dialog if fails.
                         // Should implement a generic error dialog that can be used for all unexpected errors that cannot be handled programmatically.
                         appdata::util::copy_tradelist_to_appdata(full_trade_list_path.as_os_str())
@@ -182,6 +204,14 @@ impl MtgoGui {
 
                         self.tradelist_processor.process(full_trade_list_path);
                     }
+                    Message::RefreshCardData => match appdata::util::current_tradelist_path() {
+                        Ok(Some(current_trade_list)) => {
+                            log::info!("Refreshing card data of the current trade list");
+                            self.tradelist_processor.refresh(current_trade_list.into());
+                        }
+                        Ok(None) => log::debug!("No current trade list to refresh"),
+                        Err(e) => log::error!("Failed to get current tradelist path: {e}"),
+                    },
                     Message::SetCards(cards) => self.collection.set_cards(cards),
                     Message::SetCollectionStats(stats) => {
                         match stats.try_into() {
@@ -251,6 +281,12 @@ impl MtgoGui {
             }
         };
 
+        // Where the stored data was downloaded from, to trace e.g. unexpected prices back to a download
+        match ProvenanceManifest::load(&appdata_dir) {
+            Ok(manifest) => metadata_browser_items.append(&mut BrowserItems::from(&manifest)),
+            Err(e) => log::warn!("Failed to load provenance manifest: {e}"),
+        }
+
         self.metadata.set_items(metadata_browser_items);
         Ok(())
     }
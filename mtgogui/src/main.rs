@@ -55,6 +55,8 @@ pub enum Message {
     MenuBar(menubar::MenubarMessage),
     Table(collection::TableMessage),
     GotFullTradeList(Box<Path>),
+    /// Sent by the refresh scheduler when card data is stale, re-enriches the current trade list
+    RefreshCardData,
     SetCards(Vec<MtgoCard>),
     SetCollectionStats(CollectionStats),
 }
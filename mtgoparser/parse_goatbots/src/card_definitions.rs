@@ -1,5 +1,5 @@
-use serde::{Deserialize, Serialize};
-use std::{collections::hash_map::HashMap, path::Path};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::hash_map::HashMap, fmt::Display, path::Path};
 
 /// The relevant card information that can be extracted from a GoatBots JSON file.
 /// e.g.
@@ -18,12 +18,17 @@ use std::{collections::hash_map::HashMap, path::Path};
 pub struct GoatBotsCard {
     pub name: String,
     pub cardset: String,
-    pub rarity: String,
-    pub foil: u8,
+    pub rarity: Rarity,
+    /// Stored as `0` or `1` by Goatbots
+    #[serde(
+        serialize_with = "serialize_foil",
+        deserialize_with = "deserialize_foil"
+    )]
+    pub foil: bool,
 }
 
 impl GoatBotsCard {
-    pub fn new(name: String, cardset: String, rarity: String, foil: u8) -> Self {
+    pub fn new(name: String, cardset: String, rarity: Rarity, foil: bool) -> Self {
         Self {
             name,
             cardset,
@@ -33,11 +38,77 @@ impl GoatBotsCard {
     }
 }
 
+fn serialize_foil<S: Serializer>(foil: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u8(u8::from(*foil))
+}
+
+fn deserialize_foil<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    match u8::deserialize(deserializer)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        other => Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Unsigned(other.into()),
+            &"0 or 1",
+        )),
+    }
+}
+
+/// Represents the rarity of an MTGO item (e.g. card, booster, event ticket)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, PartialOrd, Ord, Eq)]
+pub enum Rarity {
+    #[default]
+    #[serde(alias = "C")]
+    Common,
+    #[serde(alias = "U")]
+    Uncommon,
+    #[serde(alias = "R")]
+    Rare,
+    #[serde(alias = "M")]
+    Mythic,
+    #[serde(alias = "B")]
+    Booster,
+    #[serde(other)]
+    None,
+}
+
+impl Display for Rarity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rarity::Common => f.write_str("Common"),
+            Rarity::Uncommon => f.write_str("Uncommon"),
+            Rarity::Rare => f.write_str("Rare"),
+            Rarity::Mythic => f.write_str("Mythic"),
+            Rarity::Booster => f.write_str("Booster"),
+            Rarity::None => f.write_str("None"),
+        }
+    }
+}
+
+impl From<&str> for Rarity {
+    fn from(s: &str) -> Self {
+        match s {
+            // Single letter matches first for speed
+            "C" => Rarity::Common,
+            "U" => Rarity::Uncommon,
+            "R" => Rarity::Rare,
+            "M" => Rarity::Mythic,
+            "B" => Rarity::Booster,
+            "Uncommon" => Rarity::Uncommon,
+            "Rare" => Rarity::Rare,
+            "Mythic" => Rarity::Mythic,
+            "Booster" => Rarity::Booster,
+            "Common" => Rarity::Common,
+            _ => Rarity::None, // e.g. Event tickets
+        }
+    }
+}
+
+/// Parse the Goatbots card definitions keyed by MTGO ID
 pub fn parse_card_def_json(
     path: &Path,
-) -> Result<HashMap<String, GoatBotsCard>, Box<dyn std::error::Error>> {
-    let json = std::fs::read_to_string(path)?;
-    let cards: HashMap<String, GoatBotsCard> = serde_json::from_str(&json)?;
+) -> Result<HashMap<u32, GoatBotsCard>, Box<dyn std::error::Error>> {
+    let json = std::fs::read(path)?;
+    let cards: HashMap<u32, GoatBotsCard> = serde_json::from_slice(&json)?;
     Ok(cards)
 }
 
@@ -52,13 +123,13 @@ mod tests {
         let card = GoatBotsCard::new(
             "Island".to_string(),
             "M20".to_string(),
-            "Common".to_string(),
-            0,
+            Rarity::Common,
+            false,
         );
         assert_eq!(card.name, "Island");
         assert_eq!(card.cardset, "M20");
-        assert_eq!(card.rarity, "Common");
-        assert_eq!(card.foil, 0);
+        assert_eq!(card.rarity, Rarity::Common);
+        assert!(!card.foil);
         Ok(())
     }
 
@@ -68,26 +139,42 @@ mod tests {
         let cards = parse_card_def_json(path)?;
         assert_eq!(cards.len(), 5);
         assert_eq!(
-            cards.get("47483").unwrap(),
+            cards.get(&47483).unwrap(),
             &GoatBotsCard {
                 name: "Gruul Charm".to_string(),
                 cardset: "GTC".to_string(),
-                rarity: "Uncommon".to_string(),
-                foil: 0
+                rarity: Rarity::Uncommon,
+                foil: false
             }
         );
         assert_eq!(
-            cards.get("348").unwrap(),
+            cards.get(&348).unwrap(),
             &GoatBotsCard {
                 name: "Black Lotus".to_string(),
                 cardset: "1E".to_string(),
-                rarity: "Rare".to_string(),
-                foil: 1
+                rarity: Rarity::Rare,
+                foil: true
             }
         );
         Ok(())
     }
 
+    #[test]
+    fn test_foil_flag() -> TestResult {
+        let json = r#"{"name": "Black Lotus", "cardset": "1E", "rarity": "Rare", "foil": 1}"#;
+        let card: GoatBotsCard = serde_json::from_str(json)?;
+        assert!(card.foil);
+        assert_eq!(
+            serde_json::to_string(&card)?,
+            json.replace(": ", ":").replace(", ", ",")
+        );
+        assert!(
+            serde_json::from_str::<GoatBotsCard>(&json.replace("\"foil\": 1", "\"foil\": 2"))
+                .is_err()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_parse_goatbots_json_full() -> TestResult {
         let path =
@@ -95,21 +182,21 @@ mod tests {
         let cards = parse_card_def_json(path)?;
         assert_eq!(cards.len(), 76070);
         assert_eq!(
-            cards.get("47483").unwrap(),
+            cards.get(&47483).unwrap(),
             &GoatBotsCard {
                 name: "Gruul Charm".to_string(),
                 cardset: "GTC".to_string(),
-                rarity: "Uncommon".to_string(),
-                foil: 0
+                rarity: Rarity::Uncommon,
+                foil: false
             }
         );
         assert_eq!(
-            cards.get("348").unwrap(),
+            cards.get(&348).unwrap(),
             &GoatBotsCard {
                 name: "Black Lotus".to_string(),
                 cardset: "1E".to_string(),
-                rarity: "Rare".to_string(),
-                foil: 1
+                rarity: Rarity::Rare,
+                foil: true
             }
         );
         Ok(())
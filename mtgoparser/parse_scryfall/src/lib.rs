@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, VecSkipError};
+
+pub mod mtgo_cards;
+pub mod sorted_cards;
+
+pub use mtgo_cards::{ScryfallMtgoCards, SkipReason, SkippedObjects};
+pub use sorted_cards::{write_sorted_json_lines, SortedCards};
 
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct Prices {
@@ -19,17 +24,6 @@ pub struct ScryfallCard {
     pub prices: Prices,
 }
 
-/// Helper struct for skipping [ScryfallCard] objects that fail serialization
-///
-/// Some objects fail serialization due to not having an `mtgo_id` field.
-/// However, we are only interested in cards that have an `mtgo_id` thus we ignore these errors.
-///
-/// In this case we ignore all errors which could come back to bite us. In which case we need to implement a custom deserializer.
-/// This is a good solution for now.
-#[serde_as]
-#[derive(Deserialize, Serialize)]
-pub struct ScryfallMtgoCards(#[serde_as(as = "VecSkipError<_>")] pub Vec<ScryfallCard>);
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,8 +83,12 @@ mod tests {
             "../../test/test-data/scryfall/default-cards-small-87objs-50cards.json",
         )?;
 
-        let ScryfallMtgoCards(mut scryfall_cards) =
-            serde_json::from_str::<ScryfallMtgoCards>(&scryfall_json_str).unwrap();
+        let ScryfallMtgoCards {
+            cards: mut scryfall_cards,
+            skipped,
+        } = serde_json::from_str::<ScryfallMtgoCards>(&scryfall_json_str).unwrap();
+        assert_eq!(scryfall_cards.len(), 50);
+        assert_eq!(skipped, SkippedObjects::from([(SkipReason::NotOnMtgo, 36)]));
 
         assert_eq!(
             scryfall_cards[0],
@@ -0,0 +1,264 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{
+    de::{DeserializeOwned, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::Value;
+
+use crate::ScryfallCard;
+
+/// Why an object of the bulk data was not parsed into a [ScryfallCard]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SkipReason {
+    /// The object has neither an `mtgo_id` nor an `mtgo_foil_id`, the card isn't on MTGO
+    NotOnMtgo,
+    /// The object only has an `mtgo_foil_id`, cards are keyed by their non-foil ID
+    FoilOnly,
+    /// The object has an `mtgo_id` but the named field is missing or doesn't have the expected type
+    InvalidField(&'static str),
+    /// The element of the array isn't an object
+    NotAnObject,
+}
+
+impl SkipReason {
+    /// Whether objects are expected to be skipped for this reason, the other reasons hint at a format change
+    pub fn is_expected(self) -> bool {
+        matches!(self, Self::NotOnMtgo | Self::FoilOnly)
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotOnMtgo => f.write_str("not on MTGO"),
+            Self::FoilOnly => f.write_str("only has an mtgo_foil_id"),
+            Self::InvalidField(field) => write!(f, "missing or invalid {field}"),
+            Self::NotAnObject => f.write_str("not an object"),
+        }
+    }
+}
+
+/// The number of skipped objects by [SkipReason]
+pub type SkippedObjects = BTreeMap<SkipReason, usize>;
+
+/// The MTGO cards of a Scryfall bulk data array
+///
+/// The array is deserialized element by element and an object is only materialized into a [ScryfallCard] if it has an
+/// `mtgo_id`. All other fields are skipped without being buffered, so deserializing from a reader keeps the memory
+/// usage proportional to the number of MTGO cards rather than the size of the bulk data.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScryfallMtgoCards {
+    pub cards: Vec<ScryfallCard>,
+    pub skipped: SkippedObjects,
+}
+
+impl ScryfallMtgoCards {
+    /// The total number of skipped objects
+    pub fn skipped_count(&self) -> usize {
+        self.skipped.values().sum()
+    }
+}
+
+impl<'de> Deserialize<'de> for ScryfallMtgoCards {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CardsVisitor;
+
+        impl<'de> Visitor<'de> for CardsVisitor {
+            type Value = ScryfallMtgoCards;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an array of Scryfall card objects")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut mtgo_cards = ScryfallMtgoCards::default();
+                while let Some(BulkObject(object)) = seq.next_element()? {
+                    match object {
+                        Ok(card) => mtgo_cards.cards.push(card),
+                        Err(reason) => *mtgo_cards.skipped.entry(reason).or_default() += 1,
+                    }
+                }
+                Ok(mtgo_cards)
+            }
+        }
+
+        deserializer.deserialize_seq(CardsVisitor)
+    }
+}
+
+/// An element of the bulk data array
+struct BulkObject(Result<ScryfallCard, SkipReason>);
+
+/// The fields of a bulk data object that are kept, the other fields are skipped
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum Field {
+    MtgoId,
+    MtgoFoilId,
+    Name,
+    ReleasedAt,
+    Rarity,
+    Prices,
+    #[serde(other)]
+    Other,
+}
+
+/// The values of the kept fields, converted to a [ScryfallCard] once the whole object is read
+#[derive(Default)]
+struct CardFields {
+    mtgo_id: Option<Value>,
+    mtgo_foil_id: Option<Value>,
+    name: Option<Value>,
+    released_at: Option<Value>,
+    rarity: Option<Value>,
+    prices: Option<Value>,
+}
+
+impl CardFields {
+    fn into_card(self) -> Result<ScryfallCard, SkipReason> {
+        let present = |v: &Option<Value>| v.as_ref().is_some_and(|v| !v.is_null());
+        if !present(&self.mtgo_id) {
+            return Err(if present(&self.mtgo_foil_id) {
+                SkipReason::FoilOnly
+            } else {
+                SkipReason::NotOnMtgo
+            });
+        }
+        Ok(ScryfallCard {
+            mtgo_id: field(self.mtgo_id, "mtgo_id")?,
+            name: field(self.name, "name")?,
+            released_at: field(self.released_at, "released_at")?,
+            rarity: field(self.rarity, "rarity")?,
+            prices: field(self.prices, "prices")?,
+        })
+    }
+}
+
+fn field<T: DeserializeOwned>(value: Option<Value>, name: &'static str) -> Result<T, SkipReason> {
+    value
+        .and_then(|v| serde_json::from_value(v).ok())
+        .ok_or(SkipReason::InvalidField(name))
+}
+
+impl<'de> Deserialize<'de> for BulkObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ObjectVisitor;
+
+        impl<'de> Visitor<'de> for ObjectVisitor {
+            type Value = BulkObject;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a Scryfall card object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut fields = CardFields::default();
+                while let Some(key) = map.next_key()? {
+                    let slot = match key {
+                        Field::MtgoId => &mut fields.mtgo_id,
+                        Field::MtgoFoilId => &mut fields.mtgo_foil_id,
+                        Field::Name => &mut fields.name,
+                        Field::ReleasedAt => &mut fields.released_at,
+                        Field::Rarity => &mut fields.rarity,
+                        Field::Prices => &mut fields.prices,
+                        Field::Other => {
+                            map.next_value::<IgnoredAny>()?;
+                            continue;
+                        }
+                    };
+                    *slot = Some(map.next_value()?);
+                }
+                Ok(BulkObject(fields.into_card()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(BulkObject(Err(SkipReason::NotAnObject)))
+            }
+
+            fn visit_bool<E>(self, _: bool) -> Result<Self::Value, E> {
+                Ok(BulkObject(Err(SkipReason::NotAnObject)))
+            }
+
+            fn visit_i64<E>(self, _: i64) -> Result<Self::Value, E> {
+                Ok(BulkObject(Err(SkipReason::NotAnObject)))
+            }
+
+            fn visit_u64<E>(self, _: u64) -> Result<Self::Value, E> {
+                Ok(BulkObject(Err(SkipReason::NotAnObject)))
+            }
+
+            fn visit_f64<E>(self, _: f64) -> Result<Self::Value, E> {
+                Ok(BulkObject(Err(SkipReason::NotAnObject)))
+            }
+
+            fn visit_str<E>(self, _: &str) -> Result<Self::Value, E> {
+                Ok(BulkObject(Err(SkipReason::NotAnObject)))
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                Ok(BulkObject(Err(SkipReason::NotAnObject)))
+            }
+        }
+
+        deserializer.deserialize_any(ObjectVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Prices;
+    use pretty_assertions::assert_eq;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_skip_reasons() -> TestResult {
+        let json = r#"[
+            {"object": "card", "mtgo_id": 235, "mtgo_foil_id": 236, "name": "Swamp", "released_at": "2003-12-31",
+             "rarity": "common", "image_uris": {"small": "https://cards.scryfall.io/small.jpg"},
+             "prices": {"usd": null, "usd_foil": null, "eur": null, "eur_foil": null, "tix": "0.05"}},
+            {"object": "card", "name": "Paper Only", "released_at": "1993-08-05", "rarity": "rare", "prices": {}},
+            {"object": "card", "mtgo_id": null, "mtgo_foil_id": 8, "name": "Foil Only"},
+            {"object": "card", "mtgo_id": 9, "name": ["Not", "a", "string"], "released_at": "2024-01-01"},
+            {"object": "card", "mtgo_id": 10, "name": "No Prices", "released_at": "2024-01-01", "rarity": "common"},
+            42,
+            [{"mtgo_id": 11}]
+        ]"#;
+        let mtgo_cards: ScryfallMtgoCards = serde_json::from_str(json)?;
+
+        assert_eq!(
+            mtgo_cards.cards,
+            [ScryfallCard {
+                mtgo_id: 235,
+                name: "Swamp".into(),
+                released_at: "2003-12-31".into(),
+                rarity: "common".into(),
+                prices: Prices {
+                    tix: Some("0.05".into()),
+                    ..Default::default()
+                }
+            }]
+        );
+        assert_eq!(
+            mtgo_cards.skipped,
+            SkippedObjects::from([
+                (SkipReason::NotOnMtgo, 1),
+                (SkipReason::FoilOnly, 1),
+                (SkipReason::InvalidField("name"), 1),
+                (SkipReason::InvalidField("prices"), 1),
+                (SkipReason::NotAnObject, 2),
+            ])
+        );
+        assert_eq!(mtgo_cards.skipped_count(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncated_array_is_error() {
+        let json = r#"[{"mtgo_id": 235, "name": "Swamp"}, {"mtgo_id": 23"#;
+        assert!(serde_json::from_str::<ScryfallMtgoCards>(json).is_err());
+        assert!(serde_json::from_str::<ScryfallMtgoCards>(r#"{"mtgo_id": 235}"#).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    vec,
+};
+
+use crate::ScryfallCard;
+
+/// Sort the cards by MTGO ID and write them as JSON lines, one card per line
+///
+/// This is the format the Scryfall cards are cached in, it can be read one card at a time with [SortedCards] and
+/// merge joined with a collection sorted by MTGO ID.
+///
+/// # Errors
+///
+/// Returns an error if a card cannot be serialized or the writer fails
+pub fn write_sorted_json_lines(
+    cards: &mut [ScryfallCard],
+    mut writer: impl Write,
+) -> Result<(), io::Error> {
+    cards.sort_by_key(|c| c.mtgo_id);
+    for card in cards.iter() {
+        serde_json::to_writer(&mut writer, card)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Iterator over the Scryfall cards of a cached cards file in ascending order of MTGO ID
+///
+/// Reads the JSON lines written by [write_sorted_json_lines] one line at a time. A file that is a JSON array, as it was
+/// cached before, is read in full and sorted instead.
+pub enum SortedCards<R> {
+    /// The cards of a JSON array, already sorted
+    Array(vec::IntoIter<ScryfallCard>),
+    /// The lines of a JSON lines file and the MTGO ID of the last card, to verify that they are sorted
+    JsonLines {
+        lines: io::Lines<R>,
+        last_id: Option<u32>,
+    },
+}
+
+impl SortedCards<BufReader<fs::File>> {
+    /// Open the cached cards file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened, or it's a JSON array that cannot be parsed
+    pub fn open(path: &Path) -> Result<Self, io::Error> {
+        Self::from_reader(BufReader::new(fs::File::open(path)?))
+    }
+}
+
+impl<R: BufRead> SortedCards<R> {
+    /// Detect the format from the first character and prepare reading the cards
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader fails, or the contents are a JSON array that cannot be parsed
+    pub fn from_reader(mut reader: R) -> Result<Self, io::Error> {
+        let is_array = loop {
+            let buf = reader.fill_buf()?;
+            if buf.is_empty() {
+                break false;
+            }
+            match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(pos) => {
+                    let is_array = buf[pos] == b'[';
+                    reader.consume(pos);
+                    break is_array;
+                }
+                None => {
+                    let len = buf.len();
+                    reader.consume(len);
+                }
+            }
+        };
+        if is_array {
+            let mut cards: Vec<ScryfallCard> = serde_json::from_reader(reader)?;
+            cards.sort_by_key(|c| c.mtgo_id);
+            Ok(Self::Array(cards.into_iter()))
+        } else {
+            Ok(Self::JsonLines {
+                lines: reader.lines(),
+                last_id: None,
+            })
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SortedCards<R> {
+    type Item = Result<ScryfallCard, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Array(cards) => cards.next().map(Ok),
+            Self::JsonLines { lines, last_id } => loop {
+                let line = match lines.next()? {
+                    Ok(line) => line,
+                    Err(e) => return Some(Err(e)),
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let card: ScryfallCard = match serde_json::from_str(&line) {
+                    Ok(card) => card,
+                    Err(e) => return Some(Err(e.into())),
+                };
+                if last_id.is_some_and(|last| card.mtgo_id < last) {
+                    return Some(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Scryfall cards are not sorted by MTGO ID at {}",
+                            card.mtgo_id
+                        ),
+                    )));
+                }
+                *last_id = Some(card.mtgo_id);
+                return Some(Ok(card));
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use testresult::TestResult;
+
+    fn card(mtgo_id: u32) -> ScryfallCard {
+        ScryfallCard {
+            mtgo_id,
+            name: format!("Card {mtgo_id}"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_json_lines_roundtrip() -> TestResult {
+        let mut cards = vec![card(300), card(7), card(25527)];
+        let mut json_lines = Vec::new();
+        write_sorted_json_lines(&mut cards, &mut json_lines)?;
+        assert_eq!(json_lines.iter().filter(|b| **b == b'\n').count(), 3);
+
+        let read =
+            SortedCards::from_reader(json_lines.as_slice())?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(read, [card(7), card(300), card(25527)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_array_is_sorted() -> TestResult {
+        let json = serde_json::to_vec(&[card(300), card(7)])?;
+        let mut with_whitespace = b"\n  ".to_vec();
+        with_whitespace.extend(json);
+        let read =
+            SortedCards::from_reader(with_whitespace.as_slice())?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(read, [card(7), card(300)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsorted_or_truncated_json_lines_are_errors() -> TestResult {
+        let mut lines = Vec::new();
+        for c in [card(300), card(7)] {
+            serde_json::to_writer(&mut lines, &c)?;
+            lines.push(b'\n');
+        }
+        let res = SortedCards::from_reader(lines.as_slice())?.collect::<Result<Vec<_>, _>>();
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let truncated = &lines[..lines.len() - 10];
+        assert!(SortedCards::from_reader(truncated)?
+            .collect::<Result<Vec<_>, _>>()
+            .is_err());
+        assert_eq!(SortedCards::from_reader(&b""[..])?.count(), 0);
+        Ok(())
+    }
+}
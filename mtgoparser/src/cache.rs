@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{self, BufReader, Read},
+    path::Path,
+    time::SystemTime,
+};
+
+use crate::mtgo_card::MtgoCard;
+
+/// Name of the file the [ParseCache] is stored in
+pub const PARSE_CACHE_FILENAME: &str = "parse-cache.json";
+
+/// Bumped whenever the cache format or the enrichment logic changes, so stale caches are discarded
+const CACHE_VERSION: u32 = 2;
+
+/// The SHA-256 hash of the contents of an input file, along with the size and modification time it was hashed at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHash {
+    pub sha256: String,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileHash {
+    /// Hash the contents of the file at `path`
+    ///
+    /// The hash of `previous` is reused if the file has the same size and modification time, so the large input files
+    /// aren't read when they haven't been replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read
+    pub fn of_file(path: &Path, previous: Option<&Self>) -> Result<Self, io::Error> {
+        let metadata = fs::metadata(path)?;
+        let len = metadata.len();
+        let modified = metadata.modified().ok();
+        if let Some(previous) = previous {
+            if modified.is_some() && previous.modified == modified && previous.len == len {
+                return Ok(previous.clone());
+            }
+        }
+        Ok(Self {
+            sha256: hash_file(path)?,
+            len,
+            modified,
+        })
+    }
+}
+
+/// Content hashes of the four inputs to [parse_full](crate::parse_full)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputHashes {
+    pub full_trade_list: FileHash,
+    pub scryfall: FileHash,
+    pub card_definitions: FileHash,
+    pub price_history: FileHash,
+}
+
+impl InputHashes {
+    /// Hash the contents of each of the input files, see [FileHash::of_file]
+    ///
+    /// # Arguments
+    ///
+    /// * `previous` - The hashes of the [ParseCache], if any, to reuse for the files that haven't changed since
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the files cannot be read
+    pub fn from_paths(
+        full_trade_list_path: &Path,
+        scryfall_path: &Path,
+        card_definitions_path: &Path,
+        price_history_path: &Path,
+        previous: Option<&Self>,
+    ) -> Result<Self, io::Error> {
+        Ok(Self {
+            full_trade_list: FileHash::of_file(
+                full_trade_list_path,
+                previous.map(|p| &p.full_trade_list),
+            )?,
+            scryfall: FileHash::of_file(scryfall_path, previous.map(|p| &p.scryfall))?,
+            card_definitions: FileHash::of_file(
+                card_definitions_path,
+                previous.map(|p| &p.card_definitions),
+            )?,
+            price_history: FileHash::of_file(
+                price_history_path,
+                previous.map(|p| &p.price_history),
+            )?,
+        })
+    }
+}
+
+/// The inputs that changed since the [ParseCache] was saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StaleInputs {
+    pub full_trade_list: bool,
+    pub scryfall: bool,
+    pub card_definitions: bool,
+    pub price_history: bool,
+}
+
+impl StaleInputs {
+    /// Returns true if the cached cards can be used as they are
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Cache of the enriched collection produced by [parse_full](crate::parse_full), keyed by the content hashes of its inputs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseCache {
+    version: u32,
+    hashes: InputHashes,
+    cards: Vec<MtgoCard>,
+}
+
+impl ParseCache {
+    pub fn new(hashes: InputHashes, cards: Vec<MtgoCard>) -> Self {
+        Self {
+            version: CACHE_VERSION,
+            hashes,
+            cards,
+        }
+    }
+
+    /// Load the cache from the given directory
+    ///
+    /// # Returns
+    ///
+    /// [None] if there's no cache, it cannot be deserialized, or it was saved by an incompatible version.
+    pub fn load(dir: &Path) -> Option<Self> {
+        let json = fs::read_to_string(dir.join(PARSE_CACHE_FILENAME)).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(cache) if cache.version == CACHE_VERSION => Some(cache),
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("Discarding unreadable parse cache: {e}");
+                None
+            }
+        }
+    }
+
+    /// Save the cache to the given directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache cannot be serialized or written
+    pub fn save(&self, dir: &Path) -> Result<(), io::Error> {
+        let json = serde_json::to_string(self)?;
+        fs::write(dir.join(PARSE_CACHE_FILENAME), json)
+    }
+
+    /// The hashes of the inputs the cached cards were produced from
+    pub fn hashes(&self) -> &InputHashes {
+        &self.hashes
+    }
+
+    /// Compare the cached input hashes with the current ones
+    pub fn stale_inputs(&self, hashes: &InputHashes) -> StaleInputs {
+        StaleInputs {
+            full_trade_list: self.hashes.full_trade_list.sha256 != hashes.full_trade_list.sha256,
+            scryfall: self.hashes.scryfall.sha256 != hashes.scryfall.sha256,
+            card_definitions: self.hashes.card_definitions.sha256 != hashes.card_definitions.sha256,
+            price_history: self.hashes.price_history.sha256 != hashes.price_history.sha256,
+        }
+    }
+
+    pub fn take_cards(self) -> Vec<MtgoCard> {
+        self.cards
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String, io::Error> {
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buf = [0; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    fn hashes(price_history: &str) -> InputHashes {
+        let hash = |sha256: &str| FileHash {
+            sha256: sha256.into(),
+            len: 0,
+            modified: None,
+        };
+        InputHashes {
+            full_trade_list: hash("1"),
+            scryfall: hash("2"),
+            card_definitions: hash("3"),
+            price_history: hash(price_history),
+        }
+    }
+
+    #[test]
+    fn test_stale_inputs() {
+        let cache = ParseCache::new(hashes("4"), vec![]);
+        assert!(cache.stale_inputs(&hashes("4")).is_empty());
+        assert_eq!(
+            cache.stale_inputs(&hashes("5")),
+            StaleInputs {
+                price_history: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_save_load_roundtrip() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        assert_eq!(ParseCache::load(temp_dir.path()), None);
+
+        let cache = ParseCache::new(
+            hashes("4"),
+            vec![MtgoCard {
+                id: 235,
+                quantity: 3,
+                name: "Swamp".into(),
+                ..Default::default()
+            }],
+        );
+        cache.save(temp_dir.path())?;
+        assert_eq!(ParseCache::load(temp_dir.path()), Some(cache));
+
+        fs::write(temp_dir.child(PARSE_CACHE_FILENAME), "{ not json")?;
+        assert_eq!(ParseCache::load(temp_dir.path()), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_file() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let a = temp_dir.child("a");
+        let b = temp_dir.child("b");
+        fs::write(&a, "some content")?;
+        fs::write(&b, "some content")?;
+        assert_eq!(hash_file(&a)?, hash_file(&b)?);
+        // The hash is stable across builds
+        assert_eq!(
+            hash_file(&a)?,
+            "290f493c44f5d63d06b374d0a5abd292fae38b92cab2fae5efefe1b0e9347f56"
+        );
+        fs::write(&b, "other content")?;
+        assert_ne!(hash_file(&a)?, hash_file(&b)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_hash_reuses_unchanged_hash() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.child("a");
+        fs::write(&path, "some content")?;
+        let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(modified)?;
+        let hash = FileHash::of_file(&path, None)?;
+        assert_eq!(hash.len, 12);
+        assert_eq!(hash.modified, Some(modified));
+
+        // The file isn't read if its size and modification time didn't change
+        let previous = FileHash {
+            sha256: "previous".into(),
+            ..hash.clone()
+        };
+        assert_eq!(FileHash::of_file(&path, Some(&previous))?, previous);
+
+        // Otherwise it's hashed again
+        fs::write(&path, "other content")?;
+        let rehashed = FileHash::of_file(&path, Some(&hash))?;
+        assert_ne!(rehashed.sha256, hash.sha256);
+        assert_eq!(rehashed.len, 13);
+        Ok(())
+    }
+}
@@ -1,11 +1,8 @@
-use crate::{
-    mtgo_card::{MtgoCard, Rarity},
-    xml::XmlCard,
-};
+use crate::{mtgo_card::MtgoCard, set_catalog::SetCatalog, xml::XmlCard};
 use parse_goatbots::card_definitions::GoatBotsCard;
 use parse_scryfall::ScryfallCard;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, num::ParseIntError};
+use std::{collections::HashMap, fs, io, num::ParseIntError, path::Path};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Collection {
@@ -27,32 +24,52 @@ impl Collection {
         })
     }
 
+    /// Deserialize a [Collection] from a JSON-file, e.g. one of the snapshots saved by [parse_full](crate::parse_full)
+    pub fn from_json_file(path: &Path) -> Result<Self, io::Error> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(io::Error::other)
+    }
+
+    /// Create a [Collection] from already enriched cards, e.g. restored from the [ParseCache](crate::cache::ParseCache)
+    pub fn from_cards(cards: Vec<MtgoCard>) -> Self {
+        Self {
+            cards,
+            total_quantity: None,
+        }
+    }
+
+    /// Assign set, rarity, foil and the Goatbots price in a single pass over the collection
     pub fn extract_goatbots_info(
         &mut self,
-        mut card_defs: HashMap<String, GoatBotsCard>,
-        price_hist: HashMap<String, f32>,
+        mut card_defs: HashMap<u32, GoatBotsCard>,
+        price_hist: HashMap<u32, f32>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         for card in self.cards.iter_mut() {
-            if card.id == 1 {
-                card.goatbots_price = 1.0; // Event tickets have value 1 per definition
-                continue;
-            }
-
-            if let Some(cd) = card_defs.remove(&card.id.to_string()) {
-                card.set = cd.cardset.into_boxed_str();
-                card.rarity = Rarity::from(cd.rarity.as_ref());
-                card.foil = cd.foil == 1;
-            } else {
-                eprintln!("Card definition key not found: ID={}", card.id);
-            }
+            assign_card_def(card, &mut card_defs);
+            assign_goatbots_price(card, &price_hist);
+        }
+        Ok(())
+    }
 
-            if let Some(price) = price_hist.get(&card.id.to_string()) {
-                card.goatbots_price = *price;
-            } else {
-                eprintln!("Price history key not found: ID={}", card.id);
-            }
+    /// Assign set, rarity and foil from the Goatbots card definitions
+    pub fn extract_goatbots_card_defs(
+        &mut self,
+        mut card_defs: HashMap<u32, GoatBotsCard>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for card in self.cards.iter_mut() {
+            assign_card_def(card, &mut card_defs);
         }
+        Ok(())
+    }
 
+    /// Assign the Goatbots price from the Goatbots price history
+    pub fn extract_goatbots_prices(
+        &mut self,
+        price_hist: &HashMap<u32, f32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for card in self.cards.iter_mut() {
+            assign_goatbots_price(card, price_hist);
+        }
         Ok(())
     }
 
@@ -61,14 +78,24 @@ impl Collection {
         mut scryfall_cards: Vec<ScryfallCard>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         scryfall_cards.sort_unstable_by_key(|k| k.mtgo_id);
+        self.extract_sorted_scryfall_info(scryfall_cards.into_iter().map(Ok::<_, io::Error>))
+    }
 
+    /// Assign the Scryfall price from Scryfall cards sorted by MTGO ID, e.g. streamed from the cache with
+    /// [SortedCards](parse_scryfall::SortedCards), without collecting them first
+    pub fn extract_sorted_scryfall_info<E: Into<Box<dyn std::error::Error>>>(
+        &mut self,
+        sorted_scryfall_cards: impl IntoIterator<Item = Result<ScryfallCard, E>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // Iterate over all the mtgo cards and the scryfall card info
         // If matching on the ID, assign the scryfall price
         // If mtgo card id is higher then scryfall id -> check the next scryfall card
         // If mtgo card id is lower than scryfall id -> check next mtgo card id
         // Loop until one of the collections is exhausted.
-        let mut scry_idx = 0;
+        let mut scryfall_cards = sorted_scryfall_cards.into_iter().peekable();
         for card in self.cards.iter_mut() {
+            // Reset any previously extracted price so the extraction can be redone on a restored collection
+            card.scryfall_price = None;
             // Skip if it is foil as scryfall API doesn't have foil prices
             if card.foil {
                 continue;
@@ -79,25 +106,31 @@ impl Collection {
                 continue;
             }
 
-            while let Some(sc) = scryfall_cards.get_mut(scry_idx) {
-                if sc.mtgo_id > card.id {
-                    // In this case we need to check the next card in the collection
-                    break;
-                }
+            // Errors are consumed too, so they are returned by `?` below
+            while let Some(sc) =
+                scryfall_cards.next_if(|sc| sc.as_ref().map_or(true, |sc| sc.mtgo_id <= card.id))
+            {
+                let sc = sc.map_err(Into::into)?;
                 if sc.mtgo_id == card.id {
-                    match sc.prices.tix.take() {
+                    match sc.prices.tix {
                         Some(tix_price) if !tix_price.is_empty() => {
                             card.scryfall_price = Some(tix_price.parse()?)
                         }
                         _ => (),
                     }
                 }
-                scry_idx += 1;
             }
         }
         Ok(())
     }
 
+    /// Assign the set name and release date of every card from the catalog
+    pub fn extract_set_info(&mut self, catalog: &SetCatalog) {
+        for card in self.cards.iter_mut() {
+            card.assign_set_info(catalog);
+        }
+    }
+
     pub fn unique_cards(&self) -> usize {
         self.cards.len()
     }
@@ -112,7 +145,38 @@ impl Collection {
         }
     }
 
+    pub fn cards(&self) -> &[MtgoCard] {
+        &self.cards
+    }
+
     pub fn take_cards(&mut self) -> Vec<MtgoCard> {
         self.cards.drain(..).collect()
     }
 }
+
+/// Move the card definition of the card out of `card_defs`, as each ID occurs once in a collection
+fn assign_card_def(card: &mut MtgoCard, card_defs: &mut HashMap<u32, GoatBotsCard>) {
+    if card.id == 1 {
+        return;
+    }
+    if let Some(cd) = card_defs.remove(&card.id) {
+        card.set = cd.cardset.into_boxed_str();
+        card.rarity = cd.rarity;
+        card.foil = cd.foil;
+    } else {
+        eprintln!("Card definition key not found: ID={}", card.id);
+    }
+}
+
+fn assign_goatbots_price(card: &mut MtgoCard, price_hist: &HashMap<u32, f32>) {
+    if card.id == 1 {
+        card.goatbots_price = 1.0; // Event tickets have value 1 per definition
+        return;
+    }
+    if let Some(price) = price_hist.get(&card.id) {
+        card.goatbots_price = *price;
+    } else {
+        card.goatbots_price = 0.;
+        eprintln!("Price history key not found: ID={}", card.id);
+    }
+}
@@ -1,19 +1,37 @@
 use std::{fs, io, path::Path};
 
+use cache::{InputHashes, ParseCache, StaleInputs};
 use chrono::Utc;
 use collection::Collection;
 use mtgo_card::MtgoCard;
 use parse_goatbots::{
     card_definitions::parse_card_def_json, price_history::parse_price_history_json,
 };
-use parse_scryfall::ScryfallCard;
+use parse_scryfall::SortedCards;
+use set_catalog::SetCatalog;
 use xml::parse_dek_xml;
 
+pub mod cache;
 pub mod collection;
 pub mod mtgo_card;
+pub mod price_report;
+pub mod set_catalog;
 pub mod util;
 pub mod xml;
 
+/// Parse the full trade list and enrich it with the Goatbots and Scryfall card data.
+///
+/// If `save_json_to_dir` is set, the enriched collection is cached there keyed on the content hashes of the four inputs.
+/// When none of the inputs changed the cached collection is returned, and if only some of them changed,
+/// only the affected part of the enrichment is redone. A collection snapshot is saved unless none of the inputs changed
+/// since the most recent one, so refreshing with the same data doesn't pile up identical snapshots.
+///
+/// The set names and release dates are taken from the [SETS_FILENAME](set_catalog::SETS_FILENAME) stored next to the
+/// Scryfall cards, if there is one.
+///
+/// # Errors
+///
+/// Returns an error if any of the inputs cannot be read or parsed
 pub fn parse_full(
     full_trade_list_path: &Path,
     scryfall_path: &Path,
@@ -21,19 +39,65 @@ pub fn parse_full(
     price_history_path: &Path,
     save_json_to_dir: Option<&Path>,
 ) -> Result<Vec<MtgoCard>, io::Error> {
-    let xml_cards =
-        parse_dek_xml(full_trade_list_path).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-    let price_hist = parse_price_history_json(price_history_path).unwrap();
-    let goatbots_card_defs = parse_card_def_json(card_definitions_path).unwrap();
-    let scryfall_json_str = fs::read_to_string(scryfall_path)?;
-    let scryfall_cards: Vec<ScryfallCard> = serde_json::from_str(&scryfall_json_str).unwrap();
-    let mut collection = Collection::from_xml_cards(xml_cards).unwrap();
-    collection
-        .extract_goatbots_info(goatbots_card_defs, price_hist)
-        .unwrap();
-    collection.extract_scryfall_info(scryfall_cards).unwrap();
+    // Set if none of the inputs changed since the cached collection was saved
+    let mut inputs_unchanged = false;
+    let mut collection = if let Some(dir) = save_json_to_dir {
+        let cache = ParseCache::load(dir);
+        let hashes = InputHashes::from_paths(
+            full_trade_list_path,
+            scryfall_path,
+            card_definitions_path,
+            price_history_path,
+            cache.as_ref().map(ParseCache::hashes),
+        )?;
+        let mut collection = match cache {
+            Some(cache) => {
+                let stale = cache.stale_inputs(&hashes);
+                inputs_unchanged = stale.is_empty();
+                if stale.full_trade_list {
+                    parse_collection(
+                        full_trade_list_path,
+                        scryfall_path,
+                        card_definitions_path,
+                        price_history_path,
+                    )?
+                } else {
+                    update_collection(
+                        Collection::from_cards(cache.take_cards()),
+                        stale,
+                        scryfall_path,
+                        card_definitions_path,
+                        price_history_path,
+                    )?
+                }
+            }
+            None => parse_collection(
+                full_trade_list_path,
+                scryfall_path,
+                card_definitions_path,
+                price_history_path,
+            )?,
+        };
+        assign_set_info(&mut collection, scryfall_path);
+        if let Err(e) = ParseCache::new(hashes, collection.cards().to_vec()).save(dir) {
+            eprintln!("Failed to save parse cache: {e}");
+        }
+        collection
+    } else {
+        let mut collection = parse_collection(
+            full_trade_list_path,
+            scryfall_path,
+            card_definitions_path,
+            price_history_path,
+        )?;
+        assign_set_info(&mut collection, scryfall_path);
+        collection
+    };
 
     if let Some(p) = save_json_to_dir {
+        if inputs_unchanged && util::most_recent_collection_snapshot(p)?.is_some() {
+            return Ok(collection.take_cards());
+        }
         if has_state_log_changed(p) {
             let fname = "fetch_log.toml";
             let state_log_path = p.join(fname);
@@ -47,12 +111,86 @@ pub fn parse_full(
         // Save json
         let json_out = serde_json::to_string(&collection)?;
         let timestamp = Utc::now();
-        let fname = format!("mtgo-cards_{}", timestamp.format("%Y-%m-%dT%H%M%SZ"));
+        let fname = format!(
+            "{}{}",
+            util::COLLECTION_SNAPSHOT_PREFIX,
+            timestamp.format("%Y-%m-%dT%H%M%SZ")
+        );
         fs::write(p.join(fname), json_out).unwrap();
     }
     Ok(collection.take_cards())
 }
 
+/// Parse the full trade list and do the full enrichment
+fn parse_collection(
+    full_trade_list_path: &Path,
+    scryfall_path: &Path,
+    card_definitions_path: &Path,
+    price_history_path: &Path,
+) -> Result<Collection, io::Error> {
+    let xml_cards = parse_dek_xml(full_trade_list_path).map_err(io::Error::other)?;
+    let collection = Collection::from_xml_cards(xml_cards).map_err(io::Error::other)?;
+    update_collection(
+        collection,
+        StaleInputs {
+            full_trade_list: true,
+            scryfall: true,
+            card_definitions: true,
+            price_history: true,
+        },
+        scryfall_path,
+        card_definitions_path,
+        price_history_path,
+    )
+}
+
+/// Redo the parts of the enrichment that depend on the stale inputs
+fn update_collection(
+    mut collection: Collection,
+    stale: StaleInputs,
+    scryfall_path: &Path,
+    card_definitions_path: &Path,
+    price_history_path: &Path,
+) -> Result<Collection, io::Error> {
+    if stale.card_definitions {
+        let goatbots_card_defs =
+            parse_card_def_json(card_definitions_path).map_err(boxed_to_io_err)?;
+        collection
+            .extract_goatbots_card_defs(goatbots_card_defs)
+            .map_err(boxed_to_io_err)?;
+    }
+    if stale.price_history {
+        let price_hist = parse_price_history_json(price_history_path).map_err(boxed_to_io_err)?;
+        collection
+            .extract_goatbots_prices(&price_hist)
+            .map_err(boxed_to_io_err)?;
+    }
+    // The Scryfall prices depend on whether the card is foil, which comes from the card definitions
+    if stale.scryfall || stale.card_definitions {
+        collection
+            .extract_sorted_scryfall_info(SortedCards::open(scryfall_path)?)
+            .map_err(boxed_to_io_err)?;
+    }
+    Ok(collection)
+}
+
+/// Assign the set info from the set catalog next to the Scryfall cards, it's redone on every parse as it's cheap and
+/// the catalog isn't one of the hashed inputs
+fn assign_set_info(collection: &mut Collection, scryfall_path: &Path) {
+    let sets_path = scryfall_path.with_file_name(set_catalog::SETS_FILENAME);
+    if !sets_path.exists() {
+        return;
+    }
+    match SetCatalog::from_json_file(&sets_path) {
+        Ok(catalog) => collection.extract_set_info(&catalog),
+        Err(e) => eprintln!("Failed to load the set catalog {sets_path:?}: {e}"),
+    }
+}
+
+fn boxed_to_io_err(e: Box<dyn std::error::Error>) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
 pub fn has_state_log_changed(appdata_dir: &Path) -> bool {
     let fname = "fetch_log.toml";
     let history_log_path = appdata_dir.join("collection-history").join(fname);
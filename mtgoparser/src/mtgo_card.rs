@@ -1,11 +1,13 @@
-use std::{fmt::Display, num::ParseIntError};
+use std::num::ParseIntError;
 
 pub mod card_history;
 pub mod collection_history;
 
 use serde::{Deserialize, Serialize};
 
-use crate::xml::XmlCard;
+use crate::{set_catalog::SetCatalog, xml::XmlCard};
+use chrono::NaiveDate;
+pub use parse_goatbots::card_definitions::Rarity;
 
 /// This is the struct that represents a card in the MTGO collection.
 ///
@@ -20,6 +22,12 @@ pub struct MtgoCard {
     pub foil: bool,
     pub goatbots_price: f32,
     pub scryfall_price: Option<f32>,
+    /// The full name of the set, if the set is in the [SetCatalog]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_name: Option<Box<str>>,
+    /// The release date of the set, if the set is in the [SetCatalog]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_released_at: Option<NaiveDate>,
 }
 
 impl MtgoCard {
@@ -33,55 +41,12 @@ impl MtgoCard {
             ..Default::default()
         })
     }
-}
-
-/// Represents the rarity of an MTGO item (e.g. card, booster, event ticket)
-#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, PartialOrd, Ord, Eq)]
-pub enum Rarity {
-    #[default]
-    #[serde(alias = "C")]
-    Common,
-    #[serde(alias = "U")]
-    Uncommon,
-    #[serde(alias = "R")]
-    Rare,
-    #[serde(alias = "M")]
-    Mythic,
-    #[serde(alias = "B")]
-    Booster,
-    #[serde(other)]
-    None,
-}
-
-impl Display for Rarity {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Rarity::Common => f.write_str("Common"),
-            Rarity::Uncommon => f.write_str("Uncommon"),
-            Rarity::Rare => f.write_str("Rare"),
-            Rarity::Mythic => f.write_str("Mythic"),
-            Rarity::Booster => f.write_str("Booster"),
-            Rarity::None => f.write_str("None"),
-        }
-    }
-}
 
-impl From<&str> for Rarity {
-    fn from(s: &str) -> Self {
-        match s {
-            // Single letter matches first for speed
-            "C" => Rarity::Common,
-            "U" => Rarity::Uncommon,
-            "R" => Rarity::Rare,
-            "M" => Rarity::Mythic,
-            "B" => Rarity::Booster,
-            "Uncommon" => Rarity::Uncommon,
-            "Rare" => Rarity::Rare,
-            "Mythic" => Rarity::Mythic,
-            "Booster" => Rarity::Booster,
-            "Common" => Rarity::Common,
-            _ => Rarity::None, // e.g. Event tickets
-        }
+    /// Assign the name and release date of the set of the card from the catalog, or clear them if the set isn't in it
+    pub fn assign_set_info(&mut self, catalog: &SetCatalog) {
+        let set = catalog.get(&self.set);
+        self.set_name = set.map(|s| s.name.clone());
+        self.set_released_at = set.map(|s| s.released_at);
     }
 }
 
@@ -0,0 +1,348 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::mtgo_card::MtgoCard;
+
+/// Thresholds that decide which cards end up in a [PriceReport]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportThresholds {
+    /// Minimum absolute spread in tix between Goatbots and Cardhoarder for a card to be included
+    pub min_abs_spread: f32,
+    /// Minimum relative spread (0.5 = the most expensive source is 50% above the cheapest) for a card to be included
+    pub min_rel_spread: f32,
+    /// A price that changed by more than this factor (in either direction) since the previous snapshot is flagged
+    pub max_price_jump: f32,
+}
+
+impl Default for ReportThresholds {
+    fn default() -> Self {
+        Self {
+            min_abs_spread: 0.1,
+            min_rel_spread: 0.25,
+            max_price_jump: 3.0,
+        }
+    }
+}
+
+/// The source of a price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    Goatbots,
+    Cardhoarder,
+}
+
+impl Display for PriceSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriceSource::Goatbots => f.write_str("Goatbots"),
+            PriceSource::Cardhoarder => f.write_str("Cardhoarder"),
+        }
+    }
+}
+
+/// The price spread of a single card between Goatbots and Cardhoarder
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceSpread {
+    pub id: u32,
+    pub name: Box<str>,
+    pub goatbots_price: f32,
+    pub scryfall_price: f32,
+    /// Absolute difference in tix between the two prices
+    pub abs_spread: f32,
+    /// The absolute spread relative to the cheapest of the two prices
+    pub rel_spread: f32,
+}
+
+impl PriceSpread {
+    /// Returns the source with the highest price
+    pub fn most_expensive_source(&self) -> PriceSource {
+        if self.goatbots_price > self.scryfall_price {
+            PriceSource::Goatbots
+        } else {
+            PriceSource::Cardhoarder
+        }
+    }
+}
+
+/// Describes why a card's price data looks suspicious
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceFlag {
+    /// The price from the source is exactly 0
+    ZeroPrice(PriceSource),
+    /// Non-foil card without a Cardhoarder price
+    MissingCardhoarderPrice,
+    /// The price changed by more than [ReportThresholds::max_price_jump] since the previous snapshot
+    PriceJump {
+        source: PriceSource,
+        previous: f32,
+        current: f32,
+    },
+}
+
+impl Display for PriceFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriceFlag::ZeroPrice(source) => write!(f, "{source} price is 0"),
+            PriceFlag::MissingCardhoarderPrice => f.write_str("Cardhoarder price is missing"),
+            PriceFlag::PriceJump {
+                source,
+                previous,
+                current,
+            } => write!(f, "{source} price jumped from {previous} to {current}"),
+        }
+    }
+}
+
+/// A card with suspicious price data
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlaggedCard {
+    pub id: u32,
+    pub name: Box<str>,
+    pub flag: PriceFlag,
+}
+
+/// Order to rank the [PriceSpread]s of a [PriceReport] by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadOrder {
+    Absolute,
+    Relative,
+}
+
+/// Report of price discrepancies between Goatbots and Cardhoarder along with any suspicious price data.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceReport {
+    spreads: Vec<PriceSpread>,
+    flagged: Vec<FlaggedCard>,
+}
+
+impl PriceReport {
+    /// Create a [PriceReport] from a collection of cards
+    ///
+    /// # Arguments
+    ///
+    /// * `cards` - The cards to report on
+    /// * `previous` - The cards from the previous snapshot, used to detect price jumps
+    /// * `thresholds` - The [ReportThresholds] that decides what is included in the report
+    ///
+    /// # Returns
+    ///
+    /// A [PriceReport] with spreads ranked by absolute spread (largest first)
+    pub fn new(
+        cards: &[MtgoCard],
+        previous: Option<&[MtgoCard]>,
+        thresholds: ReportThresholds,
+    ) -> Self {
+        let previous_prices: HashMap<u32, &MtgoCard> = previous
+            .unwrap_or_default()
+            .iter()
+            .map(|c| (c.id, c))
+            .collect();
+
+        let mut spreads = Vec::new();
+        let mut flagged = Vec::new();
+        let mut flag = |card: &MtgoCard, flag: PriceFlag| {
+            flagged.push(FlaggedCard {
+                id: card.id,
+                name: card.name.clone(),
+                flag,
+            })
+        };
+
+        for card in cards {
+            // Event tickets have a fixed price of 1 tix
+            if card.id == 1 {
+                continue;
+            }
+
+            if card.goatbots_price == 0. {
+                flag(card, PriceFlag::ZeroPrice(PriceSource::Goatbots));
+            }
+            match card.scryfall_price {
+                Some(0.0) => flag(card, PriceFlag::ZeroPrice(PriceSource::Cardhoarder)),
+                // Scryfall doesn't have foil prices
+                None if !card.foil => flag(card, PriceFlag::MissingCardhoarderPrice),
+                _ => (),
+            }
+
+            if let Some(prev) = previous_prices.get(&card.id) {
+                if is_price_jump(prev.goatbots_price, card.goatbots_price, thresholds) {
+                    flag(
+                        card,
+                        PriceFlag::PriceJump {
+                            source: PriceSource::Goatbots,
+                            previous: prev.goatbots_price,
+                            current: card.goatbots_price,
+                        },
+                    );
+                }
+                if let (Some(prev_price), Some(price)) = (prev.scryfall_price, card.scryfall_price)
+                {
+                    if is_price_jump(prev_price, price, thresholds) {
+                        flag(
+                            card,
+                            PriceFlag::PriceJump {
+                                source: PriceSource::Cardhoarder,
+                                previous: prev_price,
+                                current: price,
+                            },
+                        );
+                    }
+                }
+            }
+
+            if let Some(spread) = price_spread(card) {
+                if spread.abs_spread >= thresholds.min_abs_spread
+                    && spread.rel_spread >= thresholds.min_rel_spread
+                {
+                    spreads.push(spread);
+                }
+            }
+        }
+
+        let mut report = Self { spreads, flagged };
+        report.rank_by(SpreadOrder::Absolute);
+        report
+    }
+
+    /// Rank the price spreads by the given [SpreadOrder] (largest first)
+    pub fn rank_by(&mut self, order: SpreadOrder) {
+        match order {
+            SpreadOrder::Absolute => self
+                .spreads
+                .sort_by(|a, b| b.abs_spread.total_cmp(&a.abs_spread)),
+            SpreadOrder::Relative => self
+                .spreads
+                .sort_by(|a, b| b.rel_spread.total_cmp(&a.rel_spread)),
+        }
+    }
+
+    /// The price spreads that exceed the thresholds, in their current ranking
+    pub fn spreads(&self) -> &[PriceSpread] {
+        &self.spreads
+    }
+
+    /// The cards with suspicious price data
+    pub fn flagged(&self) -> &[FlaggedCard] {
+        &self.flagged
+    }
+}
+
+/// Calculate the spread between the Goatbots and Cardhoarder price of a card.
+///
+/// Returns [None] if the card lacks a Cardhoarder price or any of the prices are 0 (those are flagged instead).
+fn price_spread(card: &MtgoCard) -> Option<PriceSpread> {
+    let scryfall_price = card.scryfall_price?;
+    let cheapest = card.goatbots_price.min(scryfall_price);
+    if cheapest <= 0. {
+        return None;
+    }
+    let abs_spread = (card.goatbots_price - scryfall_price).abs();
+
+    Some(PriceSpread {
+        id: card.id,
+        name: card.name.clone(),
+        goatbots_price: card.goatbots_price,
+        scryfall_price,
+        abs_spread,
+        rel_spread: abs_spread / cheapest,
+    })
+}
+
+fn is_price_jump(previous: f32, current: f32, thresholds: ReportThresholds) -> bool {
+    if previous <= 0. || current <= 0. {
+        return false;
+    }
+    (current / previous).max(previous / current) > thresholds.max_price_jump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn card(id: u32, goatbots_price: f32, scryfall_price: Option<f32>) -> MtgoCard {
+        MtgoCard {
+            id,
+            quantity: 1,
+            name: format!("Card {id}").into(),
+            goatbots_price,
+            scryfall_price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_spreads_ranked_and_filtered() {
+        let cards = vec![
+            card(1, 1.0, Some(1.0)),   // Event ticket, ignored
+            card(10, 1.0, Some(1.05)), // Below thresholds
+            card(11, 2.0, Some(1.0)),  // abs 1.0, rel 1.0
+            card(12, 10.0, Some(8.0)), // abs 2.0, rel 0.25
+        ];
+        let mut report = PriceReport::new(&cards, None, ReportThresholds::default());
+
+        let ids: Vec<u32> = report.spreads().iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![12, 11]);
+        assert_eq!(
+            report.spreads()[0].most_expensive_source(),
+            PriceSource::Goatbots
+        );
+
+        report.rank_by(SpreadOrder::Relative);
+        let ids: Vec<u32> = report.spreads().iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![11, 12]);
+        assert!(report.flagged().is_empty());
+    }
+
+    #[test]
+    fn test_flags_zero_and_missing_prices() {
+        let mut foil = card(21, 0.5, None);
+        foil.foil = true;
+        let cards = vec![card(20, 0., Some(0.)), foil, card(22, 0.5, None)];
+        let report = PriceReport::new(&cards, None, ReportThresholds::default());
+
+        assert_eq!(
+            report
+                .flagged()
+                .iter()
+                .map(|f| (f.id, f.flag.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (20, PriceFlag::ZeroPrice(PriceSource::Goatbots)),
+                (20, PriceFlag::ZeroPrice(PriceSource::Cardhoarder)),
+                (22, PriceFlag::MissingCardhoarderPrice),
+            ]
+        );
+        assert!(report.spreads().is_empty());
+    }
+
+    #[test]
+    fn test_flags_price_jumps() {
+        let previous = vec![card(30, 1.0, Some(1.0)), card(31, 1.0, Some(1.0))];
+        let cards = vec![card(30, 4.0, Some(1.2)), card(31, 1.5, Some(0.2))];
+        let report = PriceReport::new(&cards, Some(&previous), ReportThresholds::default());
+
+        assert_eq!(
+            report.flagged(),
+            &[
+                FlaggedCard {
+                    id: 30,
+                    name: "Card 30".into(),
+                    flag: PriceFlag::PriceJump {
+                        source: PriceSource::Goatbots,
+                        previous: 1.0,
+                        current: 4.0
+                    }
+                },
+                FlaggedCard {
+                    id: 31,
+                    name: "Card 31".into(),
+                    flag: PriceFlag::PriceJump {
+                        source: PriceSource::Cardhoarder,
+                        previous: 1.0,
+                        current: 0.2
+                    }
+                }
+            ]
+        );
+    }
+}
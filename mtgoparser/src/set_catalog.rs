@@ -0,0 +1,192 @@
+use std::{cmp::Ordering, collections::HashMap, fs, io, path::Path};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::mtgo_card::MtgoCard;
+
+/// Name of the file `mtgogetter` stores the MTGO sets from Scryfall in, next to the Scryfall cards
+pub const SETS_FILENAME: &str = "scryfall-sets.json";
+
+/// An MTGO set as stored in [SETS_FILENAME]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetInfo {
+    pub name: Box<str>,
+    pub released_at: NaiveDate,
+    pub mtgo_code: Box<str>,
+    /// The Scryfall set code, empty if the file was stored before it was recorded
+    #[serde(default)]
+    pub code: Box<str>,
+}
+
+/// The MTGO sets in release order, looked up by the set codes Goatbots uses for the `cardset` of a card
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetCatalog {
+    sets: Vec<SetInfo>,
+    /// Index into `sets` keyed by the uppercase MTGO code, or the Scryfall code if no set has it as MTGO code
+    by_code: HashMap<Box<str>, usize>,
+}
+
+impl SetCatalog {
+    /// Create a catalog of the given sets, sorted by release date
+    pub fn new(mut sets: Vec<SetInfo>) -> Self {
+        sets.sort_by(|a, b| {
+            a.released_at
+                .cmp(&b.released_at)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        let mut by_code = HashMap::with_capacity(sets.len());
+        for (idx, set) in sets.iter().enumerate() {
+            by_code.insert(set.mtgo_code.to_ascii_uppercase().into_boxed_str(), idx);
+        }
+        for (idx, set) in sets.iter().enumerate() {
+            if !set.code.is_empty() {
+                by_code
+                    .entry(set.code.to_ascii_uppercase().into_boxed_str())
+                    .or_insert(idx);
+            }
+        }
+        Self { sets, by_code }
+    }
+
+    /// Load the catalog from a [SETS_FILENAME] file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed
+    pub fn from_json_file(path: &Path) -> Result<Self, io::Error> {
+        let json = fs::read(path)?;
+        let sets: Vec<SetInfo> = serde_json::from_slice(&json)?;
+        Ok(Self::new(sets))
+    }
+
+    /// Get the set with the given Goatbots set code, e.g. `MH3`, ignoring case
+    pub fn get(&self, goatbots_code: &str) -> Option<&SetInfo> {
+        self.by_code
+            .get(goatbots_code.to_ascii_uppercase().as_str())
+            .map(|&idx| &self.sets[idx])
+    }
+
+    /// All the sets, oldest first
+    pub fn sets(&self) -> &[SetInfo] {
+        &self.sets
+    }
+
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+}
+
+/// Order cards by the release date of their set, oldest first, and then by set code
+///
+/// Cards of a set that's not in the [SetCatalog] are ordered after all others.
+pub fn cmp_release_order(a: &MtgoCard, b: &MtgoCard) -> Ordering {
+    match (a.set_released_at, b.set_released_at) {
+        (Some(a_date), Some(b_date)) => a_date.cmp(&b_date),
+        (a_date, b_date) => b_date.is_some().cmp(&a_date.is_some()),
+    }
+    .then_with(|| a.set.cmp(&b.set))
+}
+
+/// The cards of a collection that belong to the same set
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetGroup<'c> {
+    /// The Goatbots set code of the cards
+    pub set: &'c str,
+    pub name: Option<&'c str>,
+    pub released_at: Option<NaiveDate>,
+    pub cards: Vec<&'c MtgoCard>,
+}
+
+/// Group the cards by set, in the order the sets were released (see [cmp_release_order])
+pub fn group_by_set(cards: &[MtgoCard]) -> Vec<SetGroup<'_>> {
+    let mut sorted: Vec<&MtgoCard> = cards.iter().collect();
+    sorted.sort_by(|a, b| cmp_release_order(a, b));
+
+    let mut groups: Vec<SetGroup> = Vec::new();
+    for card in sorted {
+        match groups.last_mut() {
+            Some(group) if group.set == card.set.as_ref() => group.cards.push(card),
+            _ => groups.push(SetGroup {
+                set: &card.set,
+                name: card.set_name.as_deref(),
+                released_at: card.set_released_at,
+                cards: vec![card],
+            }),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use testresult::TestResult;
+
+    const SETS_JSON: &str = r#"[
+        {"name": "Modern Horizons 3", "released_at": "2024-06-14", "mtgo_code": "mh3", "code": "mh3"},
+        {"name": "Conflux", "released_at": "2009-02-06", "mtgo_code": "cfx", "code": "con"},
+        {"name": "Limited Edition Alpha", "released_at": "1993-08-05", "mtgo_code": "1e"}
+    ]"#;
+
+    fn card(id: u32, set: &str) -> MtgoCard {
+        MtgoCard {
+            id,
+            set: set.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lookup_by_goatbots_code() -> TestResult {
+        let catalog = SetCatalog::new(serde_json::from_str(SETS_JSON)?);
+        assert_eq!(catalog.len(), 3);
+        assert_eq!(catalog.sets()[0].name.as_ref(), "Limited Edition Alpha");
+        assert_eq!(
+            catalog.get("1E").map(|s| s.name.as_ref()),
+            Some("Limited Edition Alpha")
+        );
+        assert_eq!(
+            catalog.get("mh3").map(|s| s.name.as_ref()),
+            Some("Modern Horizons 3")
+        );
+        // Goatbots uses the Scryfall code for Conflux
+        assert_eq!(catalog.get("CON").map(|s| s.name.as_ref()), Some("Conflux"));
+        assert_eq!(catalog.get("CFX").map(|s| s.name.as_ref()), Some("Conflux"));
+        assert_eq!(catalog.get("XYZ"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_set_in_release_order() -> TestResult {
+        let catalog = SetCatalog::new(serde_json::from_str(SETS_JSON)?);
+        let mut cards = vec![
+            card(1, "EVENT"),
+            card(2, "MH3"),
+            card(3, "1E"),
+            card(4, "MH3"),
+        ];
+        for card in &mut cards {
+            card.assign_set_info(&catalog);
+        }
+
+        let groups: Vec<(&str, Option<&str>, Vec<u32>)> = group_by_set(&cards)
+            .into_iter()
+            .map(|g| (g.set, g.name, g.cards.iter().map(|c| c.id).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("1E", Some("Limited Edition Alpha"), vec![3]),
+                ("MH3", Some("Modern Horizons 3"), vec![2, 4]),
+                ("EVENT", None, vec![1]),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -5,6 +5,9 @@ use std::{
 
 use chrono::{DateTime, NaiveDateTime, Utc};
 
+/// Prefix of the collection snapshot files saved by [parse_full](crate::parse_full)
+pub const COLLECTION_SNAPSHOT_PREFIX: &str = "mtgo-cards_";
+
 /// Get all files in a directory that have a timestamp suffix of pattern `YYYY-MM-DDThhmmssZ`.
 pub fn get_files_with_timestamp(dir: &Path) -> Result<Vec<(PathBuf, DateTime<Utc>)>, io::Error> {
     let mut files: Vec<(PathBuf, DateTime<Utc>)> = Vec::new();
@@ -28,6 +31,26 @@ pub fn get_files_with_timestamp(dir: &Path) -> Result<Vec<(PathBuf, DateTime<Utc
     Ok(files)
 }
 
+/// Get all collection snapshots (pattern `mtgo-cards_YYYY-MM-DDThhmmssZ`) in a directory, sorted oldest first.
+pub fn collection_snapshots(dir: &Path) -> Result<Vec<(PathBuf, DateTime<Utc>)>, io::Error> {
+    let mut snapshots: Vec<(PathBuf, DateTime<Utc>)> = get_files_with_timestamp(dir)?
+        .into_iter()
+        .filter(|(p, _)| {
+            p.file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with(COLLECTION_SNAPSHOT_PREFIX))
+        })
+        .collect();
+    snapshots.sort_by_key(|(_, timestamp)| *timestamp);
+    Ok(snapshots)
+}
+
+/// Get the most recent collection snapshot (pattern `mtgo-cards_YYYY-MM-DDThhmmssZ`) in a directory.
+///
+/// Returns [None] if the directory contains no snapshots.
+pub fn most_recent_collection_snapshot(dir: &Path) -> Result<Option<PathBuf>, io::Error> {
+    Ok(collection_snapshots(dir)?.pop().map(|(p, _)| p))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +99,23 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_most_recent_collection_snapshot() -> TestResult {
+        let temp_dir = TempDir::new()?;
+        assert_eq!(most_recent_collection_snapshot(temp_dir.path())?, None);
+
+        let older = temp_dir.path().join("mtgo-cards_2023-11-05T152700Z");
+        let newer = temp_dir.path().join("mtgo-cards_2023-11-06T080000Z");
+        let other = temp_dir.path().join("other_2024-01-01T000000Z");
+        fs::write(&older, "content")?;
+        fs::write(&newer, "content")?;
+        fs::write(&other, "content")?;
+
+        assert_eq!(
+            most_recent_collection_snapshot(temp_dir.path())?,
+            Some(newer)
+        );
+        Ok(())
+    }
 }
@@ -1,9 +1,13 @@
-use quick_xml::events::attributes::{AttrError, Attributes};
+use quick_xml::events::attributes::Attributes;
 use quick_xml::events::Event;
 use quick_xml::name::QName;
 use quick_xml::reader::Reader;
 use std::path::Path;
 
+use validate::TradeListError;
+
+pub mod validate;
+
 /// The relevant card information that can be extracted from an MTGO .dek XML file.
 #[derive(Debug, Clone, PartialEq)]
 pub struct XmlCard {
@@ -28,13 +32,18 @@ impl XmlCard {
     // The XML card information is contained in the attributes of the <Cards> tag.
     // e.g.
     // <Cards CatID="235" Quantity="1" Sideboard="false" Name="Swamp" Annotation="0" />
-    pub fn from_xml_cards_attrs(card_attrs: Attributes) -> Result<Self, AttrError> {
+    //
+    // `card_index` is the index of the <Cards> tag in the file, it's used to tell which card is missing an attribute.
+    pub fn from_xml_cards_attrs(
+        card_attrs: Attributes,
+        card_index: usize,
+    ) -> Result<Self, TradeListError> {
         let mut cat_id = String::new();
         let mut quantity = String::new();
         let mut name = String::new();
 
         for (index, att) in card_attrs.enumerate() {
-            let att = att?;
+            let att = att.map_err(|e| TradeListError::Xml(e.into()))?;
             match index {
                 Self::CAT_ID_IDX => cat_id.push_str(&String::from_utf8_lossy(&att.value)),
                 Self::QUANTITY_IDX => quantity.push_str(&String::from_utf8_lossy(&att.value)),
@@ -42,12 +51,25 @@ impl XmlCard {
                 _ => (),
             }
         }
-        debug_assert!(!cat_id.is_empty() && !quantity.is_empty() && !name.is_empty());
+        for (attribute, value) in [("CatID", &cat_id), ("Quantity", &quantity), ("Name", &name)] {
+            if value.is_empty() {
+                return Err(TradeListError::MissingAttribute {
+                    card_index,
+                    attribute,
+                });
+            }
+        }
         Ok(Self::new(cat_id, quantity, name))
     }
 }
 
-pub fn parse_dek_xml(path: &Path) -> Result<Vec<XmlCard>, quick_xml::Error> {
+/// Parse the cards of an MTGO .dek XML file
+///
+/// # Errors
+///
+/// Returns a [TradeListError] if the file is not well-formed XML, contains an empty element other than `<Cards>`,
+/// or a `<Cards>` element without a `CatID`, `Quantity` or `Name` attribute.
+pub fn parse_dek_xml(path: &Path) -> Result<Vec<XmlCard>, TradeListError> {
     let mut reader = Reader::from_file(path)?;
     let mut deck: Vec<XmlCard> = Vec::with_capacity(1024);
 
@@ -58,9 +80,13 @@ pub fn parse_dek_xml(path: &Path) -> Result<Vec<XmlCard>, quick_xml::Error> {
             Ok(e) => match e {
                 Event::Empty(e_tag) => {
                     let name = e_tag.name();
-                    debug_assert_eq!(name, QName(b"Cards"), "Expected any/all empty element tags in MTGO .dek XML to have name=Cards but got name={name:?}");
+                    if name != QName(b"Cards") {
+                        return Err(TradeListError::UnexpectedElement(
+                            String::from_utf8_lossy(name.as_ref()).into_owned(),
+                        ));
+                    }
 
-                    let card = XmlCard::from_xml_cards_attrs(e_tag.attributes())?;
+                    let card = XmlCard::from_xml_cards_attrs(e_tag.attributes(), deck.len())?;
                     deck.push(card);
                 }
                 // Here for visibility
@@ -76,7 +102,7 @@ pub fn parse_dek_xml(path: &Path) -> Result<Vec<XmlCard>, quick_xml::Error> {
             },
             Err(err) => {
                 eprintln!("{err}");
-                return Err(err);
+                return Err(err.into());
             }
         }
     }
@@ -88,6 +114,7 @@ pub fn parse_dek_xml(path: &Path) -> Result<Vec<XmlCard>, quick_xml::Error> {
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
     use testresult::TestResult;
 
     #[test]
@@ -137,4 +164,31 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_parse_dek_xml_invalid_cards() -> TestResult {
+        let dir = TempDir::new()?;
+        let path = dir.child("invalid.dek");
+        std::fs::write(
+            &path,
+            r#"<Deck>
+  <Cards CatID="1" Quantity="453" Sideboard="false" Name="Event Ticket" Annotation="0" />
+  <Cards CatID="235" Quantity="1" Sideboard="false" />
+</Deck>"#,
+        )?;
+        assert!(matches!(
+            parse_dek_xml(&path),
+            Err(TradeListError::MissingAttribute {
+                card_index: 1,
+                attribute: "Name"
+            })
+        ));
+
+        std::fs::write(&path, r#"<Deck><Card CatID="1" /></Deck>"#)?;
+        assert!(matches!(
+            parse_dek_xml(&path),
+            Err(TradeListError::UnexpectedElement(name)) if name == "Card"
+        ));
+        Ok(())
+    }
 }
@@ -0,0 +1,355 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use std::{error::Error, fmt::Display, path::Path};
+
+/// Deck exports rarely exceed a constructed main deck and sideboard, a full trade list without event tickets
+/// and at most this many cards is most likely a deck export.
+const MAX_DECK_EXPORT_CARDS: u32 = 100;
+
+/// CatID of Event Tickets
+const EVENT_TICKET_CAT_ID: u32 = 1;
+
+/// The reason a file was rejected as an MTGO full trade list
+#[derive(Debug)]
+pub enum TradeListError {
+    /// The file is not well-formed XML (or could not be read)
+    Xml(quick_xml::Error),
+    /// The root element is not `<Deck>`
+    MissingDeckRoot(Option<String>),
+    /// An empty element other than `<Cards>` was found
+    UnexpectedElement(String),
+    /// A `<Cards>` element is missing a required attribute
+    MissingAttribute {
+        card_index: usize,
+        attribute: &'static str,
+    },
+    /// A `<Cards>` element has an attribute with an invalid value
+    InvalidAttribute {
+        card_index: usize,
+        attribute: &'static str,
+        value: String,
+    },
+    /// The file doesn't contain any cards
+    NoCards,
+    /// The file looks like an ordinary deck export rather than a full trade list
+    DeckExport(&'static str),
+}
+
+impl Display for TradeListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeListError::Xml(e) => write!(f, "Not a valid XML file: {e}"),
+            TradeListError::MissingDeckRoot(Some(root)) => {
+                write!(f, "Expected the root element to be <Deck> but got <{root}>")
+            }
+            TradeListError::MissingDeckRoot(None) => f.write_str("No <Deck> element found"),
+            TradeListError::UnexpectedElement(name) => {
+                write!(
+                    f,
+                    "Unexpected element <{name}>, expected only <Cards> elements"
+                )
+            }
+            TradeListError::MissingAttribute {
+                card_index,
+                attribute,
+            } => write!(f, "Card #{card_index} is missing the {attribute} attribute"),
+            TradeListError::InvalidAttribute {
+                card_index,
+                attribute,
+                value,
+            } => write!(
+                f,
+                "Card #{card_index} has an invalid {attribute} attribute: {value:?}"
+            ),
+            TradeListError::NoCards => f.write_str("The file doesn't contain any cards"),
+            TradeListError::DeckExport(reason) => {
+                write!(
+                    f,
+                    "Looks like a deck export, not a full trade list: {reason}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for TradeListError {}
+
+impl From<quick_xml::Error> for TradeListError {
+    fn from(e: quick_xml::Error) -> Self {
+        TradeListError::Xml(e)
+    }
+}
+
+/// Summary of a validated full trade list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TradeListSummary {
+    pub unique_cards: usize,
+    pub total_quantity: u32,
+    pub event_tickets: u32,
+}
+
+/// Validate that the file at the given path is an MTGO full trade list.
+///
+/// Checks that the root element is `<Deck>`, that all `<Cards>` elements have valid `CatID`, `Quantity` and `Name` attributes,
+/// and uses heuristics (sideboard cards, event tickets, card count) to reject ordinary deck exports.
+///
+/// # Errors
+///
+/// Returns a [TradeListError] describing why the file was rejected.
+pub fn validate_full_trade_list(path: &Path) -> Result<TradeListSummary, TradeListError> {
+    let mut reader = Reader::from_file(path)?;
+    let mut buf = Vec::new();
+    let mut summary = TradeListSummary::default();
+    let mut has_deck_root = false;
+    let mut has_sideboard_cards = false;
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e_tag) if !has_deck_root => {
+                if e_tag.name().as_ref() != b"Deck" {
+                    return Err(TradeListError::MissingDeckRoot(Some(tag_name(&e_tag))));
+                }
+                has_deck_root = true;
+            }
+            Event::Empty(e_tag) => {
+                if !has_deck_root {
+                    return Err(TradeListError::MissingDeckRoot(Some(tag_name(&e_tag))));
+                }
+                if e_tag.name().as_ref() != b"Cards" {
+                    return Err(TradeListError::UnexpectedElement(tag_name(&e_tag)));
+                }
+                let card_index = summary.unique_cards;
+                let card = validate_card(&e_tag, card_index)?;
+                // No collection comes close to the limit, it's a corrupt or crafted file
+                let too_many = || TradeListError::InvalidAttribute {
+                    card_index,
+                    attribute: "Quantity",
+                    value: card.quantity.to_string(),
+                };
+                if card.cat_id == EVENT_TICKET_CAT_ID {
+                    summary.event_tickets = summary
+                        .event_tickets
+                        .checked_add(card.quantity)
+                        .ok_or_else(too_many)?;
+                }
+                has_sideboard_cards |= card.sideboard;
+                summary.unique_cards += 1;
+                summary.total_quantity = summary
+                    .total_quantity
+                    .checked_add(card.quantity)
+                    .ok_or_else(too_many)?;
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    if !has_deck_root {
+        return Err(TradeListError::MissingDeckRoot(None));
+    }
+    if summary.unique_cards == 0 {
+        return Err(TradeListError::NoCards);
+    }
+    if has_sideboard_cards {
+        return Err(TradeListError::DeckExport("it contains sideboard cards"));
+    }
+    if summary.event_tickets == 0 && summary.total_quantity <= MAX_DECK_EXPORT_CARDS {
+        return Err(TradeListError::DeckExport(
+            "it has no event tickets and too few cards",
+        ));
+    }
+
+    Ok(summary)
+}
+
+/// The validated attributes of a `<Cards>` element
+struct ValidCard {
+    cat_id: u32,
+    quantity: u32,
+    sideboard: bool,
+}
+
+fn validate_card(e_tag: &BytesStart, card_index: usize) -> Result<ValidCard, TradeListError> {
+    let mut cat_id = None;
+    let mut quantity = None;
+    let mut name = None;
+    let mut sideboard = false;
+
+    for att in e_tag.attributes() {
+        let att = att.map_err(|e| TradeListError::Xml(e.into()))?;
+        let value = String::from_utf8_lossy(&att.value).into_owned();
+        match att.key.as_ref() {
+            b"CatID" => cat_id = Some(parse_attr(card_index, "CatID", value)?),
+            b"Quantity" => quantity = Some(parse_attr(card_index, "Quantity", value)?),
+            b"Name" => name = Some(value),
+            b"Sideboard" => sideboard = value == "true",
+            _ => (),
+        }
+    }
+
+    let missing = |attribute| TradeListError::MissingAttribute {
+        card_index,
+        attribute,
+    };
+    let cat_id = cat_id.ok_or_else(|| missing("CatID"))?;
+    let quantity = quantity.ok_or_else(|| missing("Quantity"))?;
+    if name.ok_or_else(|| missing("Name"))?.is_empty() {
+        return Err(TradeListError::InvalidAttribute {
+            card_index,
+            attribute: "Name",
+            value: String::new(),
+        });
+    }
+
+    Ok(ValidCard {
+        cat_id,
+        quantity,
+        sideboard,
+    })
+}
+
+fn parse_attr(
+    card_index: usize,
+    attribute: &'static str,
+    value: String,
+) -> Result<u32, TradeListError> {
+    value.parse().map_err(|_| TradeListError::InvalidAttribute {
+        card_index,
+        attribute,
+        value,
+    })
+}
+
+fn tag_name(e_tag: &BytesStart) -> String {
+    String::from_utf8_lossy(e_tag.name().as_ref()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+    use testresult::TestResult;
+
+    const DECK_HEADER: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<Deck xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <NetDeckID>0</NetDeckID>
+  <PreconstructedDeckID>0</PreconstructedDeckID>
+"#;
+
+    fn validate_str(contents: &str) -> Result<TradeListSummary, TradeListError> {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.child("test.dek");
+        std::fs::write(&path, contents).unwrap();
+        validate_full_trade_list(&path)
+    }
+
+    #[test]
+    fn test_validate_full_trade_list_5_cards() -> TestResult {
+        let summary = validate_full_trade_list(Path::new(
+            r"../test/test-data/mtgo/Full Trade List-small-5cards.dek",
+        ))?;
+        assert_eq!(
+            summary,
+            TradeListSummary {
+                unique_cards: 5,
+                total_quantity: 457,
+                event_tickets: 453
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_full_trade_list_500_cards() -> TestResult {
+        let summary = validate_full_trade_list(Path::new(
+            r"../test/test-data/mtgo/Full Trade List-small-500cards.dek",
+        ))?;
+        assert_eq!(summary.unique_cards, 500);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_wrong_root() {
+        let res = validate_str(
+            r#"<Collection><Cards CatID="1" Quantity="1" Name="Event Ticket" /></Collection>"#,
+        );
+        assert!(
+            matches!(res, Err(TradeListError::MissingDeckRoot(Some(root))) if root == "Collection")
+        );
+    }
+
+    #[test]
+    fn test_reject_garbage() {
+        let res = validate_str("This is not XML <<<");
+        assert!(res.is_err(), "{res:?}");
+    }
+
+    #[test]
+    fn test_reject_unexpected_element() {
+        let res = validate_str(&format!("{DECK_HEADER}<Card CatID=\"1\" />\n</Deck>"));
+        assert!(matches!(res, Err(TradeListError::UnexpectedElement(name)) if name == "Card"));
+    }
+
+    #[test]
+    fn test_reject_missing_and_invalid_attributes() {
+        let res = validate_str(&format!(
+            "{DECK_HEADER}<Cards CatID=\"1\" Name=\"Event Ticket\" />\n</Deck>"
+        ));
+        assert!(matches!(
+            res,
+            Err(TradeListError::MissingAttribute {
+                card_index: 0,
+                attribute: "Quantity"
+            })
+        ));
+
+        let res = validate_str(&format!(
+            "{DECK_HEADER}<Cards CatID=\"abc\" Quantity=\"1\" Name=\"Swamp\" />\n</Deck>"
+        ));
+        assert!(matches!(
+            res,
+            Err(TradeListError::InvalidAttribute {
+                attribute: "CatID",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_reject_quantity_overflow() {
+        let res = validate_str(&format!(
+            "{DECK_HEADER}<Cards CatID=\"235\" Quantity=\"4294967295\" Sideboard=\"false\" Name=\"Swamp\" />
+<Cards CatID=\"236\" Quantity=\"4294967295\" Sideboard=\"false\" Name=\"Island\" />\n</Deck>"
+        ));
+        assert!(matches!(
+            res,
+            Err(TradeListError::InvalidAttribute {
+                card_index: 1,
+                attribute: "Quantity",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_reject_empty_deck() {
+        let res = validate_str(&format!("{DECK_HEADER}</Deck>"));
+        assert!(matches!(res, Err(TradeListError::NoCards)));
+    }
+
+    #[test]
+    fn test_reject_deck_export() {
+        let res = validate_str(&format!(
+            "{DECK_HEADER}<Cards CatID=\"235\" Quantity=\"60\" Sideboard=\"false\" Name=\"Swamp\" Annotation=\"0\" />\n</Deck>"
+        ));
+        assert!(matches!(res, Err(TradeListError::DeckExport(_))));
+
+        let res = validate_str(&format!(
+            "{DECK_HEADER}<Cards CatID=\"1\" Quantity=\"20\" Sideboard=\"false\" Name=\"Event Ticket\" Annotation=\"0\" />\n\
+            <Cards CatID=\"235\" Quantity=\"15\" Sideboard=\"true\" Name=\"Swamp\" Annotation=\"0\" />\n</Deck>"
+        ));
+        assert!(matches!(res, Err(TradeListError::DeckExport(_))));
+    }
+}
@@ -89,7 +89,7 @@ pub fn test_collection_parse_small() -> TestResult {
     assert_eq!(timestamp.minute(), 27);
 
     // Copy of the most recent filestamp
-    let most_recent_ts = timestamp.clone();
+    let most_recent_ts = *timestamp;
 
     let colletion_from_f1: Collection = serde_json::from_str(&fs::read_to_string(&f1)?)?;
     let mut colletion_from_f2 = serde_json::from_str(&fs::read_to_string(&f2)?)?;
@@ -187,7 +187,7 @@ fn test_collection_parse_medium_prices() -> TestResult {
     let cards = collection.take_cards();
 
     // Check the first card is event tickets with expected values
-    let expect_tickets = cards.get(0).unwrap();
+    let expect_tickets = cards.first().unwrap();
     assert_eq!(expect_tickets.id, 1);
     assert_eq!(expect_tickets.name, "Event Ticket".into());
     assert_eq!(expect_tickets.goatbots_price, 1.0f32);
@@ -208,3 +208,59 @@ fn test_collection_parse_medium_prices() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+pub fn test_parse_full_cached_reparse() -> TestResult {
+    let temp_dir = temp_dir::TempDir::new()?;
+    fs::copy(
+        "../test/test-data/mtgogetter-out/fetch_log.toml",
+        temp_dir.child("fetch_log.toml"),
+    )?;
+    let price_hist_path = temp_dir.child("price-history.json");
+    fs::copy(
+        "../test/test-data/goatbots/price-hist-small-5cards.json",
+        &price_hist_path,
+    )?;
+    let parse = || {
+        mtgoparser::parse_full(
+            Path::new(r"../test/test-data/mtgo/Full Trade List-small-5cards.dek"),
+            Path::new("../test/test-data/scryfall/default-cards-small-5cards.json"),
+            Path::new("../test/test-data/goatbots/card-defs-small-5cards.json"),
+            &price_hist_path,
+            Some(temp_dir.path()),
+        )
+    };
+
+    let snapshots = || mtgoparser::util::collection_snapshots(temp_dir.path());
+
+    let cards = parse()?;
+    assert!(temp_dir
+        .child(mtgoparser::cache::PARSE_CACHE_FILENAME)
+        .exists());
+    // Make the snapshot older, so a new one cannot end up with the same name
+    let (snapshot, _) = snapshots()?.pop().expect("a snapshot is saved");
+    fs::rename(&snapshot, temp_dir.child("mtgo-cards_2023-11-05T152700Z"))?;
+    // Nothing changed, the cached collection is identical and no new snapshot is saved
+    assert_eq!(parse()?, cards);
+    assert_eq!(snapshots()?.len(), 1);
+
+    // Only the prices changed
+    fs::write(
+        &price_hist_path,
+        r#"{"112348": 0.5, "40516": 1.03, "31745": 0.37, "348": 419.99, "347": 244.99}"#,
+    )?;
+    let repriced = parse()?;
+    assert_eq!(snapshots()?.len(), 2);
+    let expected: Vec<_> = cards
+        .into_iter()
+        .map(|mut c| {
+            if c.id == 112348 {
+                c.goatbots_price = 0.5;
+            }
+            c
+        })
+        .collect();
+    assert_eq!(repriced, expected);
+
+    Ok(())
+}
@@ -9,19 +9,23 @@ use std::sync::OnceLock;
 
 pub mod date;
 pub mod new_update_all;
+pub mod price_archive;
+pub mod retention;
 mod util;
 mod zip_util;
 
-use zip_util::Archive;
-use zip_util::Archived;
-use zip_util::UnArchived;
-
 static MTGOGETTER_BIN: OnceLock<OsString> = OnceLock::new();
 static MTGOPARSER_BIN: OnceLock<OsString> = OnceLock::new();
 
+pub use mtgoparser::collection::Collection;
 pub use mtgoparser::mtgo_card::MtgoCard;
 pub use mtgoparser::mtgo_card::Rarity;
 pub use mtgoparser::parse_full;
+pub use mtgoparser::price_report;
+pub use mtgoparser::set_catalog;
+pub use mtgoparser::util::collection_snapshots;
+pub use mtgoparser::util::most_recent_collection_snapshot;
+pub use mtgoparser::xml::validate::{validate_full_trade_list, TradeListError, TradeListSummary};
 
 /// Returns the version of `MTGO Updater`
 pub fn mtgo_updater_version() -> &'static str {
@@ -92,20 +96,8 @@ pub fn zip_price_history(
         }
     }
 
-    // Create ZIP-file if it doesn't exist
-    let zip_file = Path::new(zip_file);
-    if zip_file.exists() {
-        let mut archive = Archive::<Archived>::init(zip_file);
-        // Add all JSON-files to the ZIP-file
-        archive.move_to_archive(json_files.iter().map(|p| p.as_path()))?;
-    } else {
-        let mut archive = Archive::<UnArchived>::new(zip_file);
-        // Add all JSON-files to the ZIP-file
-        json_files.into_iter().for_each(|f| archive.move_file(f));
-        // Create ZIP-file
-        archive.archive()?;
-    }
-
+    // Create ZIP-file if it doesn't exist, or add the JSON-files to it
+    zip_util::move_into_zip(Path::new(zip_file), &json_files)?;
     Ok(())
 }
 
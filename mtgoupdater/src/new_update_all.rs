@@ -1,9 +1,19 @@
-use std::{io, path::PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
-use mtgogetter::fetch_all;
+use chrono::Utc;
+use mtgogetter::{fetch_all_with_options, fetch_all_with_progress, FetchOptions, FetchStatus};
+pub use mtgogetter::{FetchPhase, FetchProgress, ProgressTracker};
+
+use crate::{price_archive::PriceArchive, retention};
 
 /// Runs a full update of all MTGO data and saves the output to the given directory
 ///
+/// A newly downloaded Goatbots price history is added to the [PriceArchive] of the directory, and the history in the
+/// directory is cleaned up if its [RetentionPolicy](retention::RetentionPolicy) is enabled.
+///
 /// # Arguments
 ///
 /// * `save_to_dir` - Path to the directory to save the output to
@@ -13,5 +23,72 @@ pub fn new_update_all(save_to_dir: &std::ffi::OsStr) -> Result<(), io::Error> {
         panic!("Save to dir does not exist");
     }
 
-    fetch_all(save_to_dir)
+    let statuses = fetch_all_with_options(&save_to_dir, FetchOptions::default())?;
+    archive_price_history(&save_to_dir, &statuses);
+    enforce_retention(&save_to_dir);
+    Ok(())
+}
+
+/// Like [new_update_all] but reports the [FetchProgress] of the update to `progress`
+///
+/// # Arguments
+///
+/// * `save_to_dir` - Path to the directory to save the output to
+/// * `progress` - Receives the phase changes and download progress of the update
+///
+/// # Errors
+///
+/// Returns an error if the directory doesn't exist, any of the downloads fail, or the data cannot be stored
+pub fn new_update_all_with_progress(
+    save_to_dir: &std::ffi::OsStr,
+    progress: &(dyn Fn(FetchProgress) + Sync),
+) -> Result<(), io::Error> {
+    let save_to_dir = PathBuf::from(save_to_dir);
+    if !save_to_dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Save to dir {save_to_dir:?} does not exist"),
+        ));
+    }
+
+    let statuses = fetch_all_with_progress(&save_to_dir, FetchOptions::default(), progress)?;
+    archive_price_history(&save_to_dir, &statuses);
+    enforce_retention(&save_to_dir);
+    Ok(())
+}
+
+/// Adds the Goatbots price history to the [PriceArchive] in `save_to_dir` if it was downloaded, and compresses the
+/// archived files of the previous months
+///
+/// A failure is only logged, the downloaded prices are stored either way.
+fn archive_price_history(save_to_dir: &Path, statuses: &[FetchStatus]) {
+    let Some(price_history) = statuses
+        .iter()
+        .find(|s| s.downloaded && s.data == FetchStatus::GOATBOTS_PRICE_HISTORY)
+    else {
+        return;
+    };
+    let archive = PriceArchive::new(save_to_dir);
+    let today = Utc::now().date_naive();
+    if let Err(e) = archive
+        .add(&price_history.path, today)
+        .and_then(|_| archive.compress(today))
+    {
+        log::warn!("Failed to archive the price history in {save_to_dir:?}: {e}");
+    }
+}
+
+/// Cleans up the history in `save_to_dir` if its retention policy is enabled
+///
+/// A failed cleanup is only logged, it shouldn't stop the update from being used.
+fn enforce_retention(save_to_dir: &Path) {
+    match retention::enforce_retention(save_to_dir, Utc::now()) {
+        Ok(Some(plan)) => log::info!(
+            "Cleaned up {} files of history, freed {} bytes",
+            plan.actions().len(),
+            plan.freed_bytes()
+        ),
+        Ok(None) => (),
+        Err(e) => log::warn!("Failed to clean up the history in {save_to_dir:?}: {e}"),
+    }
 }
@@ -0,0 +1,330 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::zip_util;
+
+/// Name of the directory in the appdata directory where the price history files are archived
+pub const PRICE_ARCHIVE_DIR: &str = "price-archive";
+/// Prefix of the archived price history files (pattern `price-history_YYYY-MM-DD.json`) and the monthly ZIP-files they
+/// are compressed into (pattern `price-history_YYYY-MM.zip`)
+const PRICE_HISTORY_PREFIX: &str = "price-history_";
+const DAY_FORMAT: &str = "%Y-%m-%d";
+const MONTH_FORMAT: &str = "%Y-%m";
+
+/// Prices keyed by MTGO ID
+pub type Prices = HashMap<u32, f32>;
+
+/// Archive of the daily Goatbots price history files
+///
+/// Each downloaded price history is stored under the date it was downloaded. Once a month has passed, the files of
+/// that month are compressed into a single ZIP-file with [PriceArchive::compress].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceArchive {
+    dir: PathBuf,
+}
+
+impl PriceArchive {
+    /// Instantiates the archive stored in [PRICE_ARCHIVE_DIR] in the given appdata directory, the directory is not
+    /// created until a file is archived
+    pub fn new(appdata_dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: appdata_dir.as_ref().join(PRICE_ARCHIVE_DIR),
+        }
+    }
+
+    /// Returns the Path to the archive directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Copies the given price history file into the archive under the given date, replacing any file archived earlier
+    /// that day
+    ///
+    /// # Returns
+    ///
+    /// The path of the archived file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive directory cannot be created or the file cannot be copied
+    pub fn add(&self, price_history: &Path, date: NaiveDate) -> Result<PathBuf, io::Error> {
+        fs::create_dir_all(&self.dir)?;
+        let dst = self.day_file(date);
+        fs::copy(price_history, &dst)?;
+        Ok(dst)
+    }
+
+    /// Moves the files of the months before the month of `today` into a ZIP-file per month
+    ///
+    /// If a ZIP-file for a month already exists, the files are added to it.
+    ///
+    /// # Returns
+    ///
+    /// The number of files that were compressed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive directory cannot be read or a ZIP-file cannot be created or added to
+    pub fn compress(&self, today: NaiveDate) -> Result<usize, io::Error> {
+        // The files to compress keyed by the ZIP-file of their month
+        let mut months: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        for (path, date) in self.day_files()? {
+            if (date.year(), date.month()) < (today.year(), today.month()) {
+                months.entry(self.month_file(date)).or_default().push(path);
+            }
+        }
+
+        let mut compressed = 0;
+        for (zip_file, files) in months {
+            zip_util::move_into_zip(&zip_file, &files)?;
+            compressed += files.len();
+        }
+        Ok(compressed)
+    }
+
+    /// Returns the dates with archived prices, sorted oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive directory or one of the ZIP-files cannot be read
+    pub fn dates(&self) -> Result<Vec<NaiveDate>, io::Error> {
+        let mut dates: Vec<NaiveDate> = self.day_files()?.into_iter().map(|(_, d)| d).collect();
+        for zip_file in self.month_files()? {
+            let zip = zip::ZipArchive::new(fs::File::open(zip_file)?)?;
+            dates.extend(zip.file_names().filter_map(parse_day_file_name));
+        }
+        dates.sort_unstable();
+        dates.dedup();
+        Ok(dates)
+    }
+
+    /// Returns all prices archived on the given date, or [None] if no prices were archived that day
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archived file cannot be read or parsed
+    pub fn prices(&self, date: NaiveDate) -> Result<Option<Prices>, io::Error> {
+        let day_file = self.day_file(date);
+        let json = if day_file.exists() {
+            fs::read(day_file)?
+        } else {
+            let zip_file = self.month_file(date);
+            if !zip_file.exists() {
+                return Ok(None);
+            }
+            let mut zip = zip::ZipArchive::new(fs::File::open(zip_file)?)?;
+            let mut file = match zip.by_name(&day_file_name(date)) {
+                Ok(file) => file,
+                Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            read_zip_entry(&mut file)?
+        };
+        Ok(Some(serde_json::from_slice(&json)?))
+    }
+
+    /// Returns the price of the card with the given MTGO ID on the given date, owned or not
+    ///
+    /// Returns [None] if no prices were archived that day or the card had no price.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archived file cannot be read or parsed
+    pub fn price(&self, id: u32, date: NaiveDate) -> Result<Option<f32>, io::Error> {
+        Ok(self
+            .prices(date)?
+            .and_then(|prices| prices.get(&id).copied()))
+    }
+
+    /// Returns the price of the card with the given MTGO ID on every archived date it had a price, sorted oldest first
+    ///
+    /// Every archived file is parsed once, and each ZIP-file is opened once and read entry by entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an archived file cannot be read or parsed
+    pub fn price_history(&self, id: u32) -> Result<Vec<(NaiveDate, f32)>, io::Error> {
+        let price_of = |json: &[u8]| -> Result<Option<f32>, io::Error> {
+            let prices: Prices = serde_json::from_slice(json)?;
+            Ok(prices.get(&id).copied())
+        };
+        let mut history = Vec::new();
+        let day_files = self.day_files()?;
+        for (path, date) in &day_files {
+            if let Some(price) = price_of(&fs::read(path)?)? {
+                history.push((*date, price));
+            }
+        }
+        for zip_file in self.month_files()? {
+            let mut zip = zip::ZipArchive::new(fs::File::open(zip_file)?)?;
+            for i in 0..zip.len() {
+                let mut file = zip.by_index(i)?;
+                let Some(date) = parse_day_file_name(file.name()) else {
+                    continue;
+                };
+                // An uncompressed file of the same day takes precedence, same as in `prices`
+                if day_files.iter().any(|(_, d)| *d == date) {
+                    continue;
+                }
+                if let Some(price) = price_of(&read_zip_entry(&mut file)?)? {
+                    history.push((date, price));
+                }
+            }
+        }
+        history.sort_unstable_by_key(|(date, _)| *date);
+        Ok(history)
+    }
+
+    fn day_file(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(day_file_name(date))
+    }
+
+    fn month_file(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(format!(
+            "{PRICE_HISTORY_PREFIX}{}.zip",
+            date.format(MONTH_FORMAT)
+        ))
+    }
+
+    /// The uncompressed files in the archive and their dates
+    fn day_files(&self) -> Result<Vec<(PathBuf, NaiveDate)>, io::Error> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter_map(|p| {
+                let date = parse_day_file_name(&p.file_name()?.to_string_lossy())?;
+                Some((p, date))
+            })
+            .collect())
+    }
+
+    /// The monthly ZIP-files in the archive
+    fn month_files(&self) -> Result<Vec<PathBuf>, io::Error> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|p| {
+                p.file_name().is_some_and(|n| {
+                    let n = n.to_string_lossy();
+                    n.starts_with(PRICE_HISTORY_PREFIX) && n.ends_with(".zip")
+                })
+            })
+            .collect())
+    }
+
+    fn entries(&self) -> Result<Vec<PathBuf>, io::Error> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_dir(&self.dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect()
+    }
+}
+
+fn read_zip_entry(file: &mut zip::read::ZipFile) -> Result<Vec<u8>, io::Error> {
+    let mut json = Vec::with_capacity(file.size() as usize);
+    io::copy(file, &mut json)?;
+    Ok(json)
+}
+
+fn day_file_name(date: NaiveDate) -> String {
+    format!("{PRICE_HISTORY_PREFIX}{}.json", date.format(DAY_FORMAT))
+}
+
+/// Parses the date of a file name of pattern `price-history_YYYY-MM-DD.json`
+pub(crate) fn parse_day_file_name(name: &str) -> Option<NaiveDate> {
+    let date = name
+        .strip_prefix(PRICE_HISTORY_PREFIX)?
+        .strip_suffix(".json")?;
+    NaiveDate::parse_from_str(date, DAY_FORMAT).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_day_file_name() {
+        assert_eq!(
+            parse_day_file_name("price-history_2024-05-19.json"),
+            Some(date(2024, 5, 19))
+        );
+        assert_eq!(parse_day_file_name("price-history_2024-05.zip"), None);
+        assert_eq!(parse_day_file_name("price-history.json"), None);
+    }
+
+    #[test]
+    fn test_price_archive() -> Result<(), Box<dyn std::error::Error>> {
+        let appdata = TempDir::new()?;
+        let archive = PriceArchive::new(appdata.path());
+        let price_history = appdata.child("price-history.json");
+        assert_eq!(archive.dates()?, []);
+
+        for (day, price) in [
+            (date(2024, 4, 29), "1.5"),
+            (date(2024, 4, 30), "2.5"),
+            (date(2024, 5, 1), "3.5"),
+        ] {
+            fs::write(&price_history, format!(r#"{{"347": {price}, "348": 0.1}}"#))?;
+            archive.add(&price_history, day)?;
+        }
+
+        // Only the files of April are compressed
+        assert_eq!(archive.compress(date(2024, 5, 2))?, 2);
+        assert!(archive.dir().join("price-history_2024-04.zip").exists());
+        assert!(!archive.dir().join("price-history_2024-04-29.json").exists());
+        assert!(archive.dir().join("price-history_2024-05-01.json").exists());
+        assert_eq!(archive.compress(date(2024, 5, 2))?, 0);
+
+        assert_eq!(
+            archive.dates()?,
+            [date(2024, 4, 29), date(2024, 4, 30), date(2024, 5, 1)]
+        );
+        assert_eq!(archive.price(347, date(2024, 4, 30))?, Some(2.5));
+        assert_eq!(archive.price(347, date(2024, 5, 1))?, Some(3.5));
+        assert_eq!(archive.price(999, date(2024, 5, 1))?, None);
+        assert_eq!(archive.price(347, date(2024, 4, 1))?, None);
+        assert_eq!(archive.price(347, date(2023, 1, 1))?, None);
+        assert_eq!(
+            archive.price_history(347)?,
+            [
+                (date(2024, 4, 29), 1.5),
+                (date(2024, 4, 30), 2.5),
+                (date(2024, 5, 1), 3.5)
+            ]
+        );
+
+        // A file archived late for a compressed month is added to the existing ZIP-file
+        fs::write(&price_history, r#"{"347": 0.5}"#)?;
+        archive.add(&price_history, date(2024, 4, 1))?;
+        assert_eq!(archive.compress(date(2024, 5, 2))?, 1);
+        assert_eq!(archive.price(347, date(2024, 4, 1))?, Some(0.5));
+        assert_eq!(archive.price(347, date(2024, 4, 29))?, Some(1.5));
+
+        // A file archived again for a compressed day replaces the compressed one
+        fs::write(&price_history, r#"{"347": 9.5}"#)?;
+        archive.add(&price_history, date(2024, 4, 29))?;
+        assert_eq!(
+            archive.price_history(347)?,
+            [
+                (date(2024, 4, 1), 0.5),
+                (date(2024, 4, 29), 9.5),
+                (date(2024, 4, 30), 2.5),
+                (date(2024, 5, 1), 3.5)
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,685 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use mtgoparser::util::COLLECTION_SNAPSHOT_PREFIX;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    price_archive::{self, PRICE_ARCHIVE_DIR},
+    zip_util::{Archive, Archived},
+};
+
+/// Name of the file the [RetentionPolicy] is read from in the appdata directory
+pub const RETENTION_FILENAME: &str = "retention.toml";
+/// Prefix of the log files rotated by the GUI, the current log file is named `mcm_log_rCURRENT.log`
+const ROTATED_LOG_PREFIX: &str = "mcm_log_r";
+const CURRENT_LOG_NAME: &str = "mcm_log_rCURRENT.log";
+
+/// How long the history in the appdata directory is kept, also known as `retention.toml`
+///
+/// The history is the collection snapshots and the rotated log files, and if [RetentionPolicy::thin_price_archive] is
+/// set also the archived Goatbots price history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// The policy is enforced after each update, otherwise only on demand e.g. by `mtgocli cleanup`
+    pub enabled: bool,
+    /// All collection snapshots of this many days are kept, older ones are thinned to the last snapshot of each day.
+    /// Rotated log files are removed when they are older.
+    pub keep_raw_days: u32,
+    /// History older than this many days is thinned to the last entry of each week
+    pub weekly_after_days: u32,
+    /// Also thin the archived Goatbots price history, and remove the oldest of it to stay under the cap. It's kept as it is
+    /// by default, as the price archive is meant to have the prices of every archived day.
+    pub thin_price_archive: bool,
+    /// Cap on the total size of the appdata directory in MiB, the oldest history is removed to stay under it.
+    /// No cap if it's not set.
+    pub max_total_mib: Option<u64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep_raw_days: 30,
+            weekly_after_days: 365,
+            thin_price_archive: false,
+            max_total_mib: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Load the policy from `retention.toml` in the given directory, or the default policy if it doesn't exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Self, io::Error> {
+        match fs::read_to_string(dir.join(RETENTION_FILENAME)) {
+            Ok(contents) => {
+                toml::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The bucket of history entries an entry from `timestamp` is thinned to at `now`, the last entry of each bucket
+    /// is kept
+    fn bucket(&self, timestamp: DateTime<Utc>, now: DateTime<Utc>) -> Bucket {
+        if age_days(timestamp, now) > i64::from(self.weekly_after_days) {
+            let week = timestamp.iso_week();
+            Bucket::Week(week.year(), week.week())
+        } else if !self.is_raw(timestamp, now) {
+            Bucket::Day(timestamp.date_naive())
+        } else {
+            Bucket::Raw(timestamp)
+        }
+    }
+
+    /// Returns true if history from `timestamp` is within [RetentionPolicy::keep_raw_days] at `now`
+    fn is_raw(&self, timestamp: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        age_days(timestamp, now) <= i64::from(self.keep_raw_days)
+    }
+}
+
+/// The number of calendar days from `timestamp` to `now`
+fn age_days(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (now.date_naive() - timestamp.date_naive()).num_days()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Bucket {
+    Raw(DateTime<Utc>),
+    Day(chrono::NaiveDate),
+    Week(i32, u32),
+}
+
+/// Where a history entry is stored
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Location {
+    File(PathBuf),
+    /// A file in a ZIP-file
+    Zipped {
+        zip: PathBuf,
+        name: String,
+    },
+}
+
+/// Why a history entry is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// Another entry is kept for the same day
+    ThinnedToDaily,
+    /// Another entry is kept for the same week
+    ThinnedToWeekly,
+    /// A rotated log file older than [RetentionPolicy::keep_raw_days]
+    Expired,
+    /// The oldest history is removed to stay under [RetentionPolicy::max_total_mib]
+    OverDiskCap,
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Reason::ThinnedToDaily => "thinned to daily",
+            Reason::ThinnedToWeekly => "thinned to weekly",
+            Reason::Expired => "expired",
+            Reason::OverDiskCap => "over disk usage cap",
+        };
+        write!(f, "{reason}")
+    }
+}
+
+/// A file or an entry of a ZIP-file that is removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionAction {
+    pub location: Location,
+    /// The size of the file, or the compressed size of the entry of a ZIP-file
+    pub bytes: u64,
+    pub reason: Reason,
+}
+
+/// The changes that enforce a [RetentionPolicy], see [RetentionPlan::new]
+///
+/// Doesn't change anything until it's [applied](RetentionPlan::apply), so it doubles as a dry-run report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPlan {
+    actions: Vec<RetentionAction>,
+    total_bytes: u64,
+}
+
+/// An entry of the history
+#[derive(Debug, Clone)]
+struct Entry {
+    location: Location,
+    timestamp: DateTime<Utc>,
+    bytes: u64,
+}
+
+impl RetentionPlan {
+    /// Plan the changes to the appdata directory `dir` that enforce the `policy` at `now`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or one of the ZIP-files cannot be read
+    pub fn new(
+        dir: &Path,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<Self, io::Error> {
+        let snapshots = history_entries(dir, false, parse_snapshot_name)?;
+        let prices = if policy.thin_price_archive {
+            let prices = history_entries(&dir.join(PRICE_ARCHIVE_DIR), true, |name| {
+                price_archive::parse_day_file_name(name)
+                    .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+            })?;
+            without_superseded(prices)
+        } else {
+            Vec::new()
+        };
+
+        let mut actions = Vec::new();
+        // The entries that are kept, in case the oldest have to be removed to stay under the disk usage cap
+        let mut kept: Vec<Entry> = Vec::new();
+        for history in [snapshots, prices] {
+            let (thinned, mut rest) = thin(history, policy, now);
+            actions.extend(thinned);
+            // The most recent entry of each kind of history is never removed
+            rest.pop();
+            kept.extend(rest);
+        }
+
+        for (path, modified) in rotated_logs(dir)? {
+            if !policy.is_raw(modified, now) {
+                actions.push(RetentionAction {
+                    bytes: fs::metadata(&path)?.len(),
+                    location: Location::File(path),
+                    reason: Reason::Expired,
+                });
+            }
+        }
+
+        let total_bytes = dir_size(dir)?;
+        if let Some(max_total_mib) = policy.max_total_mib {
+            let max_bytes = max_total_mib.saturating_mul(1024 * 1024);
+            let mut remaining = total_bytes.saturating_sub(freed(&actions));
+            kept.sort_by_key(|entry| entry.timestamp);
+            let over_cap = kept
+                .iter()
+                .take_while(|entry| {
+                    let over = remaining > max_bytes;
+                    remaining = remaining.saturating_sub(entry.bytes);
+                    over
+                })
+                .count();
+            for entry in kept.drain(..over_cap) {
+                actions.push(RetentionAction {
+                    location: entry.location,
+                    bytes: entry.bytes,
+                    reason: Reason::OverDiskCap,
+                });
+            }
+        }
+
+        Ok(Self {
+            actions,
+            total_bytes,
+        })
+    }
+
+    pub fn actions(&self) -> &[RetentionAction] {
+        &self.actions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// The size of the appdata directory before the plan is applied
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// The number of bytes the removals free
+    pub fn freed_bytes(&self) -> u64 {
+        freed(&self.actions)
+    }
+
+    /// Remove the history as planned
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be removed, or a ZIP-file cannot be rewritten
+    pub fn apply(&self) -> Result<(), io::Error> {
+        let mut zipped_removals: BTreeMap<&Path, Vec<&str>> = BTreeMap::new();
+        for action in &self.actions {
+            match &action.location {
+                Location::File(path) => fs::remove_file(path)?,
+                Location::Zipped { zip, name } => {
+                    zipped_removals.entry(zip).or_default().push(name)
+                }
+            }
+        }
+        for (zip, names) in zipped_removals {
+            Archive::<Archived>::init(zip).remove_from_archive(&names)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plan and apply the [RetentionPolicy] of the appdata directory `dir` at `now` if it's [enabled](RetentionPolicy::enabled)
+///
+/// # Returns
+///
+/// The applied [RetentionPlan], or [None] if the policy isn't enabled
+///
+/// # Errors
+///
+/// Returns an error if the policy cannot be loaded, see [RetentionPlan::new] and [RetentionPlan::apply]
+pub fn enforce_retention(
+    dir: &Path,
+    now: DateTime<Utc>,
+) -> Result<Option<RetentionPlan>, io::Error> {
+    let policy = RetentionPolicy::load(dir)?;
+    if !policy.enabled {
+        return Ok(None);
+    }
+    let plan = RetentionPlan::new(dir, &policy, now)?;
+    plan.apply()?;
+    Ok(Some(plan))
+}
+
+fn freed(actions: &[RetentionAction]) -> u64 {
+    actions.iter().map(|a| a.bytes).sum()
+}
+
+/// Split the `history` into the removals that thin it according to the `policy`, and the entries that are kept
+/// sorted oldest first
+fn thin(
+    mut history: Vec<Entry>,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> (Vec<RetentionAction>, Vec<Entry>) {
+    history.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.location.cmp(&b.location))
+    });
+    // The last entry of each bucket is kept
+    let last_in_bucket: HashMap<Bucket, usize> = history
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (policy.bucket(e.timestamp, now), i))
+        .collect();
+
+    let mut removals = Vec::new();
+    let mut kept = Vec::new();
+    for (i, entry) in history.into_iter().enumerate() {
+        let bucket = policy.bucket(entry.timestamp, now);
+        if last_in_bucket[&bucket] == i {
+            kept.push(entry);
+            continue;
+        }
+        let reason = match bucket {
+            Bucket::Week(..) => Reason::ThinnedToWeekly,
+            Bucket::Day(_) => Reason::ThinnedToDaily,
+            Bucket::Raw(_) => unreachable!("raw entries have a bucket of their own"),
+        };
+        removals.push(RetentionAction {
+            location: entry.location,
+            bytes: entry.bytes,
+            reason,
+        });
+    }
+    (removals, kept)
+}
+
+/// Leave out the entries of ZIP-files of days that also have a loose file, e.g. after an interrupted compression
+///
+/// The loose file takes precedence when the prices are read, see [PriceArchive](crate::price_archive::PriceArchive).
+fn without_superseded(mut entries: Vec<Entry>) -> Vec<Entry> {
+    let loose: HashSet<DateTime<Utc>> = entries
+        .iter()
+        .filter(|e| matches!(e.location, Location::File(_)))
+        .map(|e| e.timestamp)
+        .collect();
+    entries.retain(|e| matches!(e.location, Location::File(_)) || !loose.contains(&e.timestamp));
+    entries
+}
+
+/// The files in `dir` whose name is parsed by `parse`, and with `zipped` also the files in the ZIP-files in `dir`
+fn history_entries(
+    dir: &Path,
+    zipped: bool,
+    parse: impl Fn(&str) -> Option<DateTime<Utc>>,
+) -> Result<Vec<Entry>, io::Error> {
+    let mut entries = Vec::new();
+    if !dir.exists() {
+        return Ok(entries);
+    }
+    for dir_entry in fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        if let Some(timestamp) = parse(&name) {
+            entries.push(Entry {
+                bytes: fs::metadata(&path)?.len(),
+                location: Location::File(path),
+                timestamp,
+            });
+        } else if zipped && name.ends_with(".zip") {
+            let mut zip = zip::ZipArchive::new(fs::File::open(&path)?)?;
+            for i in 0..zip.len() {
+                let file = zip.by_index_raw(i)?;
+                if let Some(timestamp) = parse(file.name()) {
+                    entries.push(Entry {
+                        location: Location::Zipped {
+                            zip: path.clone(),
+                            name: file.name().to_owned(),
+                        },
+                        timestamp,
+                        bytes: file.compressed_size(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Parses the timestamp of a collection snapshot name of pattern `mtgo-cards_YYYY-MM-DDThhmmssZ`
+fn parse_snapshot_name(name: &str) -> Option<DateTime<Utc>> {
+    let timestamp = name.strip_prefix(COLLECTION_SNAPSHOT_PREFIX)?;
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H%M%SZ")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// The log files rotated by the GUI and when they were last modified
+fn rotated_logs(dir: &Path) -> Result<Vec<(PathBuf, DateTime<Utc>)>, io::Error> {
+    let mut logs = Vec::new();
+    for dir_entry in fs::read_dir(dir)? {
+        let dir_entry = dir_entry?;
+        let name = dir_entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(ROTATED_LOG_PREFIX) && name != CURRENT_LOG_NAME {
+            logs.push((dir_entry.path(), dir_entry.metadata()?.modified()?.into()));
+        }
+    }
+    Ok(logs)
+}
+
+/// The total size of the files in `dir` and its subdirectories
+fn dir_size(dir: &Path) -> Result<u64, io::Error> {
+    let mut size = 0;
+    for dir_entry in fs::read_dir(dir)? {
+        let dir_entry = dir_entry?;
+        let metadata = dir_entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&dir_entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_archive::PriceArchive;
+    use chrono::{Duration, NaiveDate};
+    use pretty_assertions::assert_eq;
+    use temp_dir::TempDir;
+
+    fn now() -> DateTime<Utc> {
+        "2024-06-20T12:00:00Z".parse().unwrap()
+    }
+
+    fn write_snapshot(dir: &Path, age: Duration) -> PathBuf {
+        let name = format!(
+            "{COLLECTION_SNAPSHOT_PREFIX}{}",
+            (now() - age).format("%Y-%m-%dT%H%M%SZ")
+        );
+        let path = dir.join(name);
+        fs::write(&path, "[]").unwrap();
+        path
+    }
+
+    fn removed(plan: &RetentionPlan) -> Vec<(String, Reason)> {
+        plan.actions()
+            .iter()
+            .map(|a| match &a.location {
+                Location::File(path) => (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    a.reason,
+                ),
+                Location::Zipped { name, .. } => (name.clone(), a.reason),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_snapshot_name() {
+        assert_eq!(
+            parse_snapshot_name("mtgo-cards_2023-11-05T152700Z"),
+            Some("2023-11-05T15:27:00Z".parse().unwrap())
+        );
+        assert_eq!(parse_snapshot_name("mtgo-cards_2023-11.zip"), None);
+        assert_eq!(parse_snapshot_name("card-definitions.json"), None);
+    }
+
+    #[test]
+    fn test_load_policy() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        assert_eq!(
+            RetentionPolicy::load(dir.path())?,
+            RetentionPolicy::default()
+        );
+        fs::write(
+            dir.child(RETENTION_FILENAME),
+            "keep_raw_days = 7\nmax_total_mib = 500\n",
+        )?;
+        let policy = RetentionPolicy::load(dir.path())?;
+        assert!(!policy.enabled);
+        assert_eq!(policy.keep_raw_days, 7);
+        assert_eq!(policy.weekly_after_days, 365);
+        assert!(!policy.thin_price_archive);
+        assert_eq!(policy.max_total_mib, Some(500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshots_are_thinned() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let policy = RetentionPolicy::default();
+        let name = |path: &Path| path.file_name().unwrap().to_string_lossy().into_owned();
+        let recent = write_snapshot(dir.path(), Duration::days(2));
+        let recent_same_day = write_snapshot(dir.path(), Duration::days(2) + Duration::hours(1));
+        let old = write_snapshot(dir.path(), Duration::days(40));
+        let old_same_day = write_snapshot(dir.path(), Duration::days(40) + Duration::hours(1));
+        let older = write_snapshot(dir.path(), Duration::days(400));
+        let older_same_week = write_snapshot(dir.path(), Duration::days(401));
+
+        let plan = RetentionPlan::new(dir.path(), &policy, now())?;
+        assert_eq!(
+            removed(&plan),
+            [
+                (name(&older_same_week), Reason::ThinnedToWeekly),
+                (name(&old_same_day), Reason::ThinnedToDaily),
+            ]
+        );
+        plan.apply()?;
+        // The remaining snapshots are left where the collection history is read from
+        let remaining: Vec<_> = mtgoparser::util::collection_snapshots(dir.path())?
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(
+            remaining,
+            [older, old, recent_same_day.clone(), recent.clone()]
+        );
+
+        // The snapshots are thinned further as they age
+        let later = now() + Duration::days(400);
+        let plan = RetentionPlan::new(dir.path(), &policy, later)?;
+        assert_eq!(
+            removed(&plan),
+            [(name(&recent_same_day), Reason::ThinnedToWeekly)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_disk_cap_removes_oldest_history() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let archive = PriceArchive::new(dir.path());
+        let prices = dir.child("price-history.json");
+        fs::write(&prices, "{\"347\": 244.99}")?;
+        let today = now().date_naive();
+        for days in [1, 2, 3] {
+            archive.add(&prices, today - Duration::days(days))?;
+        }
+        archive.add(&prices, NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())?;
+        archive.compress(today)?;
+        let snapshot = write_snapshot(dir.path(), Duration::days(1));
+        // Large enough that the cap can only be met by removing everything but the most recent history
+        fs::write(&snapshot, vec![b' '; 2 * 1024 * 1024])?;
+        let old_snapshot = write_snapshot(dir.path(), Duration::days(5));
+        fs::write(&old_snapshot, vec![b' '; 1024 * 1024])?;
+
+        let old_snapshot_name = old_snapshot
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+
+        // The price archive is left out by default
+        let policy = RetentionPolicy {
+            max_total_mib: Some(2),
+            ..Default::default()
+        };
+        let plan = RetentionPlan::new(dir.path(), &policy, now())?;
+        assert_eq!(
+            removed(&plan),
+            [(old_snapshot_name.clone(), Reason::OverDiskCap)]
+        );
+
+        let policy = RetentionPolicy {
+            thin_price_archive: true,
+            ..policy
+        };
+        let plan = RetentionPlan::new(dir.path(), &policy, now())?;
+        assert!(plan.total_bytes() > 3 * 1024 * 1024);
+        assert_eq!(
+            removed(&plan),
+            [
+                (
+                    "price-history_2024-05-01.json".to_owned(),
+                    Reason::OverDiskCap
+                ),
+                (old_snapshot_name, Reason::OverDiskCap),
+                (
+                    "price-history_2024-06-17.json".to_owned(),
+                    Reason::OverDiskCap
+                ),
+                (
+                    "price-history_2024-06-18.json".to_owned(),
+                    Reason::OverDiskCap
+                ),
+            ]
+        );
+        plan.apply()?;
+        assert!(snapshot.exists());
+        assert_eq!(archive.dates()?, [today - Duration::days(1)]);
+        // The emptied monthly ZIP-file is removed
+        assert!(!archive.dir().join("price-history_2024-05.zip").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_day_both_loose_and_zipped() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let archive = PriceArchive::new(dir.path());
+        let prices = dir.child("price-history.json");
+        fs::write(&prices, "{\"347\": 244.99}")?;
+        let day = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        archive.add(&prices, day)?;
+        archive.add(&prices, day + Duration::days(1))?;
+        archive.compress(now().date_naive())?;
+        // The day is archived again after its month was compressed
+        archive.add(&prices, day)?;
+
+        let policy = RetentionPolicy {
+            thin_price_archive: true,
+            keep_raw_days: 60,
+            ..Default::default()
+        };
+        assert!(RetentionPlan::new(dir.path(), &policy, now())?.is_empty());
+
+        // Once thinned, the loose file is removed and the copy in the ZIP-file is left as it is
+        let policy = RetentionPolicy {
+            keep_raw_days: 7,
+            weekly_after_days: 14,
+            ..policy
+        };
+        let plan = RetentionPlan::new(dir.path(), &policy, now())?;
+        assert_eq!(
+            plan.actions(),
+            [RetentionAction {
+                location: Location::File(archive.dir().join("price-history_2024-05-10.json")),
+                bytes: fs::metadata(&prices)?.len(),
+                reason: Reason::ThinnedToWeekly,
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_enforce_retention_is_opt_in() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let rotated_log = dir.child("mcm_log_r2024-06-01_10-00-00.log");
+        fs::write(&rotated_log, "rotated")?;
+        let later = Utc::now() + Duration::days(31);
+
+        assert_eq!(enforce_retention(dir.path(), later)?, None);
+        assert!(rotated_log.exists());
+
+        fs::write(dir.child(RETENTION_FILENAME), "enabled = true\n")?;
+        let plan = enforce_retention(dir.path(), later)?.expect("policy is enabled");
+        assert_eq!(plan.actions().len(), 1);
+        assert!(!rotated_log.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rotated_logs_expire() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        fs::write(dir.child(CURRENT_LOG_NAME), "current")?;
+        fs::write(dir.child("mcm_log_r2024-06-01_10-00-00.log"), "rotated")?;
+
+        let plan = RetentionPlan::new(dir.path(), &RetentionPolicy::default(), Utc::now())?;
+        assert!(plan.is_empty());
+        let later = Utc::now() + Duration::days(31);
+        let plan = RetentionPlan::new(dir.path(), &RetentionPolicy::default(), later)?;
+        assert_eq!(
+            removed(&plan),
+            [(
+                "mcm_log_r2024-06-01_10-00-00.log".to_owned(),
+                Reason::Expired
+            )]
+        );
+        assert_eq!(plan.freed_bytes(), 7);
+        plan.apply()?;
+        assert!(dir.child(CURRENT_LOG_NAME).exists());
+        assert!(!dir.child("mcm_log_r2024-06-01_10-00-00.log").exists());
+        Ok(())
+    }
+}
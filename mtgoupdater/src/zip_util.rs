@@ -131,18 +131,25 @@ impl Archive<Archived> {
         &self.location
     }
 
+    /// The path of the temporary archive the archive is rewritten to, named after the archive so archives in the same
+    /// directory don't share it
+    fn temp_location(&self) -> PathBuf {
+        let mut name = self.location.file_name().unwrap_or_default().to_owned();
+        name.push(".tmp");
+        self.location.with_file_name(name)
+    }
+
     /// Adds the given files to the archive following the steps:
     /// 1. Add a new temporary archive at the same location as the existing archive
     /// 2. Copy the already compressed files from the existing archive to the temporary archive
     /// 3. Add the new files to the temporary archive
-    /// 4. Delete the existing archive
-    /// 5. Rename the temporary archive to the the name of the original archive
+    /// 4. Rename the temporary archive over the existing archive
     pub fn add_to_archive<'f, F>(&mut self, new_files: F) -> Result<(), io::Error>
     where
         F: IntoIterator<Item = &'f Path>,
     {
         // 1. Add a new temporary archive at the same location as the existing archive
-        let temp_archive_path = &self.location.with_file_name("temp.zip");
+        let temp_archive_path = &self.temp_location();
         let temp_archive = fs::File::create(temp_archive_path)?;
 
         let mut new_archive = zip::ZipWriter::new(temp_archive);
@@ -184,15 +191,42 @@ impl Archive<Archived> {
 
         new_archive.finish()?;
 
-        // 4. Delete the existing archive
-        fs::remove_file(&self.location)?;
-
-        // 5. Rename the temporary archive to the existing archive
+        // 4. Rename the temporary archive over the existing archive, which replaces it in one step
         fs::rename(temp_archive_path, &self.location)?;
 
         Ok(())
     }
 
+    /// Removes the files with the given names from the archive, and the archive itself if no files are left
+    ///
+    /// # Returns
+    ///
+    /// The number of files left in the archive
+    pub fn remove_from_archive(&mut self, names: &[&str]) -> Result<usize, io::Error> {
+        let temp_archive_path = &self.temp_location();
+        let mut new_archive = zip::ZipWriter::new(fs::File::create(temp_archive_path)?);
+        let mut existing_archive = zip::ZipArchive::new(fs::File::open(&self.location)?)?;
+
+        let mut kept = 0;
+        for i in 0..existing_archive.len() {
+            let file = existing_archive.by_index(i)?;
+            if !names.contains(&file.name()) {
+                new_archive.raw_copy_file(file)?;
+                kept += 1;
+            }
+        }
+        new_archive.finish()?;
+
+        if kept == 0 {
+            fs::remove_file(temp_archive_path)?;
+            fs::remove_file(&self.location)?;
+        } else {
+            // Replaces the existing archive in one step, so it's never lost if the rewrite is interrupted
+            fs::rename(temp_archive_path, &self.location)?;
+        }
+        Ok(kept)
+    }
+
     /// Moves the given files to the archive and deletes the original files
     pub fn move_to_archive<'f, F>(&mut self, files: F) -> Result<(), io::Error>
     where
@@ -210,6 +244,17 @@ impl Archive<Archived> {
     }
 }
 
+/// Moves the given files into the ZIP-file at `location`, creating it if it doesn't exist
+pub fn move_into_zip(location: &Path, files: &[PathBuf]) -> Result<(), io::Error> {
+    if location.exists() {
+        Archive::<Archived>::init(location).move_to_archive(files.iter().map(PathBuf::as_path))
+    } else {
+        let mut archive = Archive::<UnArchived>::new(location);
+        files.iter().cloned().for_each(|f| archive.move_file(f));
+        archive.archive().map(|_| ())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +398,32 @@ mod tests {
     /// 3. Move f2 & f3 from dir_b to the archive in dir_a.
     /// 4. Check that the archive contains f1, f2, and f3 with correct name and content.
     /// 5. Check that f2 & f3 in dir_b are no longer there.
+    #[test]
+    fn test_remove_from_archive() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let files = [temp_dir.child("a.json"), temp_dir.child("b.json")];
+        create_file(&files[0], "a");
+        create_file(&files[1], "b");
+        let zip_path = temp_dir.child("my.zip");
+        move_into_zip(&zip_path, &files)?;
+
+        let mut archived = Archive::<Archived>::init(&zip_path);
+        assert_eq!(archived.remove_from_archive(&["a.json"])?, 1);
+        // The archive is replaced by the rewritten one, and no temporary archive is left behind
+        let names: Vec<_> = fs::read_dir(temp_dir.path())?
+            .map(|e| e.map(|e| e.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(names, ["my.zip"]);
+        let mut zip = zip::ZipArchive::new(fs::File::open(&zip_path)?)?;
+        assert_eq!(zip.len(), 1);
+        check_zipfile_name_and_contents("b.json", "b", &mut zip.by_index(0)?)?;
+
+        // The archive is removed when its last file is
+        assert_eq!(archived.remove_from_archive(&["b.json"])?, 0);
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 0);
+        Ok(())
+    }
+
     #[test]
     fn test_move_to_archive() {
         let dir_a = TempDir::new().expect("Failed to create temporary directory");